@@ -14,6 +14,10 @@ pub struct Response {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Event {
+    /// CloudWatch event ID, unique across all log streams of the function
+    #[serde(default)]
+    pub id: Option<String>,
+
     pub timestamp: i64,
     pub message: String,
 }
@@ -1,5 +1,6 @@
 use crate::project::Project;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -17,6 +18,66 @@ pub struct ProjectInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Kvdb {
     pub name: String,
+
+    /// Optional sort key of the table, e.g. `range_key = "sk"` in kinetics.toml
+    ///
+    /// When set the table gets a composite primary key: `id` as the hash key and this
+    /// attribute as the range key. Omitted from requests when not set, so that existing
+    /// single-key tables stay unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_key: Option<String>,
+
+    /// ARN of an existing table to use instead of creating one, e.g. shared by several projects
+    ///
+    /// The functions of the project are granted access to the table, its lifecycle is not
+    /// managed by kinetics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub existing_arn: Option<String>,
+
+    /// How read and write capacity of the table is paid for, e.g. `billing_mode = "PAY_PER_REQUEST"`
+    ///
+    /// Provisioned capacity is used when not set, so that existing tables stay unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billing_mode: Option<BillingMode>,
+
+    /// Global secondary indexes of the table, to query it by other attributes than the key
+    ///
+    /// Omitted from requests when empty, so that existing tables stay unchanged.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<KvdbIndex>,
+
+    /// Attribute with the expiration time of items, as Unix epoch seconds, e.g. `ttl_attribute = "expires_at"`
+    ///
+    /// Expired items are deleted by DynamoDB. TTL is disabled when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_attribute: Option<String>,
+}
+
+/// Global secondary index of a KV DB table, e.g. `[[kvdb.indexes]]` in kinetics.toml
+///
+/// All attributes of the items are projected into the index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KvdbIndex {
+    /// Name of the index, passed to `Query::index_name`
+    pub name: String,
+
+    /// Partition key of the index
+    pub hash_key: String,
+
+    /// Optional sort key of the index
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range_key: Option<String>,
+}
+
+/// Billing mode of a KV DB table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BillingMode {
+    /// Fixed read and write capacity
+    Provisioned,
+
+    /// Pay per request, without capacity planning, suited for spiky or idle tables
+    PayPerRequest,
 }
 
 impl From<ProjectInfo> for Project {
@@ -27,6 +88,13 @@ impl From<ProjectInfo> for Project {
             url: value.url,
             kvdb: value.kvdb,
             observability: None,
+            features: Vec::new(),
+            shared_environment: HashMap::new(),
+            embed_git: false,
+            git: None,
+            fifo_queues: Vec::new(),
+            not_found: None,
+            strict_access: false,
         }
     }
 }
@@ -2,14 +2,14 @@ use crate::api::request::Validate;
 use crate::{function::Function, project::Project};
 use kinetics_parser::{Params, Role};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 pub mod envs;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Request {
     pub is_hotswap: bool,
     pub project: Project,
-    pub secrets: HashMap<String, String>,
+    pub secrets: BTreeMap<String, String>,
     pub functions: Vec<FunctionRequest>,
     pub version_message: Option<String>,
 }
@@ -30,6 +30,10 @@ impl Validate for Request {
             }
         }
 
+        for function in &self.functions {
+            errors.extend(function.validate_policies());
+        }
+
         if let Some(observability) = &self.project.observability {
             if observability.dd_api_key.is_empty() {
                 errors.push(
@@ -52,7 +56,7 @@ pub struct FunctionRequest {
     pub name: String,
     pub role: Role,
     pub params: Params,
-    pub environment: HashMap<String, String>,
+    pub environment: BTreeMap<String, String>,
 }
 
 impl From<&Function> for FunctionRequest {
@@ -62,8 +66,47 @@ impl From<&Function> for FunctionRequest {
             is_deploying: f.is_deploying,
             params: f.params.clone(),
             role: f.role.clone(),
-            environment: f.environment(),
+            environment: f.environment().into_iter().collect(),
+        }
+    }
+}
+
+impl FunctionRequest {
+    /// Check custom IAM statements of the function, which are passed to IAM as is
+    fn validate_policies(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for (i, policy) in self.params.policies().iter().enumerate() {
+            if policy.actions.is_empty() || policy.actions.iter().any(|a| a.trim().is_empty()) {
+                errors.push(format!(
+                    "policy #{} of {} must have non-empty actions, e.g. \"ses:SendEmail\"",
+                    i + 1,
+                    self.name
+                ));
+            }
+
+            if let Some(action) = policy
+                .actions
+                .iter()
+                .find(|a| !a.trim().is_empty() && !a.contains(':') && a.as_str() != "*")
+            {
+                errors.push(format!(
+                    "policy #{} of {} has malformed action {action}, expected service:Action",
+                    i + 1,
+                    self.name
+                ));
+            }
+
+            if policy.resources.is_empty() || policy.resources.iter().any(|r| r.trim().is_empty()) {
+                errors.push(format!(
+                    "policy #{} of {} must have non-empty resources, e.g. \"*\"",
+                    i + 1,
+                    self.name
+                ));
+            }
         }
+
+        errors
     }
 }
 
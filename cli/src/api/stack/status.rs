@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Request {
@@ -9,4 +10,8 @@ pub struct Request {
 pub struct Response {
     pub status: String,
     pub errors: Option<Vec<String>>,
+
+    /// Outputs of the stack, e.g. URLs of the provisioned resources
+    #[serde(default)]
+    pub outputs: Option<HashMap<String, String>>,
 }
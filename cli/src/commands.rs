@@ -3,12 +3,14 @@ pub mod build;
 pub mod cicd;
 pub mod deploy;
 pub mod envs;
+pub mod export;
 pub mod func;
 pub mod init;
 pub mod invoke;
 pub mod login;
 pub mod migrations;
 pub mod proj;
+pub mod serve;
 use clap::Subcommand;
 
 #[derive(Subcommand)]
@@ -24,6 +26,10 @@ pub enum Commands {
     #[clap(subcommand)]
     Envs(envs::EnvsCommands),
 
+    /// Export project types to other languages
+    #[clap(subcommand)]
+    Export(export::ExportCommands),
+
     #[clap(subcommand)]
     Func(func::FuncCommands),
 
@@ -41,6 +47,9 @@ pub enum Commands {
     /// Invoke a function
     Invoke(invoke::InvokeCommand),
 
+    /// Serve all endpoints locally, at http://localhost:8080 by default
+    Serve(serve::ServeCommand),
+
     /// Deploy entire project or certain function(s)
     Deploy(deploy::DeployCommand),
 
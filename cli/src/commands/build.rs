@@ -12,6 +12,32 @@ pub(crate) struct BuildCommand {
     /// Comma-separated list of function names to build (if not specified, all functions will be built)
     #[arg(short, long, value_delimiter = ',')]
     pub(crate) functions: Vec<String>,
+
+    /// Comma-separated cargo features to enable on top of the default ones
+    #[arg(long, value_delimiter = ',')]
+    pub(crate) features: Vec<String>,
+
+    /// Regenerate all build files from scratch, ignoring the cached checksums.
+    ///
+    /// Slower, but deterministic. Useful when the cache gets stale, e.g. after a toolchain update.
+    #[arg(long)]
+    pub(crate) no_cache: bool,
+
+    /// Build inside a Docker container with a pinned toolchain, for reproducible builds.
+    ///
+    /// Falls back to the host toolchain when Docker is not available.
+    #[arg(long)]
+    pub(crate) docker_build: bool,
+
+    /// Print how long each phase of the build took, to find the bottleneck of slow builds
+    #[arg(long)]
+    pub(crate) profile_build: bool,
+
+    /// Print a plain line per stage instead of animated progress bars.
+    ///
+    /// Used automatically when the output is not a terminal, e.g. in CI.
+    #[arg(long)]
+    pub(crate) no_progress: bool,
 }
 
 impl Runnable for BuildCommand {
@@ -1,17 +1,25 @@
 use super::progress::{PipelineProgress, ProgressStatus};
 use crate::api::client::Client;
+use crate::api::stack;
 use crate::config::build_config;
-use crate::config::deploy::DeployConfig;
+use crate::config::deploy::{DeployConfig, DeployEvent};
+use crate::error::Error;
 use crate::function::{build, Function};
-use crate::project::Project;
+use crate::project::{DeployState, Project, Timings};
 use crate::writer::Writer;
-use eyre::{eyre, OptionExt, Report};
+use eyre::{eyre, OptionExt, Report, WrapErr};
 use futures::future;
+use std::io::{stdout, IsTerminal};
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tabled::settings::style::Style;
+use tabled::{Table, Tabled};
 use tokio::sync::Semaphore;
 
+/// Stop waiting for the stack after this long, unless set explicitly
+const DEFAULT_DEPLOY_TIMEOUT: Duration = Duration::from_secs(20 * 60);
+
 pub struct Pipeline<'a> {
     is_deploy_enabled: bool,
     is_hotswap: bool,
@@ -20,6 +28,20 @@ pub struct Pipeline<'a> {
     deploy_config: Option<Arc<dyn DeployConfig>>,
     writer: &'a Writer,
     version_message: Option<String>,
+    is_cache_disabled: bool,
+    is_waiting: bool,
+    is_docker_build: bool,
+    is_profiled: bool,
+    deploy_timeout: Duration,
+    is_plain_progress: bool,
+}
+
+#[derive(Tabled)]
+struct TimingRow {
+    #[tabled(rename = "Phase")]
+    phase: String,
+    #[tabled(rename = "Duration")]
+    duration: String,
 }
 
 impl<'a> Pipeline<'a> {
@@ -47,19 +69,47 @@ impl<'a> Pipeline<'a> {
 
         let start_time = Instant::now();
 
+        // Animated bars and control sequences only clutter the output which is not a terminal, e.g. CI logs
+        let is_plain_progress = self.is_plain_progress || !stdout().is_terminal();
+
         self.writer.text(&format!(
-            "{}...",
-            console::style("Preparing").green().bold()
+            "{}...{}",
+            console::style("Preparing").green().bold(),
+            if is_plain_progress { "\n" } else { "" }
         ))?;
 
+        let mut timings = Timings::default();
+
         // All functions to add to the template
-        let all_functions = self.project.parse(
+        let all_functions = self.project.parse_with_timings(
             PathBuf::from(build_config()?.kinetics_path),
             deploy_functions,
+            self.is_cache_disabled,
+            &mut timings,
         )?;
 
         // Clear the previous line, the "Preparing..." step is not a part of the build pipeline
-        self.writer.text("\r\x1B[K")?;
+        if !is_plain_progress {
+            self.writer.text("\r\x1B[K")?;
+        }
+
+        // Fail before building if the stack would exceed AWS quotas
+        if self.is_deploy_enabled {
+            let resources = Project::check_limits(&all_functions)?;
+
+            self.writer.text(&format!(
+                "    {} {}\n",
+                console::style("Resources").green().bold(),
+                console::style(resources.summary()).dim(),
+            ))?;
+
+            for warning in resources.warnings() {
+                self.writer.text(&format!(
+                    "    {} {warning}\n",
+                    console::style("Warning").yellow().bold(),
+                ))?;
+            }
+        }
 
         let deploy_functions: Vec<Function> = all_functions
             .iter()
@@ -71,6 +121,7 @@ impl<'a> Pipeline<'a> {
             deploy_functions.len() as u64 * if self.is_deploy_enabled { 1 } else { 0 },
             self.is_deploy_enabled,
             self.writer.is_structured(),
+            is_plain_progress,
         );
 
         let deploying_progress = pipeline_progress.new_progress(&self.project.name);
@@ -79,9 +130,25 @@ impl<'a> Pipeline<'a> {
             .new_progress(&self.project.name)
             .log_stage("Building");
 
-        build(&deploy_functions, &pipeline_progress.total_progress_bar).await?;
+        let build_started = Instant::now();
+
+        build(
+            &deploy_functions,
+            &pipeline_progress.total_progress_bar,
+            self.is_docker_build,
+        )
+        .await?;
+
+        // Cargo builds and zips all functions in a single run, so they can't be timed separately
+        timings.record("Cargo lambda build and zip", build_started);
         pipeline_progress.increase_current_function_position();
 
+        for function in &deploy_functions {
+            self.emit(DeployEvent::Built {
+                function: function.name.clone(),
+            });
+        }
+
         if !self.is_deploy_enabled {
             pipeline_progress.increase_current_function_position();
             pipeline_progress.total_progress_bar.finish_and_clear();
@@ -93,6 +160,10 @@ impl<'a> Pipeline<'a> {
                 start_time.elapsed().as_secs_f64(),
             ))?;
 
+            if self.is_profiled {
+                self.print_timings(&timings)?;
+            }
+
             return Ok(());
         }
 
@@ -103,6 +174,11 @@ impl<'a> Pipeline<'a> {
 
         let client = Client::new(self.deploy_config.is_some()).await?;
 
+        // Bundles confirmed by the last successful deployment, compared against by `proj local-diff`
+        let mut deploy_state = DeployState::new(
+            &PathBuf::from(build_config()?.kinetics_path).join(&self.project.name),
+        );
+
         let handles = deploy_functions.into_iter().map(|mut function| {
             let client = client.clone();
             let sem = Arc::clone(&semaphore);
@@ -116,6 +192,12 @@ impl<'a> Pipeline<'a> {
                 let function_progress = pipeline_progress.new_progress(&function.name);
                 function_progress.log_stage("Uploading");
 
+                // The bundle is removed after upload, calculate the checksum beforehand
+                let checksum = function.bundle_checksum().await.wrap_err(format!(
+                    "Failed to read the bundle of function: \"{}\"",
+                    function.name
+                ))?;
+
                 match function
                     .upload(&client, deploy_config_clone.as_deref())
                     .await
@@ -128,10 +210,29 @@ impl<'a> Pipeline<'a> {
                                 Some("No changes, skipped"),
                             );
                         }
+
+                        if let Some(config) = &deploy_config_clone {
+                            let function = function.name.clone();
+
+                            config.on_event(if updated {
+                                DeployEvent::Uploaded { function }
+                            } else {
+                                DeployEvent::UploadSkipped { function }
+                            });
+                        }
+
                         Ok(())
                     }
                     Err(e) => {
                         function_progress.error("Uploading");
+
+                        if let Some(config) = &deploy_config_clone {
+                            config.on_event(DeployEvent::UploadFailed {
+                                function: function.name.clone(),
+                                error: format!("{e:#}"),
+                            });
+                        }
+
                         Err(e.wrap_err(format!("Failed to upload function: \"{}\"", function.name)))
                     }
                 }?;
@@ -146,7 +247,7 @@ impl<'a> Pipeline<'a> {
                     );
                 };
 
-                Ok(())
+                Ok((function.name, checksum))
             })
         });
 
@@ -159,22 +260,35 @@ impl<'a> Pipeline<'a> {
             })
             .collect();
 
-        let (.., errors): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
+        let (uploaded, errors): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
 
         if !errors.is_empty() {
-            log::error!(
-                "Failed to process functions: {:?}",
-                errors
-                    .into_iter()
-                    .map(Result::unwrap_err)
-                    .collect::<Vec<_>>()
-            );
-
-            return Err(eyre!("Failed to process function(s)"));
+            // Each error is attributed to its function, print them one by one
+            // rather than as a single debug dump of all reports
+            let errors = errors
+                .into_iter()
+                .map(Result::unwrap_err)
+                .map(|error| format!("{error:#}"))
+                .collect::<Vec<_>>();
+
+            log::error!("Failed to process functions: {errors:?}");
+
+            return Err(eyre!(
+                "Failed to process function(s):\n\n{}",
+                errors.join("\n")
+            ));
         }
 
+        let uploaded = uploaded
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<(String, String)>>();
+
+        // Both waiting for a previous deployment and for this one count towards the timeout
+        let deadline = Instant::now() + self.deploy_timeout;
+
         // Check if there's an ongoing deployment and wait for it to finish
-        let mut status = self.project.status().await?;
+        let status = self.project.status().await?;
         log::debug!("Pipeline status: {:?}", status.status);
         deploying_progress.log_stage("Provisioning");
 
@@ -184,9 +298,10 @@ impl<'a> Pipeline<'a> {
                     .total_progress_bar
                     .set_message("Waiting for previous deployment to finish...");
 
-                while status.status == "IN_PROGRESS" {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-                    status = self.project.status().await?;
+                if let Err(err) = self.wait_for_stack(status, deadline).await {
+                    deploying_progress.error("Provisioning");
+                    pipeline_progress.total_progress_bar.finish_and_clear();
+                    return Err(err);
                 }
             }
             "FROZEN" => {
@@ -196,6 +311,8 @@ impl<'a> Pipeline<'a> {
             _ => {}
         }
 
+        self.emit(DeployEvent::ProvisioningStarted);
+
         pipeline_progress
             .total_progress_bar
             .set_message(if deploy_functions_len >= 5 {
@@ -210,7 +327,7 @@ impl<'a> Pipeline<'a> {
                 &all_functions,
                 self.is_hotswap,
                 self.deploy_config.as_deref(),
-                self.version_message,
+                self.version_message.clone(),
             )
             .await
         {
@@ -220,17 +337,42 @@ impl<'a> Pipeline<'a> {
                     ProgressStatus::Warn,
                     Some("Nothing to update"),
                 );
+
+                self.emit(DeployEvent::ProvisioningSkipped);
+            }
+            Ok(_) if !self.is_waiting => {
+                // Deployment is only initiated, failures are reported by the status check
+                deploying_progress.finish("Provisioning", ProgressStatus::Success, Some("Started"));
+
+                pipeline_progress.total_progress_bar.finish_and_clear();
+
+                self.writer.text(&format!(
+                    "    {} deploying {} in {:.2}s, check the progress with `kinetics proj status`\n",
+                    console::style("Started").green().bold(),
+                    console::style(&self.project.name).bold(),
+                    start_time.elapsed().as_secs_f64(),
+                ))?;
+
+                return Ok(());
             }
             Ok(_) => {
                 // Wait for stack deployment if it is updated.
                 deploying_progress.progress_bar.finish_and_clear();
-                let mut status = self.project.status().await?;
+                let status = self.project.status().await?;
 
-                // Poll the status of the deployment
-                while status.status == "IN_PROGRESS" {
-                    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
-                    status = self.project.status().await?;
-                }
+                let status = match self.wait_for_stack(status, deadline).await {
+                    Ok(status) => status,
+                    Err(err) => {
+                        deploying_progress.error("Provisioning");
+                        pipeline_progress.total_progress_bar.finish_and_clear();
+
+                        self.emit(DeployEvent::ProvisioningFailed {
+                            error: format!("{err:#}"),
+                        });
+
+                        return Err(err);
+                    }
+                };
 
                 if matches!(status.status.as_str(), "FAILED" | "FROZEN") {
                     deploying_progress.error("Provisioning");
@@ -246,16 +388,41 @@ impl<'a> Pipeline<'a> {
                         .map(|errors| errors.join("\n"))
                         .unwrap_or("Unknown error".into());
 
+                    self.emit(DeployEvent::ProvisioningFailed {
+                        error: error_text.clone(),
+                    });
+
                     return Err(eyre!("{error_text}"));
                 }
             }
             Err(err) => {
                 deploying_progress.error("Provisioning");
                 pipeline_progress.total_progress_bar.finish_and_clear();
+
+                self.emit(DeployEvent::ProvisioningFailed {
+                    error: format!("{err:#}"),
+                });
+
                 return Err(err);
             }
         };
 
+        // The stack is provisioned, so the uploaded bundles are the deployed ones now
+        for (name, checksum) in &uploaded {
+            deploy_state.confirm(name, checksum);
+        }
+
+        // A full update removes the functions removed from the project from the stack as well
+        if !self.is_hotswap {
+            deploy_state.retain(all_functions.iter().map(|f| f.name.as_str()));
+        }
+
+        if let Err(error) = deploy_state.save() {
+            log::error!("Failed to save the deploy state: {error:?}");
+        }
+
+        self.emit(DeployEvent::ProvisioningFinished);
+
         pipeline_progress.increase_current_function_position();
         pipeline_progress.total_progress_bar.finish_and_clear();
 
@@ -267,6 +434,70 @@ impl<'a> Pipeline<'a> {
 
         Ok(())
     }
+
+    /// Poll the status of the stack until it is no longer in progress
+    ///
+    /// Gives up at the deadline, so that CI jobs don't hang on a stuck stack.
+    /// The deployment itself is not cancelled, only the waiting stops.
+    async fn wait_for_stack(
+        &self,
+        mut status: stack::status::Response,
+        deadline: Instant,
+    ) -> eyre::Result<stack::status::Response> {
+        while status.status == "IN_PROGRESS" {
+            if Instant::now() >= deadline {
+                let errors = status
+                    .errors
+                    .filter(|errors| !errors.is_empty())
+                    .map(|errors| format!("\n\nRecent errors:\n{}", errors.join("\n")))
+                    .unwrap_or_default();
+
+                return Err(Error::new(
+                    &format!(
+                        "Gave up waiting for the deployment after {}",
+                        humantime::format_duration(self.deploy_timeout)
+                    ),
+                    Some(&format!(
+                        "The stack is still {}, the deployment keeps going. \
+                        Check it with `kinetics proj status`.{errors}",
+                        status.status
+                    )),
+                )
+                .into());
+            }
+
+            tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+            status = self.project.status().await?;
+        }
+
+        Ok(status)
+    }
+
+    /// Print the durations of the build phases, slowest first
+    fn print_timings(&self, timings: &Timings) -> eyre::Result<()> {
+        let mut phases = timings.phases().to_vec();
+        phases.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        let rows = phases
+            .into_iter()
+            .map(|(phase, duration)| TimingRow {
+                phase,
+                duration: format!("{:.2}s", duration.as_secs_f64()),
+            })
+            .collect::<Vec<_>>();
+
+        let mut table = Table::new(rows);
+        table.with(Style::modern());
+        self.writer.text(&format!("\n{table}\n"))?;
+        Ok(())
+    }
+
+    /// Report the event to the custom deployment configuration, if any
+    fn emit(&self, event: DeployEvent) {
+        if let Some(config) = &self.deploy_config {
+            config.on_event(event);
+        }
+    }
 }
 
 #[derive(Default)]
@@ -278,6 +509,12 @@ pub struct PipelineBuilder<'a> {
     deploy_config: Option<Arc<dyn DeployConfig>>,
     writer: &'a Writer,
     version_message: Option<String>,
+    is_cache_disabled: Option<bool>,
+    is_waiting: Option<bool>,
+    is_docker_build: Option<bool>,
+    is_profiled: Option<bool>,
+    deploy_timeout: Option<Duration>,
+    is_plain_progress: Option<bool>,
 }
 
 impl<'a> PipelineBuilder<'a> {
@@ -292,6 +529,12 @@ impl<'a> PipelineBuilder<'a> {
             deploy_config: self.deploy_config,
             writer: self.writer,
             version_message: self.version_message,
+            is_cache_disabled: self.is_cache_disabled.unwrap_or(false),
+            is_waiting: self.is_waiting.unwrap_or(true),
+            is_docker_build: self.is_docker_build.unwrap_or(false),
+            is_profiled: self.is_profiled.unwrap_or(false),
+            deploy_timeout: self.deploy_timeout.unwrap_or(DEFAULT_DEPLOY_TIMEOUT),
+            is_plain_progress: self.is_plain_progress.unwrap_or(false),
         })
     }
 
@@ -319,4 +562,39 @@ impl<'a> PipelineBuilder<'a> {
         self.version_message = message;
         self
     }
+
+    pub fn with_cache_disabled(mut self, is_cache_disabled: bool) -> Self {
+        self.is_cache_disabled = Some(is_cache_disabled);
+        self
+    }
+
+    /// Build functions inside a Docker container with a pinned toolchain
+    pub fn with_docker_build(mut self, is_docker_build: bool) -> Self {
+        self.is_docker_build = Some(is_docker_build);
+        self
+    }
+
+    /// Print the durations of the build phases in the end
+    pub fn with_profile(mut self, is_profiled: bool) -> Self {
+        self.is_profiled = Some(is_profiled);
+        self
+    }
+
+    /// Print a plain line per stage instead of animated progress bars, even in a terminal
+    pub fn with_plain_progress(mut self, is_plain_progress: bool) -> Self {
+        self.is_plain_progress = Some(is_plain_progress);
+        self
+    }
+
+    /// Wait for the deployment to finish, otherwise return as soon as it is started
+    pub fn with_wait(mut self, is_waiting: bool) -> Self {
+        self.is_waiting = Some(is_waiting);
+        self
+    }
+
+    /// Stop waiting for the stack to be provisioned after the given time
+    pub fn with_deploy_timeout(mut self, deploy_timeout: Duration) -> Self {
+        self.deploy_timeout = Some(deploy_timeout);
+        self
+    }
 }
@@ -1,6 +1,5 @@
 use crate::logger::Logger;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::io::{stdout, IsTerminal};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
@@ -10,16 +9,30 @@ pub(super) struct PipelineProgress<'a> {
     pub(super) total_progress_bar: ProgressBar,
     completed_functions_count: Arc<AtomicUsize>,
     is_disabled: bool,
+
+    /// Print a plain line per stage instead of animated bars, e.g. in CI logs
+    is_plain: bool,
 }
 
 impl<'a> PipelineProgress<'a> {
-    pub(super) fn new(total_functions: u64, is_deploy: bool, is_disabled: bool) -> Self {
+    pub(super) fn new(
+        total_functions: u64,
+        is_deploy: bool,
+        is_disabled: bool,
+
+        // Print a plain line per stage instead of animated bars
+        is_plain: bool,
+    ) -> Self {
         let multi_progress = Logger::multi_progress();
         let completed_functions_count = Arc::new(AtomicUsize::new(0));
 
         // +1 for provisioning phase
         // +1 for build phase
-        let total_progress_bar = multi_progress.add(ProgressBar::new(total_functions + 2));
+        let total_progress_bar = if is_plain {
+            ProgressBar::hidden()
+        } else {
+            multi_progress.add(ProgressBar::new(total_functions + 2))
+        };
 
         total_progress_bar.set_style(
             ProgressStyle::default_bar()
@@ -48,6 +61,7 @@ impl<'a> PipelineProgress<'a> {
             total_progress_bar,
             completed_functions_count,
             is_disabled,
+            is_plain,
         }
     }
 
@@ -65,6 +79,7 @@ impl<'a> PipelineProgress<'a> {
             &self.total_progress_bar,
             resource_name,
             self.is_disabled,
+            self.is_plain,
         )
     }
 }
@@ -73,10 +88,10 @@ pub struct Progress {
     pub progress_bar: ProgressBar,
     resource_name: String,
     is_disabled: bool,
+    is_plain: bool,
 }
 
 pub(super) enum ProgressStatus {
-    #[allow(dead_code)]
     Success,
     Warn,
     Error,
@@ -88,9 +103,13 @@ impl Progress {
         total_progress_bar: &ProgressBar,
         function_name: &str,
         is_disabled: bool,
+        is_plain: bool,
     ) -> Self {
-        let function_progress_bar =
-            multi_progress.insert_before(total_progress_bar, ProgressBar::new_spinner());
+        let function_progress_bar = if is_plain {
+            ProgressBar::hidden()
+        } else {
+            multi_progress.insert_before(total_progress_bar, ProgressBar::new_spinner())
+        };
 
         function_progress_bar.set_style(ProgressStyle::with_template("{msg}").unwrap());
 
@@ -98,6 +117,7 @@ impl Progress {
             progress_bar: function_progress_bar,
             resource_name: function_name.to_string(),
             is_disabled,
+            is_plain,
         }
     }
 
@@ -112,13 +132,11 @@ impl Progress {
             self.resource_name,
         );
 
-        // Terminal or CI/CD?
-        if stdout().is_terminal() {
-            self.progress_bar.println(msg);
+        // Hidden bars don't print anything, e.g. in CI/CD
+        if self.is_plain {
+            println!("{msg}");
         } else {
-            self.progress_bar.suspend(|| {
-                println!("{msg}");
-            });
+            self.progress_bar.println(msg);
         }
     }
 
@@ -134,8 +152,13 @@ impl Progress {
             ProgressStatus::Error => stage.red(),
         };
         let message = message.map(|m| format!(": {m}")).unwrap_or_default();
-        self.progress_bar
-            .finish_with_message(format!("{} {}{}", stage, self.resource_name, message));
+        let message = format!("{} {}{}", stage, self.resource_name, message);
+
+        if self.is_plain {
+            println!("{message}");
+        } else {
+            self.progress_bar.finish_with_message(message);
+        }
     }
 
     pub(super) fn error(&self, stage: &str) {
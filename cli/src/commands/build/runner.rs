@@ -14,10 +14,14 @@ pub(crate) struct BuildRunner<'a> {
 impl Runner for BuildRunner<'_> {
     /// Build one or more functions
     async fn run(&mut self) -> Result<(), Error> {
-        let project = self.project().await?;
+        let project = self.project().await?.with_features(&self.command.features);
 
         Pipeline::builder(self.writer)
             .with_deploy_enabled(false)
+            .with_cache_disabled(self.command.no_cache)
+            .with_docker_build(self.command.docker_build)
+            .with_profile(self.command.profile_build)
+            .with_plain_progress(self.command.no_progress)
             .set_project(project)
             .build()
             .wrap_err("Failed to build pipeline")?
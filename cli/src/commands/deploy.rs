@@ -3,6 +3,7 @@ use crate::runner::{Runnable, Runner};
 use crate::writer::Writer;
 use clap::ArgAction;
 use runner::DeployRunner;
+use std::time::Duration;
 
 #[derive(clap::Args, Clone)]
 pub(crate) struct DeployCommand {
@@ -25,6 +26,42 @@ pub(crate) struct DeployCommand {
     /// Message to include in the deployment (max 100 characters)
     #[arg(short, long)]
     message: Option<String>,
+
+    /// Comma-separated cargo features to enable on top of the default ones
+    #[arg(long, value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Regenerate all build files from scratch, ignoring the cached checksums.
+    ///
+    /// Slower, but deterministic. Useful when the cache gets stale, e.g. after a toolchain update.
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_cache: bool,
+
+    /// Build inside a Docker container with a pinned toolchain, for reproducible builds.
+    ///
+    /// Falls back to the host toolchain when Docker is not available.
+    #[arg(long, action = ArgAction::SetTrue)]
+    docker_build: bool,
+
+    /// Wait for the deployment to finish.
+    ///
+    /// With `--wait=false` the command returns as soon as the deployment is started.
+    /// Deployment failures are not reported then, check them with `kinetics proj status`.
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    wait: bool,
+
+    /// Stop waiting for the deployment after this long, e.g. `45m`. Defaults to 20 minutes.
+    ///
+    /// The command fails then, but the deployment itself keeps going.
+    /// Check it later with `kinetics proj status`.
+    #[arg(long, value_parser = humantime::parse_duration)]
+    deploy_timeout: Option<Duration>,
+
+    /// Print a plain line per stage instead of animated progress bars.
+    ///
+    /// Used automatically when the output is not a terminal, e.g. in CI.
+    #[arg(long, action = ArgAction::SetTrue)]
+    no_progress: bool,
 }
 
 impl Runnable for DeployCommand {
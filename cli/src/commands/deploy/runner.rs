@@ -21,7 +21,7 @@ pub(crate) struct DeployRunner<'a> {
 impl Runner for DeployRunner<'_> {
     /// Invoke the function either locally or remotely
     async fn run(&mut self) -> Result<(), Error> {
-        let project = Project::from_current_dir()?;
+        let project = Project::from_current_dir()?.with_features(&self.command.features);
 
         // DataDog API key only needed during deployment, to send it to the backend
         if project
@@ -61,6 +61,7 @@ impl DeployRunner<'_> {
             .parse(
                 PathBuf::from(build_config()?.kinetics_path),
                 &self.command.functions,
+                self.command.no_cache,
             )?
             .iter()
             .filter(|f| f.is_deploying)
@@ -146,12 +147,22 @@ impl DeployRunner<'_> {
 
     /// Do full deployment of requested functions
     async fn deploy_all(&self, project: Project) -> eyre::Result<()> {
-        Pipeline::builder(self.writer)
+        let mut builder = Pipeline::builder(self.writer)
             .set_max_concurrent(self.command.max_concurrency)
             .with_deploy_enabled(true)
             .with_hotswap(self.command.hotswap)
             .with_version_message(self.command.message.clone())
-            .set_project(project)
+            .with_cache_disabled(self.command.no_cache)
+            .with_docker_build(self.command.docker_build)
+            .with_wait(self.command.wait)
+            .with_plain_progress(self.command.no_progress)
+            .set_project(project);
+
+        if let Some(deploy_timeout) = self.command.deploy_timeout {
+            builder = builder.with_deploy_timeout(deploy_timeout);
+        }
+
+        builder
             .build()
             .wrap_err("Failed to build pipeline")?
             .run(&self.command.functions)
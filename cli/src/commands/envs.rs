@@ -1,5 +1,7 @@
+pub mod diff;
 pub mod list;
 
+use crate::commands::envs::diff::DiffCommand;
 use crate::commands::envs::list::ListCommand;
 use clap::Subcommand;
 
@@ -7,4 +9,7 @@ use clap::Subcommand;
 pub(crate) enum EnvsCommands {
     /// List all environment variables for all functions
     List(ListCommand),
+
+    /// Compare env vars in the code with the deployed ones
+    Diff(DiffCommand),
 }
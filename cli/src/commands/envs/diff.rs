@@ -0,0 +1,154 @@
+use super::list::{local, remote};
+use crate::error::Error;
+use crate::runner::{Runnable, Runner};
+use crate::writer::Writer;
+use crossterm::style::Stylize;
+use kinetics_parser::Parser;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+
+/// Env vars with this prefix are managed by kinetics, e.g. git metadata changes with every commit
+const MANAGED_PREFIX: &str = "KINETICS_";
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct DiffCommand {}
+
+impl Runnable for DiffCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        DiffRunner { writer }
+    }
+}
+
+struct DiffRunner<'a> {
+    writer: &'a Writer,
+}
+
+/// Difference between env vars of a function declared locally and deployed
+#[derive(Debug, Default, Serialize)]
+struct Drift {
+    /// Declared locally but not deployed, will be added on deploy
+    added: BTreeMap<String, String>,
+
+    /// Deployed but not declared locally, e.g. set in the console, will be removed on deploy
+    removed: BTreeMap<String, String>,
+
+    /// Var name -> (deployed value, local value)
+    changed: BTreeMap<String, (String, String)>,
+}
+
+impl Drift {
+    fn new(local: &HashMap<String, String>, remote: &HashMap<String, String>) -> Self {
+        let mut drift = Drift::default();
+        let is_user_var = |name: &&String| !name.starts_with(MANAGED_PREFIX);
+
+        for (name, value) in local.iter().filter(|(name, _)| is_user_var(name)) {
+            match remote.get(name) {
+                None => {
+                    drift.added.insert(name.clone(), value.clone());
+                }
+                Some(deployed) if deployed != value => {
+                    drift
+                        .changed
+                        .insert(name.clone(), (deployed.clone(), value.clone()));
+                }
+                Some(_) => {}
+            }
+        }
+
+        for (name, value) in remote.iter().filter(|(name, _)| is_user_var(name)) {
+            if !local.contains_key(name) {
+                drift.removed.insert(name.clone(), value.clone());
+            }
+        }
+
+        drift
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Runner for DiffRunner<'_> {
+    /// Compare env vars declared in the code with the deployed ones
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?;
+
+        let parsed_functions = Parser::new(Some(&project.path), &project.features)
+            .map_err(|e| self.error(None, None, Some(e.into())))?
+            .functions;
+
+        self.writer.text(&format!(
+            "\n{}...\n\n",
+            console::style("Comparing local and deployed env vars")
+                .green()
+                .bold()
+        ))?;
+
+        let local_envs = local(&project)
+            .await
+            .map_err(|e| self.error(None, None, Some(e.into())))?;
+
+        let remote_envs = remote(&project, &parsed_functions)
+            .await
+            .map_err(|e| self.server_error(Some(e.into())))?;
+
+        let empty = HashMap::new();
+        let mut drifts = BTreeMap::new();
+
+        // Only deployed functions are compared, the rest have nothing to drift from
+        for (function_name, deployed) in &remote_envs {
+            let declared = local_envs.get(function_name).unwrap_or(&empty);
+            let drift = Drift::new(declared, deployed);
+
+            if !drift.is_empty() {
+                drifts.insert(function_name.clone(), drift);
+            }
+        }
+
+        if drifts.is_empty() {
+            self.writer
+                .text(&format!("{}\n", "Deployed env vars match the code".green()))?;
+
+            self.writer.json(json!({"success": true, "drift": {}}))?;
+            return Ok(());
+        }
+
+        for (function_name, drift) in &drifts {
+            self.writer
+                .text(&format!("{}\n", function_name.as_str().bold()))?;
+
+            for (name, value) in &drift.added {
+                self.writer.text(&format!(
+                    "{} {name}={value} {}\n",
+                    "+".green(),
+                    "(not deployed)".dim()
+                ))?;
+            }
+
+            for (name, value) in &drift.removed {
+                self.writer.text(&format!(
+                    "{} {name}={value} {}\n",
+                    "-".red(),
+                    "(not in code, removed on next deploy)".dim()
+                ))?;
+            }
+
+            for (name, (deployed, declared)) in &drift.changed {
+                self.writer.text(&format!(
+                    "{} {name}={deployed} {} {declared}\n",
+                    "~".yellow(),
+                    "->".dim()
+                ))?;
+            }
+
+            self.writer.text("\n")?;
+        }
+
+        self.writer
+            .json(json!({"success": true, "drift": drifts}))?;
+
+        Ok(())
+    }
+}
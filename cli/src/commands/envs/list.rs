@@ -37,7 +37,7 @@ impl Runner for ListRunner<'_> {
     /// Lists all environment variables for all functions in the current crate
     async fn run(&mut self) -> Result<(), Error> {
         let project = self.project().await?;
-        let parsed_functions = Parser::new(Some(&project.path))
+        let parsed_functions = Parser::new(Some(&project.path), &project.features)
             .map_err(|e| self.error(None, None, Some(e.into())))?
             .functions;
 
@@ -117,7 +117,7 @@ impl Runner for ListRunner<'_> {
 }
 
 /// Gets environment variables from the backend
-async fn remote(
+pub(super) async fn remote(
     project: &Project,
     functions: &[ParsedFunction],
 ) -> eyre::Result<HashMap<String, HashMap<String, String>>> {
@@ -155,8 +155,10 @@ async fn remote(
 }
 
 /// Gets environment variables from local configuration
-async fn local(project: &Project) -> eyre::Result<HashMap<String, HashMap<String, String>>> {
-    let functions = project.parse(PathBuf::from(build_config()?.kinetics_path), &[])?;
+pub(super) async fn local(
+    project: &Project,
+) -> eyre::Result<HashMap<String, HashMap<String, String>>> {
+    let functions = project.parse(PathBuf::from(build_config()?.kinetics_path), &[], false)?;
     let mut result = HashMap::new();
 
     for function in functions {
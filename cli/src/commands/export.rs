@@ -0,0 +1,9 @@
+mod typescript;
+use crate::commands::export::typescript::TypescriptCommand;
+use clap::Subcommand;
+
+#[derive(Subcommand)]
+pub(crate) enum ExportCommands {
+    /// Generate TypeScript definitions of endpoint request and response types
+    Typescript(TypescriptCommand),
+}
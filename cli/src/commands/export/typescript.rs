@@ -0,0 +1,56 @@
+use crate::error::Error;
+use crate::runner::{Runnable, Runner};
+use crate::writer::Writer;
+use eyre::Context;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct TypescriptCommand {
+    /// File to write the definitions to, e.g. frontend/src/api.d.ts
+    file: PathBuf,
+}
+
+impl Runnable for TypescriptCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        TypescriptRunner {
+            command: self.clone(),
+            writer,
+        }
+    }
+}
+
+struct TypescriptRunner<'a> {
+    command: TypescriptCommand,
+    writer: &'a Writer,
+}
+
+impl Runner for TypescriptRunner<'_> {
+    /// Generate TypeScript definitions of the types listed in `types` of endpoints
+    ///
+    /// Works with the source code only, so no login is required.
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?;
+
+        let content = project
+            .typescript()
+            .map_err(|e| self.error(Some("Failed to export"), Some(&e.to_string()), None))?;
+
+        std::fs::write(&self.command.file, content)
+            .wrap_err(format!("Failed to write {:?}", self.command.file))
+            .map_err(|e| self.error(None, None, Some(e.into())))?;
+
+        self.writer.text(&format!(
+            "{} {}\n",
+            console::style("Exported TypeScript definitions")
+                .green()
+                .bold(),
+            console::style(format!("to {}", self.command.file.display())).dim(),
+        ))?;
+
+        self.writer
+            .json(json!({"success": true, "file": self.command.file}))?;
+
+        Ok(())
+    }
+}
@@ -17,6 +17,8 @@ use terminal_size::{terminal_size, Height as TerminalHeight, Width as TerminalWi
 struct EndpointRow {
     #[tabled(rename = "Function")]
     function: String,
+    #[tabled(rename = "Description")]
+    description: String,
     #[tabled(rename = "Environment")]
     environment: String,
     #[tabled(rename = "Url Path")]
@@ -29,6 +31,8 @@ struct EndpointRow {
 struct CronRow {
     #[tabled(rename = "Function")]
     function: String,
+    #[tabled(rename = "Description")]
+    description: String,
     #[tabled(rename = "Environment")]
     environment: String,
     #[tabled(rename = "Schedule")]
@@ -41,6 +45,8 @@ struct CronRow {
 struct WorkerRow {
     #[tabled(rename = "Function")]
     function: String,
+    #[tabled(rename = "Description")]
+    description: String,
     #[tabled(rename = "Environment")]
     environment: String,
     #[tabled(rename = "FIFO")]
@@ -55,7 +61,7 @@ struct WorkerRow {
 pub(crate) struct ListCommand {
     /// Show detailed information for each function
     #[arg(short, long)]
-    verbose: bool,
+    details: bool,
 }
 
 impl Runnable for ListCommand {
@@ -83,21 +89,21 @@ impl Runner for ListRunner<'_> {
         // If the method is called within other method, then the auth error won't be propogated
         let client = self.api_client().await?;
 
-        self.functions = Parser::new(Some(&project.path))
+        self.functions = Parser::new(Some(&project.path), &project.features)
             .wrap_err("Failed to parse the project")
             .map_err(|e| self.error(None, None, Some(e.into())))?
             .functions;
 
-        if !self.command.verbose {
+        if !self.command.details {
             return self
                 .simple()
                 .wrap_err("Failed to output the simple list")
                 .map_err(|e| self.error(None, None, Some(e.into())));
         }
 
-        self.verbose(&client)
+        self.details(&client)
             .await
-            .wrap_err("Failed to output the verbose list")
+            .wrap_err("Failed to output the detailed list")
             .map_err(|e| self.error(None, None, Some(e.into())))?;
 
         Ok(())
@@ -157,6 +163,10 @@ impl ListRunner<'_> {
                 "path": &f.relative_path,
             });
 
+            if let Some(description) = f.params.description() {
+                entry["description"] = json!(description);
+            }
+
             if let Params::Cron(ref params) = f.params {
                 entry["schedule"] = json!(params.schedule.to_string());
             }
@@ -171,7 +181,7 @@ impl ListRunner<'_> {
         Ok(())
     }
 
-    async fn verbose(&mut self, client: &Client) -> eyre::Result<()> {
+    async fn details(&mut self, client: &Client) -> eyre::Result<()> {
         let project = self.project().await?;
         let project_base_url = Project::fetch_one(&project.name).await?.url;
         let mut endpoint_rows = Vec::new();
@@ -203,18 +213,30 @@ impl ListRunner<'_> {
 
             let func_path = parsed_function.relative_path;
 
+            let description = parsed_function
+                .params
+                .description()
+                .cloned()
+                .unwrap_or_default();
+
             match parsed_function.params {
                 Params::Endpoint(params) => {
                     endpoint_rows.push(EndpointRow {
                         function: format_function_and_path(&function.name, &func_path),
+                        description: description.clone(),
                         environment: format_environment(&format!("{:?}", params.environment)),
-                        url_path: format!("{}{}", project_base_url, params.url_path),
+                        url_path: params
+                            .url_paths()
+                            .map(|url_path| format!("{project_base_url}{url_path}"))
+                            .collect::<Vec<_>>()
+                            .join(", "),
                         last_modified,
                     });
                 }
                 Params::Cron(params) => {
                     cron_rows.push(CronRow {
                         function: format_function_and_path(&function.name, &func_path),
+                        description: description.clone(),
                         environment: format_environment(&format!("{:?}", params.environment)),
                         schedule: params.schedule.to_string(),
                         last_modified,
@@ -223,6 +245,7 @@ impl ListRunner<'_> {
                 Params::Worker(params) => {
                     worker_rows.push(WorkerRow {
                         function: format_function_and_path(&function.name, &func_path),
+                        description: description.clone(),
                         environment: format_environment(&format!("{:?}", params.environment)),
                         fifo: format!("{:?}", params.fifo),
                         concurrency: format!("{:?}", params.concurrency),
@@ -271,6 +294,7 @@ impl ListRunner<'_> {
             functions_json.push(json!({
                 "role": "endpoint",
                 "function": &row.function,
+                "description": &row.description,
                 "environment": &row.environment,
                 "url_path": &row.url_path,
                 "last_modified": &row.last_modified,
@@ -281,6 +305,7 @@ impl ListRunner<'_> {
             functions_json.push(json!({
                 "role": "cron",
                 "function": &row.function,
+                "description": &row.description,
                 "environment": &row.environment,
                 "schedule": &row.schedule,
                 "last_modified": &row.last_modified,
@@ -291,6 +316,7 @@ impl ListRunner<'_> {
             functions_json.push(json!({
                 "role": "worker",
                 "function": &row.function,
+                "description": &row.description,
                 "environment": &row.environment,
                 "fifo": &row.fifo,
                 "concurrency": &row.concurrency,
@@ -315,6 +341,12 @@ impl ListRunner<'_> {
             ))
             .map_err(|e| eyre::eyre!(e))?;
 
+        if let Some(description) = function.params.description() {
+            self.writer
+                .text(&format!("{}\n", description.dimmed()))
+                .map_err(|e| eyre::eyre!(e))?;
+        }
+
         match function.params.clone() {
             Params::Endpoint(_) => {}
             Params::Cron(params) => {
@@ -1,18 +1,32 @@
+use crate::api::client::Client;
 use crate::api::func;
 use crate::error::Error;
 use crate::function::Function;
+use crate::project::Project;
 use crate::runner::{Runnable, Runner};
 use crate::writer::Writer;
 use chrono::{DateTime, Utc};
 use eyre::Context;
 use kinetics_parser::Parser;
 use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How often new logs are polled in follow mode
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Logs are polled for this trailing window, to catch events delivered by CloudWatch with a delay
+const FOLLOW_WINDOW: Duration = Duration::from_secs(60);
 
 #[derive(clap::Args, Clone)]
 pub(crate) struct LogsCommand {
     /// Function name to retrieve logs for
-    #[arg()]
-    name: String,
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    name: Option<String>,
+
+    /// Retrieve logs of all functions in the project, interleaved by time
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    all: bool,
 
     /// Time period to get logs for.
     ///
@@ -24,6 +38,10 @@ pub(crate) struct LogsCommand {
     ///
     #[arg(short, long)]
     period: Option<String>,
+
+    /// Keep printing new logs as they arrive, until Ctrl-C
+    #[arg(short, long)]
+    follow: bool,
 }
 
 impl Runnable for LogsCommand {
@@ -40,43 +58,21 @@ struct LogsRunner<'a> {
     writer: &'a Writer,
 }
 
-impl Runner for LogsRunner<'_> {
-    /// Retrieves and displays logs for a specific function
-    async fn run(&mut self) -> Result<(), Error> {
-        let project = self.project().await?;
-
-        // Get all function names without any additional manipulations.
-        let all_functions = Parser::new(Some(&project.path))
-            .map_err(|e| self.error(None, None, Some(e.into())))?
-            .functions
-            .into_iter()
-            .map(|f| Function::new(&project, &f))
-            .collect::<eyre::Result<Vec<Function>>>()
-            .map_err(|e| self.error(None, None, Some(e.into())))?;
-
-        let function = Function::find_by_name(&all_functions, &self.command.name).map_err(|e| {
-            self.error(
-                Some("Could not find requested function"),
-                None,
-                Some(e.into()),
-            )
-        })?;
-
-        let client = self.api_client().await?;
-
-        self.writer.text(&format!(
-            "\n{} {} {}...\n\n",
-            console::style("Fetching logs").bold().green(),
-            console::style("for").dim(),
-            console::style(&function.name).bold()
-        ))?;
-
+impl LogsRunner<'_> {
+    /// Fetch log events of a single function
+    async fn fetch(
+        &self,
+        client: &Client,
+        project: &Project,
+        function: &Function,
+        period: Option<String>,
+    ) -> Result<Vec<func::logs::Event>, Error> {
         let response = client
             .post("/function/logs")
             .json(&func::logs::Request {
                 project_name: project.name.clone(),
                 function_name: function.name.clone(),
-                period: self.command.period.to_owned(),
+                period,
             })
             .send()
             .await
@@ -96,36 +92,210 @@ impl Runner for LogsRunner<'_> {
             .wrap_err("Invalid response from server")
             .map_err(|e| self.error(None, None, Some(e.into())))?;
 
-        if logs_response.events.is_empty() {
+        Ok(logs_response.events)
+    }
+
+    /// Fetch log events of all the functions, merged into a single timeline
+    async fn fetch_all<'f>(
+        &self,
+        client: &Client,
+        project: &Project,
+        functions: &'f [Function],
+        period: Option<String>,
+    ) -> Result<Vec<(&'f Function, func::logs::Event)>, Error> {
+        let results = futures::future::try_join_all(
+            functions
+                .iter()
+                .map(|function| self.fetch(client, project, function, period.clone())),
+        )
+        .await?;
+
+        let mut events = functions
+            .iter()
+            .zip(results)
+            .flat_map(|(function, events)| events.into_iter().map(move |event| (function, event)))
+            .collect::<Vec<_>>();
+
+        events.sort_by_key(|(_, event)| event.timestamp);
+        Ok(events)
+    }
+
+    /// Format a log event as a line to print, None if the event is invalid
+    fn line(&self, function: &Function, event: &func::logs::Event) -> Option<String> {
+        // Convert timestamp to readable format
+        let datetime = match DateTime::<Utc>::from_timestamp_millis(event.timestamp) {
+            Some(dt) => dt,
+            None => {
+                log::warn!("Invalid timestamp: {}", event.timestamp);
+                return None;
+            }
+        };
+
+        let formatted_time = datetime.format("%Y-%m-%d %H:%M:%S").to_string();
+
+        Some(if self.command.all {
+            format!(
+                "{} {} {}",
+                console::style(formatted_time).dim(),
+                console::style(format!("[{}]", function.name)).cyan(),
+                event.message
+            )
+        } else {
+            format!("{} {}", console::style(formatted_time).dim(), event.message)
+        })
+    }
+
+    /// Poll for new log events and print them, until interrupted with Ctrl-C
+    ///
+    /// Events are polled from all log streams of the functions, so rotated streams are picked up
+    /// as well. As the polled windows overlap, events are deduplicated by their IDs.
+    async fn follow(
+        &self,
+        client: &Client,
+        project: &Project,
+        functions: &[Function],
+        events_json: &mut Vec<String>,
+
+        // Keys of the events already printed, with their timestamps
+        mut seen: HashMap<String, i64>,
+    ) -> Result<(), Error> {
+        let period = humantime::format_duration(FOLLOW_WINDOW).to_string();
+
+        // Created once, so that Ctrl-C pressed while polling is not missed
+        let interrupted = tokio::signal::ctrl_c();
+        tokio::pin!(interrupted);
+
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(FOLLOW_INTERVAL) => {}
+                _ = &mut interrupted => return Ok(()),
+            }
+
+            // A failed poll is covered by the window of the next one
+            let events = match self
+                .fetch_all(client, project, functions, Some(period.clone()))
+                .await
+            {
+                Ok(events) => events,
+                Err(e) => {
+                    log::warn!("Failed to poll logs: {e}");
+                    continue;
+                }
+            };
+
+            for (function, event) in events {
+                if seen
+                    .insert(key(function, &event), event.timestamp)
+                    .is_some()
+                {
+                    continue;
+                }
+
+                if let Some(line) = self.line(function, &event) {
+                    self.writer.text(&line)?;
+                    events_json.push(line);
+                }
+            }
+
+            // Events older than the window are not returned anymore, no need to keep them
+            let oldest = Utc::now().timestamp_millis() - 2 * FOLLOW_WINDOW.as_millis() as i64;
+            seen.retain(|_, timestamp| *timestamp >= oldest);
+        }
+    }
+}
+
+/// Key to deduplicate events by, the CloudWatch event ID if the backend returned one
+fn key(function: &Function, event: &func::logs::Event) -> String {
+    match &event.id {
+        Some(id) => format!("{}:{id}", function.name),
+        None => format!("{}:{}:{}", function.name, event.timestamp, event.message),
+    }
+}
+
+impl Runner for LogsRunner<'_> {
+    /// Retrieves and displays logs for a specific function, or for all functions of the project
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?;
+
+        // Get all function names without any additional manipulations.
+        let all_functions = Parser::new(Some(&project.path), &project.features)
+            .map_err(|e| self.error(None, None, Some(e.into())))?
+            .functions
+            .into_iter()
+            .map(|f| Function::new(&project, &f))
+            .collect::<eyre::Result<Vec<Function>>>()
+            .map_err(|e| self.error(None, None, Some(e.into())))?;
+
+        let functions = match &self.command.name {
+            Some(name) => vec![Function::find_by_name(&all_functions, name).map_err(|e| {
+                self.error(
+                    Some("Could not find requested function"),
+                    None,
+                    Some(e.into()),
+                )
+            })?],
+
+            None => all_functions,
+        };
+
+        let client = self.api_client().await?;
+
+        self.writer.text(&format!(
+            "\n{} {} {}...\n\n",
+            console::style("Fetching logs").bold().green(),
+            console::style("for").dim(),
+            console::style(match &self.command.name {
+                Some(name) => name.to_owned(),
+                None => format!("all functions of {}", project.name),
+            })
+            .bold()
+        ))?;
+
+        let events = self
+            .fetch_all(
+                &client,
+                &project,
+                &functions,
+                self.command.period.to_owned(),
+            )
+            .await?;
+
+        if events.is_empty() {
             self.writer.text(&format!(
                 "{}\n",
                 console::style(format!(
-                    "No logs found for this function in the last {}.",
+                    "No logs found for {} in the last {}.",
+                    if self.command.all {
+                        "the project"
+                    } else {
+                        "this function"
+                    },
                     self.command.period.clone().unwrap_or("1 hour".into())
                 ))
                 .yellow(),
             ))?;
 
-            self.writer.json(json!({"success": true, "logs": []}))?;
-            return Ok(());
+            if !self.command.follow {
+                self.writer.json(json!({"success": true, "logs": []}))?;
+                return Ok(());
+            }
         }
 
         let mut events_json: Vec<String> = vec![];
+        let mut seen = HashMap::new();
 
-        for event in logs_response.events {
-            // Convert timestamp to readable format
-            let datetime = match DateTime::<Utc>::from_timestamp_millis(event.timestamp) {
-                Some(dt) => dt,
-                None => {
-                    log::warn!("Invalid timestamp: {}", event.timestamp);
-                    continue;
-                }
-            };
+        for (function, event) in events {
+            seen.insert(key(function, &event), event.timestamp);
+
+            if let Some(line) = self.line(function, &event) {
+                self.writer.text(&line)?;
+                events_json.push(line);
+            }
+        }
 
-            let formatted_time = datetime.format("%Y-%m-%d %H:%M:%S").to_string();
-            let line = format!("{} {}", console::style(formatted_time).dim(), event.message);
-            self.writer.text(&line)?;
-            events_json.push(line);
+        if self.command.follow {
+            self.follow(&client, &project, &functions, &mut events_json, seen)
+                .await?;
         }
 
         self.writer
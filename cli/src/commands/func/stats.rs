@@ -48,7 +48,7 @@ impl Runner for StatsRunner<'_> {
         let project = self.project().await?;
 
         // Get all function names without any additional manipulations.
-        let all_functions = Parser::new(Some(&project.path))?
+        let all_functions = Parser::new(Some(&project.path), &project.features)?
             .functions
             .into_iter()
             .map(|f| Function::new(&project, &f))
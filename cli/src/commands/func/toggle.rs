@@ -58,7 +58,7 @@ impl Runner for ToggleRunner<'_> {
         let project = self.project().await?;
 
         // Get all function names without any additional manipulations.
-        let all_functions = Parser::new(Some(&project.path))
+        let all_functions = Parser::new(Some(&project.path), &project.features)
             .map_err(|e| self.error(None, None, Some(e.into())))?
             .functions
             .into_iter()
@@ -7,7 +7,10 @@ use crate::{
     runner::{Runnable, Runner},
     writer::Writer,
 };
+pub(crate) use docker::Docker;
 use runner::InvokeRunner;
+pub(crate) use service::LocalServices;
+use std::path::PathBuf;
 
 #[derive(clap::Args, Clone)]
 pub(crate) struct InvokeCommand {
@@ -28,15 +31,32 @@ pub(crate) struct InvokeCommand {
     #[arg(long)]
     url_path: Option<String>,
 
+    /// HTTP method to call endpoint function with.
+    /// Defaults to the first one listed in `methods` of the endpoint, if any.
+    ///
+    /// Example: --method PUT
+    #[arg(long, value_parser = parse_method)]
+    method: Option<http::Method>,
+
     /// Must be a valid JSON.
     ///
     /// In case of endpoint functions payload is a body.
     /// In case of workers, payload is a single event of a queue, which will be wrapped in array and passed to worker function.
+    /// In case of crons, payload is the detail of the scheduled event.
     ///
     /// Example: --payload '{"name": "John Smith"}'
     #[arg(short, long)]
     payload: Option<String>,
 
+    /// Read the payload from a file, as raw bytes. Allows binary bodies, e.g. images.
+    ///
+    /// Locally binary bodies of endpoints are passed base64-encoded, same as by function URLs,
+    /// unless the content type set in --headers is a text one.
+    ///
+    /// Example: --payload-file ./avatar.png
+    #[arg(long, conflicts_with = "payload")]
+    payload_file: Option<PathBuf>,
+
     /// Invoke function remotely. Only works if function was deployed before.
     #[arg(short, long)]
     remote: bool,
@@ -58,6 +78,52 @@ pub(crate) struct InvokeCommand {
     /// Provision a queue. Helpful when you test a function which sends something to queue. Not available when called with --remote flag.
     #[arg(long="with-queue", visible_aliases=["queue"])]
     with_queue: bool,
+
+    /// Set an env var for the local invocation, overriding the one from the code or .env file.
+    /// Can be repeated. Not available when called with --remote flag.
+    ///
+    /// Example: --env LOG_LEVEL=debug --env FEATURE_X=on
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env, conflicts_with = "remote")]
+    envs: Vec<(String, String)>,
+
+    /// Deliver messages the function sends to the queue to the given worker, invoked locally as well.
+    /// Provisions a local queue. Not available when called with --remote flag.
+    ///
+    /// Example: kinetics invoke BasicEndpointEndpoint --with-worker BasicWorkerWorker
+    #[arg(long, value_name = "WORKER", conflicts_with = "remote")]
+    with_worker: Option<String>,
+
+    /// Invoke the function again on every change of .rs files in src/, until Ctrl-C.
+    /// Local services are restarted for each invocation. Not available when called with --remote flag.
+    #[arg(long, conflicts_with = "remote")]
+    watch: bool,
+}
+
+/// Parse an env var passed as `KEY=VALUE`, the value might be empty
+fn parse_env(pair: &str) -> Result<(String, String), String> {
+    let (key, value) = pair
+        .split_once('=')
+        .ok_or_else(|| format!("Expected KEY=VALUE, got `{pair}`"))?;
+
+    let is_valid_key = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if !is_valid_key {
+        return Err(format!(
+            "Invalid env var name `{key}`, only letters, digits and underscores are allowed"
+        ));
+    }
+
+    Ok((key.to_owned(), value.to_owned()))
+}
+
+/// Parse an HTTP method, case-insensitive
+fn parse_method(method: &str) -> Result<http::Method, String> {
+    http::Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| format!("Invalid HTTP method `{method}`"))
 }
 
 impl Runnable for InvokeCommand {
@@ -1,35 +1,32 @@
-use super::docker::Docker;
-use super::service::{LocalDynamoDB, LocalQueue, LocalSqlDB};
+use super::service::{LocalQueue, LocalServices};
 use crate::commands::invoke::InvokeRunner;
-use crate::config::build_config;
-use crate::function::Function;
+use crate::function::{Function, Role};
 use crate::process::Process;
 use crate::runner::Runner;
-use crate::secrets::Secrets;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
 use color_eyre::owo_colors::OwoColorize;
 use eyre::WrapErr;
 use serde_json::json;
 use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::path::Path;
 use std::process::{Command, Stdio};
 
+/// Stop delivering to the worker after this many messages, e.g. if it keeps re-sending them
+const MAX_DELIVERIES: usize = 100;
+
 impl InvokeRunner<'_> {
     /// Invoke the function locally
     #[allow(clippy::too_many_arguments)]
     pub async fn local(
         &self,
         function: &Function,
+
+        // Worker to process the messages the function sends to the local queue
+        worker: Option<&Function>,
         migrations_path: Option<&str>,
     ) -> eyre::Result<()> {
         let project = self.project().await?;
         let home = std::env::var("HOME").wrap_err("Can not read HOME env var")?;
-        let mut secrets_envs = HashMap::new();
-
-        // Envs with the prefix are then processed and provisioned as secrets
-        for (name, value) in Secrets::load() {
-            secrets_envs.insert(format!("KINETICS_SECRET_{}", name.clone()), value);
-        }
-
         let invoke_dir = Path::new(&home).join(format!(".kinetics/{}", project.name));
         let display_path = format!("{}/src/bin/{}Local.rs", invoke_dir.display(), function.name);
 
@@ -42,72 +39,77 @@ impl InvokeRunner<'_> {
             ))
             .map_err(|e| eyre::eyre!(e))?;
 
-        let mut docker = Docker::new(&PathBuf::from(&build_config()?.kinetics_path));
-
-        let mut local_environment = HashMap::from([
-            ("KINETICS_IS_LOCAL", "true".to_string()),
-            // Local SQS uses a fixed account id
-            ("KINETICS_CLOUD_ACCOUNT_ID", "000000000000".to_string()),
-        ]);
-
-        if self.command.with_database {
-            let mut sqldb = LocalSqlDB::new(&project, self.writer);
-
-            if self.command.with_migrations.is_some() {
-                sqldb.with_migrations(migrations_path);
-            }
-
-            local_environment.insert(
-                "KINETICS_SQLDB_LOCAL_CONNECTION_STRING",
-                sqldb.connection_string(),
-            );
-            docker.with_sqldb(sqldb);
+        // Env vars shared by all functions invoked locally, e.g. by the function and its worker
+        let (_docker, environment) = LocalServices {
+            with_database: self.command.with_database,
+            with_migrations: self.command.with_migrations.is_some(),
+            migrations_path: migrations_path.map(str::to_string),
+            // Delivering to a worker requires a queue to send the messages to
+            with_queue: self.command.with_queue || worker.is_some(),
+            table: self.command.table.clone(),
         }
+        .start(&project, self.writer)
+        .await?;
 
-        if self.command.with_queue {
-            let queue = LocalQueue::new();
-            local_environment.insert("KINETICS_QUEUE_NAME", queue.name());
-            local_environment.insert("KINETICS_QUEUE_ENDPOINT_URL", queue.endpoint_url());
-            docker.with_queue(queue);
-        }
+        let payload_env = payload_env(
+            &function.role,
+            self.payload()?,
+            self.command.headers.as_deref(),
+            self.command.payload_file.is_some(),
+        )?;
 
-        if let Some(table) = self.command.table.clone() {
-            docker.with_dynamodb(LocalDynamoDB::new(&table));
-        }
+        let process = self.run_bin(
+            function,
+            &invoke_dir,
+            &environment,
+            &[
+                (payload_env.0, payload_env.1),
+                (
+                    "KINETICS_INVOKE_HEADERS",
+                    self.command.headers.clone().unwrap_or("{}".into()),
+                ),
+                (
+                    "KINETICS_INVOKE_URL_PATH",
+                    self.command.url_path.clone().unwrap_or_default(),
+                ),
+                (
+                    "KINETICS_INVOKE_METHOD",
+                    self.method(function, http::Method::GET).to_string(),
+                ),
+            ],
+        )?;
 
-        docker.start(self.writer)?;
-        docker.provision().await?;
+        // If successful, print the full stdout
+        process.print()?;
 
-        let mut aws_credentials = HashMap::new();
+        let mut output = json!({"success": true, "output": process.output()});
 
-        // Do not mock AWS endpoint when not needed
-        if self.command.table.is_some() || self.command.with_queue {
-            aws_credentials.insert("AWS_IGNORE_CONFIGURED_ENDPOINT_URLS", "false");
-            aws_credentials.insert("AWS_ENDPOINT_URL", "http://localhost:8000");
-            aws_credentials.insert("AWS_ACCESS_KEY_ID", "key");
-            aws_credentials.insert("AWS_SECRET_ACCESS_KEY", "secret");
+        if let Some(worker) = worker {
+            output["worker_outputs"] =
+                json!(self.deliver(worker, &invoke_dir, &environment).await?);
         }
 
+        self.writer.json(output)?;
+        Ok(())
+    }
+
+    /// Run the local binary of a function, logging its output in real time
+    fn run_bin(
+        &self,
+        function: &Function,
+        invoke_dir: &Path,
+        environment: &HashMap<String, String>,
+        invoke_environment: &[(&str, String)],
+    ) -> eyre::Result<Process<'_>> {
         // Start the command with piped stdout and stderr
         let child = Command::new("cargo")
             .args(["run", "--bin", &format!("{}Local", function.name)])
-            .envs(secrets_envs)
-            .envs(aws_credentials)
-            .envs(local_environment)
+            .envs(environment)
             .envs(function.environment())
-            .env(
-                "KINETICS_INVOKE_PAYLOAD",
-                self.command.payload.clone().unwrap_or("{}".into()),
-            )
-            .env(
-                "KINETICS_INVOKE_HEADERS",
-                self.command.headers.clone().unwrap_or("{}".into()),
-            )
-            .env(
-                "KINETICS_INVOKE_URL_PATH",
-                self.command.url_path.clone().unwrap_or_default(),
-            )
-            .current_dir(&invoke_dir)
+            // Vars passed with --env take precedence over the ones from the code and .env
+            .envs(self.command.envs.clone())
+            .envs(invoke_environment.iter().map(|(name, value)| (name, value)))
+            .current_dir(invoke_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -126,12 +128,199 @@ impl InvokeRunner<'_> {
             ));
         }
 
-        // If successful, print the full stdout
-        process.print()?;
+        Ok(process)
+    }
 
-        self.writer
-            .json(json!({"success": true, "output": process.output()}))?;
+    /// Invoke the worker with each message sent to the local queue, until it is drained
+    ///
+    /// Messages sent by the worker itself are delivered in turn. Returns outputs of the worker.
+    async fn deliver(
+        &self,
+        worker: &Function,
+        invoke_dir: &Path,
+        environment: &HashMap<String, String>,
+    ) -> eyre::Result<Vec<String>> {
+        let queue = LocalQueue::new();
+        let mut outputs = vec![];
 
-        Ok(())
+        loop {
+            let messages = queue.receive().await?;
+
+            if messages.is_empty() {
+                break;
+            }
+
+            for message in messages {
+                if outputs.len() == MAX_DELIVERIES {
+                    log::warn!("Stopped after delivering {MAX_DELIVERIES} messages to the worker");
+                    return Ok(outputs);
+                }
+
+                self.writer
+                    .text(&format!(
+                        "\n{} {} {}...\n",
+                        console::style("Delivering message").green().bold(),
+                        console::style("to").dimmed(),
+                        console::style(&worker.name).bold()
+                    ))
+                    .map_err(|e| eyre::eyre!(e))?;
+
+                let process = self.run_bin(
+                    worker,
+                    invoke_dir,
+                    environment,
+                    &[(
+                        "KINETICS_INVOKE_PAYLOAD",
+                        message.body().unwrap_or_default().to_string(),
+                    )],
+                )?;
+
+                process.print()?;
+                outputs.push(process.output());
+                queue.delete(&message).await?;
+            }
+        }
+
+        Ok(outputs)
+    }
+}
+
+/// Env var with the payload for the local binary of a function
+///
+/// Binary payloads of endpoints are passed base64-encoded, same as function URLs do.
+fn payload_env(
+    role: &Role,
+    payload: Vec<u8>,
+    headers: Option<&str>,
+    is_file: bool,
+) -> eyre::Result<(&'static str, String)> {
+    if matches!(role, Role::Endpoint) && is_file && is_binary(&payload, headers)? {
+        return Ok((
+            "KINETICS_INVOKE_PAYLOAD_BASE64",
+            BASE64_STANDARD.encode(&payload),
+        ));
+    }
+
+    Ok((
+        "KINETICS_INVOKE_PAYLOAD",
+        String::from_utf8(payload).wrap_err("Payload must be a UTF-8 text")?,
+    ))
+}
+
+/// Whether the payload file is to be passed as a binary body
+///
+/// Function URLs base64-encode bodies unless the content type is a text one.
+/// Without the content type header, only non-UTF-8 payloads are considered binary.
+fn is_binary(payload: &[u8], headers: Option<&str>) -> eyre::Result<bool> {
+    let headers = serde_json::from_str::<HashMap<String, String>>(headers.unwrap_or("{}"))
+        .wrap_err("Failed to parse headers JSON object, must be {\"String\": \"String\"}")?;
+
+    let content_type = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.to_lowercase());
+
+    Ok(match content_type {
+        Some(content_type) => {
+            !(content_type.starts_with("text/")
+                || ["json", "xml", "javascript", "x-www-form-urlencoded"]
+                    .iter()
+                    .any(|text| content_type.contains(text)))
+        }
+        None => std::str::from_utf8(payload).is_err(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixture::{assert_success, Fixture};
+
+    /// Not a valid UTF-8 sequence, e.g. a fragment of an image
+    const BINARY: [u8; 5] = [0, 159, 146, 150, 255];
+
+    #[test]
+    fn detects_binary_payloads() {
+        // Without a content type only non-UTF-8 payloads are binary
+        assert!(is_binary(&BINARY, None).unwrap());
+        assert!(!is_binary(br#"{"name": "John"}"#, None).unwrap());
+
+        // The content type takes precedence over the bytes, header names are case-insensitive
+        let png = r#"{"Content-Type": "image/png"}"#;
+        assert!(is_binary(b"text", Some(png)).unwrap());
+
+        for text in [
+            "text/plain",
+            "application/json",
+            "application/xml; charset=utf-8",
+        ] {
+            let headers = format!(r#"{{"content-type": "{text}"}}"#);
+            assert!(!is_binary(&BINARY, Some(&headers)).unwrap(), "{text}");
+        }
+
+        assert!(is_binary(&BINARY, Some("not json")).is_err());
+    }
+
+    #[test]
+    fn encodes_binary_payload_files_of_endpoints() {
+        let (name, value) = payload_env(&Role::Endpoint, BINARY.to_vec(), None, true).unwrap();
+        assert_eq!(name, "KINETICS_INVOKE_PAYLOAD_BASE64");
+        assert_eq!(BASE64_STANDARD.decode(value).unwrap(), BINARY);
+
+        // Text files and --payload are passed as is
+        let (name, value) = payload_env(&Role::Endpoint, b"{}".to_vec(), None, true).unwrap();
+        assert_eq!((name, value.as_str()), ("KINETICS_INVOKE_PAYLOAD", "{}"));
+
+        let payload = "{}".as_bytes().to_vec();
+        let (name, _) = payload_env(&Role::Endpoint, payload, Some("{}"), false).unwrap();
+        assert_eq!(name, "KINETICS_INVOKE_PAYLOAD");
+
+        // Other functions have no binary bodies
+        assert!(payload_env(&Role::Worker, BINARY.to_vec(), None, true).is_err());
+    }
+
+    #[test]
+    #[ignore = "builds the generated crate, fetching its dependencies"]
+    fn passes_binary_payload_file_to_local_endpoint() {
+        let fixture = Fixture::new(&[
+            ("src/lib.rs", "pub mod api;\n"),
+            (
+                "src/api.rs",
+                r#"use http::{Request, Response};
+use kinetics::macros::endpoint;
+use kinetics::tools::config::Config;
+use kinetics::tools::http::Body;
+use std::collections::HashMap;
+use tower::BoxError;
+
+#[endpoint(url_path = "/echo")]
+pub async fn echo(
+    event: Request<Body>,
+    _secrets: &HashMap<String, String>,
+    _config: &Config,
+) -> Result<Response<String>, BoxError> {
+    Ok(Response::builder().body(format!("{:?}", event.body()))?)
+}
+"#,
+            ),
+        ]);
+
+        assert_eq!(fixture.parse(), ["ApiEcho"]);
+
+        // The same as `kinetics invoke ApiEcho --payload-file <file>` reads and passes it
+        let file = std::env::temp_dir().join(format!("kinetics-{}.bin", uuid::Uuid::new_v4()));
+        std::fs::write(&file, BINARY).unwrap();
+        let payload = std::fs::read(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        let (name, value) = payload_env(&Role::Endpoint, payload, None, true).unwrap();
+
+        let output = fixture.cargo(
+            &["run", "--quiet", "--bin", "ApiEchoLocal"],
+            &[(name, value), ("KINETICS_INVOKE_URL_PATH", "/echo".into())],
+        );
+
+        assert_success(&output);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("Binary([0, 159, 146, 150, 255])"));
     }
 }
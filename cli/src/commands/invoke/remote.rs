@@ -65,9 +65,9 @@ impl InvokeRunner<'_> {
         let client = reqwest::Client::new();
 
         let response = client
-            .post(url)
+            .request(self.method(function, http::Method::POST), url)
             .headers(headers_map)
-            .body(self.command.payload.clone().unwrap_or_else(|| "{}".into()))
+            .body(self.payload()?)
             .send()
             .await
             .wrap_err("Failed to call function URL")?;
@@ -1,9 +1,11 @@
 use crate::commands::invoke::InvokeCommand;
 use crate::config::build_config;
 use crate::error::Error;
-use crate::function::Function;
+use crate::function::{Function, Params, Role};
+use crate::project::Watcher;
 use crate::runner::Runner;
 use crate::writer::Writer;
+use eyre::WrapErr;
 use std::path::PathBuf;
 
 pub(crate) struct InvokeRunner<'a> {
@@ -11,19 +13,73 @@ pub(crate) struct InvokeRunner<'a> {
     pub(crate) writer: &'a Writer,
 }
 
-impl Runner for InvokeRunner<'_> {
-    /// Invoke the function either locally or remotely
-    async fn run(&mut self) -> Result<(), Error> {
+impl InvokeRunner<'_> {
+    /// Raw payload, either read from --payload-file or passed with --payload
+    pub(crate) fn payload(&self) -> eyre::Result<Vec<u8>> {
+        match &self.command.payload_file {
+            Some(path) => {
+                std::fs::read(path).wrap_err(format!("Failed to read payload file {path:?}"))
+            }
+            None => Ok(self
+                .command
+                .payload
+                .clone()
+                .unwrap_or_else(|| "{}".into())
+                .into_bytes()),
+        }
+    }
+
+    /// HTTP method to call an endpoint with
+    ///
+    /// Either passed with --method, or the first one allowed by the endpoint, or the `default`.
+    pub(crate) fn method(&self, function: &Function, default: http::Method) -> http::Method {
+        if let Some(method) = &self.command.method {
+            return method.clone();
+        }
+
+        match &function.params {
+            Params::Endpoint(params) => params
+                .methods
+                .first()
+                .and_then(|method| http::Method::from_bytes(method.as_bytes()).ok())
+                .unwrap_or(default),
+            _ => default,
+        }
+    }
+}
+
+impl InvokeRunner<'_> {
+    /// Parse the project and invoke the function once, either locally or remotely
+    async fn invoke(&self) -> Result<(), Error> {
         let project = self.project().await?;
 
         // Get function names as well as pull all updates from the code.
         let all_functions = project.parse(
             PathBuf::from(build_config()?.kinetics_path),
             &[self.command.name.clone().into()],
+            false,
         )?;
 
         let function = Function::find_by_name(&all_functions, &self.command.name)?;
 
+        let worker = match &self.command.with_worker {
+            Some(name) => {
+                let worker = Function::find_by_name(&all_functions, name)?;
+
+                if !matches!(worker.role, Role::Worker) {
+                    return Err(Error::new(
+                        "Not a worker",
+                        Some(&format!(
+                            "{name} is not a worker, --with-worker expects one"
+                        )),
+                    ));
+                }
+
+                Some(worker)
+            }
+            None => None,
+        };
+
         // If --with_migrations was not passed, or comes with default "" value, then
         // do not set the migrations path. There is a default value set down the flow.
         let migrations_path = if self
@@ -39,7 +95,8 @@ impl Runner for InvokeRunner<'_> {
         };
 
         if !self.command.remote {
-            self.local(&function, migrations_path.as_deref()).await?
+            self.local(&function, worker.as_ref(), migrations_path.as_deref())
+                .await?
         } else {
             self.remote(&function).await?
         }
@@ -47,3 +104,36 @@ impl Runner for InvokeRunner<'_> {
         Ok(())
     }
 }
+
+impl Runner for InvokeRunner<'_> {
+    /// Invoke the function, or keep invoking it on changes in watch mode
+    async fn run(&mut self) -> Result<(), Error> {
+        if !self.command.watch {
+            return self.invoke().await;
+        }
+
+        let mut watcher = Watcher::new(&self.project().await?)?;
+
+        loop {
+            // Failures, e.g. compilation errors, are expected to be fixed by the next change
+            if let Err(e) = self.invoke().await {
+                self.writer.text(&format!(
+                    "\n{} {e}\n",
+                    console::style("Error:").red().bold()
+                ))?;
+            }
+
+            self.writer.text(&format!(
+                "\n{}\n",
+                console::style("Watching for changes, press Ctrl-C to stop...").dim()
+            ))?;
+
+            tokio::select! {
+                changed = watcher.changed() => changed?,
+                _ = tokio::signal::ctrl_c() => break,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,113 @@
 mod dynamodb;
 mod queue;
 mod sqldb;
+use super::docker::Docker;
+use crate::config::build_config;
+use crate::project::Project;
+use crate::secrets::Secrets;
+use crate::writer::Writer;
 pub use dynamodb::LocalDynamoDB;
 pub use queue::LocalQueue;
 pub use sqldb::LocalSqlDB;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 pub enum Service<'a> {
     DynamoDB(LocalDynamoDB),
     SqlDB(LocalSqlDB<'a>),
     Queue(LocalQueue),
 }
+
+/// Services provisioned for functions run locally, by either `invoke` or `serve` command
+#[derive(Default)]
+pub(crate) struct LocalServices {
+    pub(crate) with_database: bool,
+
+    /// Apply migrations to the database
+    pub(crate) with_migrations: bool,
+
+    /// Dir with migrations, the default one is used if not set
+    pub(crate) migrations_path: Option<String>,
+
+    pub(crate) with_queue: bool,
+
+    /// Name of a DynamoDB table to provision
+    pub(crate) table: Option<String>,
+}
+
+impl LocalServices {
+    /// Start the services in docker and return env vars for functions to use them
+    ///
+    /// The containers are stopped once the returned docker instance is dropped.
+    pub(crate) async fn start<'a>(
+        &self,
+        project: &Project,
+        writer: &'a Writer,
+    ) -> eyre::Result<(Docker<'a>, HashMap<String, String>)> {
+        let mut environment = HashMap::new();
+
+        // Envs with the prefix are then processed and provisioned as secrets
+        for (name, value) in Secrets::load() {
+            environment.insert(format!("KINETICS_SECRET_{}", name.clone()), value);
+        }
+
+        let mut docker = Docker::new(&PathBuf::from(&build_config()?.kinetics_path));
+
+        let mut local_environment = HashMap::from([
+            ("KINETICS_IS_LOCAL", "true".to_string()),
+            // Local SQS uses a fixed account id
+            ("KINETICS_CLOUD_ACCOUNT_ID", "000000000000".to_string()),
+        ]);
+
+        if self.with_database {
+            let mut sqldb = LocalSqlDB::new(project, writer);
+
+            if self.with_migrations {
+                sqldb.with_migrations(self.migrations_path.as_deref());
+            }
+
+            local_environment.insert(
+                "KINETICS_SQLDB_LOCAL_CONNECTION_STRING",
+                sqldb.connection_string(),
+            );
+            docker.with_sqldb(sqldb);
+        }
+
+        if self.with_queue {
+            let queue = LocalQueue::new();
+            local_environment.insert("KINETICS_QUEUE_NAME", queue.name());
+            local_environment.insert("KINETICS_QUEUE_ENDPOINT_URL", queue.endpoint_url());
+            docker.with_queue(queue);
+        }
+
+        if let Some(table) = self.table.clone() {
+            docker.with_dynamodb(LocalDynamoDB::new(&table));
+        }
+
+        docker.start(writer)?;
+        docker.provision().await?;
+
+        let mut aws_credentials = HashMap::new();
+
+        // Do not mock AWS endpoint when not needed
+        if self.table.is_some() || self.with_queue {
+            aws_credentials.insert("AWS_IGNORE_CONFIGURED_ENDPOINT_URLS", "false");
+            aws_credentials.insert("AWS_ENDPOINT_URL", "http://localhost:8000");
+            aws_credentials.insert("AWS_ACCESS_KEY_ID", "key");
+            aws_credentials.insert("AWS_SECRET_ACCESS_KEY", "secret");
+        }
+
+        environment.extend(
+            aws_credentials
+                .into_iter()
+                .chain(
+                    local_environment
+                        .iter()
+                        .map(|(name, value)| (*name, value.as_str())),
+                )
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+
+        Ok((docker, environment))
+    }
+}
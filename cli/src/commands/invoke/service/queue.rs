@@ -1,5 +1,7 @@
 use crate::error::Error;
 use aws_config::BehaviorVersion;
+use aws_sdk_sqs::types::Message;
+use eyre::WrapErr;
 use std::time::Duration;
 
 const DOCKER_COMPOSE_SNIPPET: &str = r#"
@@ -29,10 +31,10 @@ impl LocalQueue {
         )
     }
 
-    pub async fn provision(&self) -> eyre::Result<()> {
-        // Configure AWS client
+    /// SQS client pointing to the local container
+    async fn client(&self) -> aws_sdk_sqs::Client {
         let config = aws_config::defaults(BehaviorVersion::latest())
-            .endpoint_url("http://localhost:9324")
+            .endpoint_url(self.endpoint_url())
             .region("us-east-1")
             .credentials_provider(aws_sdk_sqs::config::Credentials::new(
                 "key", "secret", None, None, "provider",
@@ -40,7 +42,11 @@ impl LocalQueue {
             .load()
             .await;
 
-        let client = aws_sdk_sqs::Client::new(&config);
+        aws_sdk_sqs::Client::new(&config)
+    }
+
+    pub async fn provision(&self) -> eyre::Result<()> {
+        let client = self.client().await;
 
         // Retry parameters
         let max_retries = 5;
@@ -69,6 +75,47 @@ impl LocalQueue {
         Ok(())
     }
 
+    /// Receive a batch of messages sent to the queue, waiting shortly for new ones
+    ///
+    /// Returns an empty list once the queue is drained.
+    pub async fn receive(&self) -> eyre::Result<Vec<Message>> {
+        let output = self
+            .client()
+            .await
+            .receive_message()
+            .queue_url(self.url())
+            .max_number_of_messages(10)
+            .wait_time_seconds(1)
+            .send()
+            .await
+            .wrap_err("Failed to receive messages from local SQS queue")?;
+
+        Ok(output.messages.unwrap_or_default())
+    }
+
+    /// Delete a processed message, so that it is not received again
+    pub async fn delete(&self, message: &Message) -> eyre::Result<()> {
+        let Some(receipt_handle) = message.receipt_handle() else {
+            return Ok(());
+        };
+
+        self.client()
+            .await
+            .delete_message()
+            .queue_url(self.url())
+            .receipt_handle(receipt_handle)
+            .send()
+            .await
+            .wrap_err("Failed to delete a message from local SQS queue")?;
+
+        Ok(())
+    }
+
+    /// URL of the queue, with the fixed account id of local SQS
+    fn url(&self) -> String {
+        format!("{}/000000000000/{}", self.endpoint_url(), self.name)
+    }
+
     pub fn name(&self) -> String {
         self.name.clone()
     }
@@ -20,8 +20,11 @@ local-postgres:
 ///
 /// This struct is used to configure properties for setting up a local SQL database.
 pub struct LocalSqlDB<'a> {
-    /// Current project
-    project: Project,
+    /// Name of the current project, the data of each project is kept in its own volume
+    project_name: String,
+
+    /// Root directory of the current project
+    project_path: PathBuf,
 
     /// Whether to apply database migrations on startup
     with_migrations: bool,
@@ -40,7 +43,8 @@ impl<'a> LocalSqlDB<'a> {
             // The default migrations path is `migrations` relative to the project root directory
             migrations_path: project.path.join("migrations"),
             with_migrations: false,
-            project: project.clone(),
+            project_name: project.name.clone(),
+            project_path: project.path.clone(),
             writer,
         }
     }
@@ -49,7 +53,7 @@ impl<'a> LocalSqlDB<'a> {
         DOCKER_COMPOSE_SNIPPET
             .replace(
                 "{{DB_VOLUME_PATH}}",
-                format!("/tmp/kinetics_db_{}", self.project.name).as_str(),
+                format!("/tmp/kinetics_db_{}", self.project_name).as_str(),
             )
             .to_string()
     }
@@ -67,7 +71,7 @@ impl<'a> LocalSqlDB<'a> {
 
         // Use a migrations path is specified; otherwise, the default migrations path will be used
         if let Some(migrations_path) = migrations_path {
-            self.migrations_path = self.project.path.join(migrations_path);
+            self.migrations_path = self.project_path.join(migrations_path);
         }
 
         self
@@ -123,7 +123,7 @@ impl LoginRunner {
 
         // Projects cache is currently holding only one user projects. Clear it to avoid
         // overlapping settings.
-        Project::clear_cache()?;
+        Project::clear_cache().await?;
 
         Ok(response.json().await?)
     }
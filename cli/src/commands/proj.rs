@@ -1,10 +1,18 @@
 pub mod destroy;
+pub mod export;
+pub mod import;
 pub mod list;
+pub mod local_diff;
 pub mod rollback;
+pub mod status;
 pub mod versions;
 use crate::commands::proj::destroy::DestroyCommand;
+use crate::commands::proj::export::ExportCommand;
+use crate::commands::proj::import::ImportCommand;
 use crate::commands::proj::list::ListCommand;
+use crate::commands::proj::local_diff::LocalDiffCommand;
 use crate::commands::proj::rollback::RollbackCommand;
+use crate::commands::proj::status::StatusCommand;
 use crate::commands::proj::versions::VersionsCommand;
 use clap::Subcommand;
 
@@ -19,6 +27,18 @@ pub(crate) enum ProjCommands {
     /// List projects
     List(ListCommand),
 
+    /// Export project configuration to a file, without secret values
+    Export(ExportCommand),
+
+    /// Scaffold configuration exported from another project
+    Import(ImportCommand),
+
+    /// Show the status of the latest deployment
+    Status(StatusCommand),
+
     /// List all available versions
     Versions(VersionsCommand),
+
+    /// Show which functions changed since the last deployment from this machine, without deploying
+    LocalDiff(LocalDiffCommand),
 }
@@ -60,8 +60,8 @@ impl Runner for DestroyRunner<'_> {
             ))?;
             self.writer.text(&format!(
                 "{} {}: ",
-                "Do you want to proceed?".bold(),
-                "[y/N]".dim()
+                "Type the project name to proceed".bold(),
+                format!("[{}]", project.name).dim()
             ))?;
 
             io::stdout()
@@ -76,7 +76,8 @@ impl Runner for DestroyRunner<'_> {
                 .wrap_err("Failed to read user's input")
                 .map_err(|e| self.error(None, None, Some(e.into())))?;
 
-            if !matches!(input.trim().to_lowercase().as_ref(), "y" | "yes") {
+            // Require the exact name, to not destroy a wrong project by a habitual "y"
+            if input.trim() != project.name {
                 self.writer
                     .text(&format!("{}\n", "Destroying canceled".dim().bold()))?;
                 return Ok(());
@@ -0,0 +1,55 @@
+use crate::error::Error;
+use crate::runner::{Runnable, Runner};
+use crate::writer::Writer;
+use eyre::Context;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct ExportCommand {
+    /// File to write the configuration to
+    file: PathBuf,
+}
+
+impl Runnable for ExportCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        ExportRunner {
+            command: self.clone(),
+            writer,
+        }
+    }
+}
+
+struct ExportRunner<'a> {
+    command: ExportCommand,
+    writer: &'a Writer,
+}
+
+impl Runner for ExportRunner<'_> {
+    /// Export the configuration of the current project to a file
+    ///
+    /// Secret values and env var values are not exported, only their names.
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?;
+
+        let content = project
+            .export()
+            .map_err(|e| self.error(Some("Failed to export"), None, Some(e.into())))?;
+
+        std::fs::write(&self.command.file, content)
+            .wrap_err(format!("Failed to write {:?}", self.command.file))
+            .map_err(|e| self.error(None, None, Some(e.into())))?;
+
+        self.writer.text(&format!(
+            "{} {} {}\n",
+            console::style("Exported").green().bold(),
+            console::style(&project.name).bold(),
+            console::style(format!("to {}", self.command.file.display())).dim(),
+        ))?;
+
+        self.writer
+            .json(json!({"success": true, "file": self.command.file}))?;
+
+        Ok(())
+    }
+}
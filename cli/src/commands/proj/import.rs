@@ -0,0 +1,79 @@
+use crate::error::Error;
+use crate::runner::{Runnable, Runner};
+use crate::writer::Writer;
+use eyre::Context;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct ImportCommand {
+    /// File created by `kinetics proj export`
+    file: PathBuf,
+}
+
+impl Runnable for ImportCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        ImportRunner {
+            command: self.clone(),
+            writer,
+        }
+    }
+}
+
+struct ImportRunner<'a> {
+    command: ImportCommand,
+    writer: &'a Writer,
+}
+
+impl Runner for ImportRunner<'_> {
+    /// Scaffold the exported configuration into the current project
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?;
+
+        let content = std::fs::read_to_string(&self.command.file)
+            .wrap_err(format!("Failed to read {:?}", self.command.file))
+            .map_err(|e| self.error(None, None, Some(e.into())))?;
+
+        let imported = project
+            .import(&content)
+            .map_err(|e| self.error(Some("Failed to import"), None, Some(e.into())))?;
+
+        if imported.config {
+            self.writer.text(&format!(
+                "{} kinetics.toml\n",
+                console::style("Created").green().bold()
+            ))?;
+        } else {
+            self.writer.text(&format!(
+                "{}\n",
+                console::style("kinetics.toml already exists, skipped").yellow()
+            ))?;
+        }
+
+        for (file, names) in [(".env", &imported.env), (".env.secrets", &imported.secrets)] {
+            if names.is_empty() {
+                continue;
+            }
+
+            self.writer.text(&format!(
+                "{} {} {}\n",
+                console::style("Added to").green().bold(),
+                file,
+                console::style(names.join(", ")).dim(),
+            ))?;
+        }
+
+        if !imported.env.is_empty() || !imported.secrets.is_empty() {
+            self.writer.text(&format!(
+                "\n{}\n",
+                console::style("Fill in the values of the added variables before deploying.")
+                    .yellow()
+            ))?;
+        }
+
+        self.writer
+            .json(json!({"success": true, "imported": imported}))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use crate::config::build_config;
+use crate::error::Error;
+use crate::function::build;
+use crate::logger::Logger;
+use crate::project::DeployState;
+use crate::runner::{Runnable, Runner};
+use crate::writer::Writer;
+use eyre::WrapErr;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct LocalDiffCommand {
+    /// Comma-separated cargo features to enable on top of the default ones.
+    ///
+    /// Use the same features as for the deployment, otherwise bundles may differ.
+    #[arg(long, value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Build inside a Docker container with a pinned toolchain, for reproducible builds.
+    ///
+    /// Use the same option as for the deployment, otherwise bundles may differ.
+    #[arg(long)]
+    docker_build: bool,
+}
+
+impl Runnable for LocalDiffCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        LocalDiffRunner {
+            command: self.clone(),
+            writer,
+        }
+    }
+}
+
+struct LocalDiffRunner<'a> {
+    command: LocalDiffCommand,
+    writer: &'a Writer,
+}
+
+/// State of a function compared to the last deployment from this machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Change {
+    Added,
+    Changed,
+    Removed,
+    Unchanged,
+}
+
+/// Classify the built bundles, `(function name, checksum)`, against the recorded deployment
+///
+/// Functions recorded as deployed but no longer in the project are removed ones.
+fn compare(checksums: &[(String, String)], deploy_state: &DeployState) -> Vec<(Change, String)> {
+    let mut changes = checksums
+        .iter()
+        .map(|(name, checksum)| {
+            let change = match deploy_state.checksum(name) {
+                None => Change::Added,
+                Some(deployed) if deployed == checksum => Change::Unchanged,
+                Some(_) => Change::Changed,
+            };
+
+            (change, name.clone())
+        })
+        .collect::<Vec<_>>();
+
+    for name in deploy_state.functions() {
+        if !checksums.iter().any(|(built, _)| built == name) {
+            changes.push((Change::Removed, name.to_owned()));
+        }
+    }
+
+    changes.sort();
+    changes
+}
+
+impl Runner for LocalDiffRunner<'_> {
+    /// Build all functions and compare their bundles to the ones deployed last time from this machine
+    ///
+    /// Nothing is uploaded or deployed, and the deployed stack is not queried.
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?.with_features(&self.command.features);
+        let kinetics_path = PathBuf::from(build_config()?.kinetics_path);
+
+        let functions = project.parse(kinetics_path.clone(), &[], false)?;
+
+        if functions.is_empty() {
+            return Err(self.error(
+                Some("No functions found"),
+                Some("Nothing to compare, the project has no functions."),
+                None,
+            ));
+        }
+
+        let progress = if self.writer.is_structured() {
+            ProgressBar::hidden()
+        } else {
+            let progress = Logger::multi_progress().add(ProgressBar::new_spinner());
+
+            progress.set_style(
+                ProgressStyle::default_spinner()
+                    .template(&format!(
+                        "   {} {{spinner}} {{wide_msg:.dim}}",
+                        console::style("Building").cyan().bold()
+                    ))
+                    .unwrap(),
+            );
+
+            progress.enable_steady_tick(std::time::Duration::from_millis(100));
+            progress
+        };
+
+        let built = build(&functions, &progress, self.command.docker_build).await;
+        progress.finish_and_clear();
+        built?;
+
+        // Bundles confirmed by the last successful deployment of the project
+        let deploy_state = DeployState::new(&kinetics_path.join(&project.name));
+
+        let mut checksums = Vec::new();
+
+        for function in &functions {
+            let checksum = function.bundle_checksum().await.wrap_err(format!(
+                "Failed to read the bundle of function: \"{}\"",
+                function.name
+            ))?;
+
+            checksums.push((function.name.clone(), checksum));
+        }
+
+        let changes = compare(&checksums, &deploy_state);
+
+        self.writer.text(&format!(
+            "Functions of \"{}\" project compared to the last deployment from this machine:\n\n",
+            console::style(&project.name).blue().bold()
+        ))?;
+
+        for (change, name) in &changes {
+            let change = match change {
+                Change::Added => console::style("+ Added    ").green(),
+                Change::Changed => console::style("~ Changed  ").yellow(),
+                Change::Removed => console::style("- Removed  ").red(),
+                Change::Unchanged => console::style("  Unchanged").dim(),
+            };
+
+            self.writer.text(&format!("{change} {name}\n"))?;
+        }
+
+        self.writer.json(json!({
+            "success": true,
+            "functions": changes
+                .iter()
+                .map(|(change, name)| json!({"name": name, "change": change}))
+                .collect::<Vec<_>>(),
+        }))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deploy_state(deployed: &[(&str, &str)]) -> DeployState {
+        let dir = std::env::temp_dir().join(format!("kinetics-diff-{}", uuid::Uuid::new_v4()));
+        let mut state = DeployState::new(&dir);
+
+        for (name, checksum) in deployed {
+            state.confirm(name, checksum);
+        }
+
+        state
+    }
+
+    fn checksums(built: &[(&str, &str)]) -> Vec<(String, String)> {
+        built
+            .iter()
+            .map(|(name, checksum)| (name.to_string(), checksum.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn classifies_functions_against_last_deployment() {
+        let state = deploy_state(&[("Orders", "a1"), ("Users", "b1"), ("Legacy", "c1")]);
+        let built = checksums(&[("Users", "b2"), ("Orders", "a1"), ("Payments", "d1")]);
+
+        assert_eq!(
+            compare(&built, &state),
+            vec![
+                (Change::Added, "Payments".to_string()),
+                (Change::Changed, "Users".to_string()),
+                (Change::Removed, "Legacy".to_string()),
+                (Change::Unchanged, "Orders".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn everything_is_added_without_deployment() {
+        let built = checksums(&[("Orders", "a1"), ("Users", "b1")]);
+
+        assert_eq!(
+            compare(&built, &deploy_state(&[])),
+            vec![
+                (Change::Added, "Orders".to_string()),
+                (Change::Added, "Users".to_string()),
+            ]
+        );
+    }
+}
@@ -8,7 +8,7 @@ use serde_json::json;
 #[derive(clap::Args, Clone)]
 pub(crate) struct RollbackCommand {
     /// Specific version to rollback to (optional)
-    #[arg(short, long)]
+    #[arg(long)]
     version: Option<u32>,
 }
 
@@ -46,35 +46,31 @@ impl Runner for RollbackRunner<'_> {
             .wrap_err("Failed to fetch versions")
             .map_err(|e| self.server_error(Some(e.into())))?;
 
-        if versions.versions.len() < 2 && self.command.version.is_none() {
-            self.writer.text(&format!(
-                "{}\n",
-                console::style("Nothing to rollback, there is only one version").yellow()
-            ))?;
+        let target_version = match target_version(&versions.versions, self.command.version) {
+            Ok(Some(version)) => version.clone(),
+            Ok(None) => {
+                self.writer.text(&format!(
+                    "{}\n",
+                    console::style("Nothing to rollback, there is only one version").yellow()
+                ))?;
 
-            self.writer.json(
-                json!({"success": true, "message": "Nothing to rollback, no other versions"}),
-            )?;
+                self.writer.json(
+                    json!({"success": true, "message": "Nothing to rollback, no other versions"}),
+                )?;
 
-            return Ok(());
-        }
-
-        let target_version = match self.command.version {
-            Some(v) => {
-                // Find the specified version in the list
-                if let Some(target) = versions.versions.iter().find(|ver| ver.version == v) {
-                    target.clone()
-                } else {
-                    return Err(self.error(
-                        Some(&format!("Version {} not found", v)),
-                        Some("Use 'kinetics proj versions' to see available versions."),
-                        None,
-                    ));
-                }
+                return Ok(());
             }
-            None => {
-                // Default behavior: rollback to previous version
-                versions.versions[1].clone()
+            Err(available) => {
+                return Err(self.error(
+                    Some(&format!(
+                        "Version {} not found",
+                        self.command.version.unwrap_or_default()
+                    )),
+                    Some(&format!(
+                        "Available versions: {available}. Use 'kinetics proj versions' to see their details."
+                    )),
+                    None,
+                ));
             }
         };
 
@@ -90,7 +86,7 @@ impl Runner for RollbackRunner<'_> {
             .bold(),
         ))?;
 
-        client
+        let response = client
             .post("/stack/rollback")
             .json(&stack::rollback::Request {
                 name: project.name.to_string(),
@@ -101,6 +97,21 @@ impl Runner for RollbackRunner<'_> {
             .wrap_err("Failed to rollback")
             .map_err(|e| self.server_error(Some(e.into())))?;
 
+        // Otherwise the status of the previous deployment would be reported as the rollback's one
+        if !response.status().is_success() {
+            log::error!(
+                "Failed to rollback ({}): {}",
+                response.status(),
+                response.text().await.unwrap_or("Unknown error".to_string())
+            );
+
+            return Err(self.error(
+                Some("Rollback request failed"),
+                Some("Try again in a few minutes."),
+                None,
+            ));
+        }
+
         let mut status = project
             .status()
             .await
@@ -131,3 +142,65 @@ impl Runner for RollbackRunner<'_> {
         Ok(())
     }
 }
+
+/// Pick the version to roll back to, the previous one unless a specific one is requested
+///
+/// Versions are ordered from the latest one. `None` if there is nothing to roll back to,
+/// and an error with the list of available versions if the requested one doesn't exist.
+fn target_version(
+    versions: &[stack::versions::Version],
+    requested: Option<u32>,
+) -> Result<Option<&stack::versions::Version>, String> {
+    let Some(requested) = requested else {
+        return Ok(versions.get(1));
+    };
+
+    match versions.iter().find(|version| version.version == requested) {
+        Some(version) => Ok(Some(version)),
+        None => Err(versions
+            .iter()
+            .map(|version| version.version.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Versions as returned by the backend, from the latest one
+    fn versions(numbers: &[u32]) -> Vec<stack::versions::Version> {
+        numbers
+            .iter()
+            .map(|&version| stack::versions::Version {
+                version,
+                updated_at: chrono::Utc::now(),
+                message: None,
+            })
+            .collect()
+    }
+
+    fn target(numbers: &[u32], requested: Option<u32>) -> Result<Option<u32>, String> {
+        target_version(&versions(numbers), requested).map(|v| v.map(|v| v.version))
+    }
+
+    #[test]
+    fn defaults_to_previous_version() {
+        assert_eq!(target(&[3, 2, 1], None), Ok(Some(2)));
+        assert_eq!(target(&[1], None), Ok(None));
+        assert_eq!(target(&[], None), Ok(None));
+    }
+
+    #[test]
+    fn accepts_available_version() {
+        assert_eq!(target(&[3, 2, 1], Some(1)), Ok(Some(1)));
+        assert_eq!(target(&[3, 2, 1], Some(3)), Ok(Some(3)));
+    }
+
+    #[test]
+    fn lists_available_versions_for_missing_one() {
+        assert_eq!(target(&[3, 2, 1], Some(7)), Err("3, 2, 1".to_string()));
+        assert_eq!(target(&[], Some(1)), Err(String::new()));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::error::Error;
+use crate::project::Project;
+use crate::runner::{Runnable, Runner};
+use crate::writer::Writer;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct StatusCommand {
+    /// Name of the project (optional, defaults to current project name)
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// Refresh the status until the deployment is finished
+    #[arg(short, long, action = clap::ArgAction::SetTrue)]
+    watch: bool,
+}
+
+impl Runnable for StatusCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        StatusRunner {
+            command: self.clone(),
+            writer,
+        }
+    }
+}
+
+struct StatusRunner<'a> {
+    command: StatusCommand,
+    writer: &'a Writer,
+}
+
+impl Runner for StatusRunner<'_> {
+    /// Prints the status of the latest deployment of a project
+    async fn run(&mut self) -> Result<(), Error> {
+        let project_name = match &self.command.name {
+            Some(name) => name.to_owned(),
+            None => self.project().await?.name,
+        };
+
+        self.writer.text(&format!(
+            "{} {}...\n\n",
+            console::style("Fetching status of").green().bold(),
+            console::style(&project_name).bold(),
+        ))?;
+
+        let mut status = Project::status_by_name(&project_name)
+            .await
+            .map_err(|e| self.server_error(Some(e.into())))?;
+
+        if self.command.watch {
+            while status.status == "IN_PROGRESS" {
+                self.writer
+                    .text(&format!("{}\n", console::style("In progress...").dim()))?;
+
+                tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+
+                status = Project::status_by_name(&project_name)
+                    .await
+                    .map_err(|e| self.server_error(Some(e.into())))?;
+            }
+        }
+
+        let styled_status = match status.status.as_str() {
+            "FAILED" | "FROZEN" => console::style(&status.status).red(),
+            "IN_PROGRESS" => console::style(&status.status).yellow(),
+            _ => console::style(&status.status).green(),
+        };
+
+        self.writer.text(&format!(
+            "{} {}\n",
+            console::style("Status").bold(),
+            styled_status
+        ))?;
+
+        let errors = status.errors.unwrap_or_default();
+
+        if !errors.is_empty() {
+            self.writer
+                .text(&format!("\n{}\n", console::style("Errors").bold()))?;
+
+            for error in &errors {
+                self.writer
+                    .text(&format!("{}\n", console::style(error).red()))?;
+            }
+        }
+
+        // Sort outputs by name
+        let outputs = status
+            .outputs
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<BTreeMap<_, _>>();
+
+        if !outputs.is_empty() {
+            self.writer
+                .text(&format!("\n{}\n", console::style("Outputs").bold()))?;
+
+            for (key, value) in &outputs {
+                self.writer
+                    .text(&format!("{} {}\n", console::style(key).dim(), value))?;
+            }
+        }
+
+        self.writer.json(json!({
+            "success": true,
+            "status": status.status,
+            "errors": errors,
+            "outputs": outputs,
+        }))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+mod router;
+mod runner;
+use crate::{
+    runner::{Runnable, Runner},
+    writer::Writer,
+};
+use runner::ServeRunner;
+
+#[derive(clap::Args, Clone)]
+pub(crate) struct ServeCommand {
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
+
+    /// Provision local SQL database for the endpoints to use
+    #[arg(long="with-database", visible_aliases=["with-db", "db"])]
+    with_database: bool,
+
+    /// Apply migrations to locally provisioned database
+    ///
+    /// Accepts a path to dir with SQL-files relative to crate's root, defaults to <crate>/migrations/
+    #[arg(short, long = "with-migrations", num_args = 0..=1, default_missing_value = "")]
+    with_migrations: Option<String>,
+
+    /// Provision a queue. Helpful when the endpoints send something to queue.
+    #[arg(long="with-queue", visible_aliases=["queue"])]
+    with_queue: bool,
+
+    /// Rebuild the endpoints and restart local services on every change of .rs files in src/
+    #[arg(long)]
+    watch: bool,
+}
+
+impl Runnable for ServeCommand {
+    fn runner(&self, writer: &Writer) -> impl Runner {
+        ServeRunner {
+            command: self.clone(),
+            writer,
+        }
+    }
+}
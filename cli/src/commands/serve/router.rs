@@ -0,0 +1,217 @@
+use crate::function::{Function, Params};
+
+/// Segment of an endpoint's URL path
+enum Segment {
+    /// Matches the same segment only
+    Literal(String),
+
+    /// Matches any single segment, e.g. `{id}` or `*`
+    Param,
+
+    /// Matches the rest of the path, e.g. `{*rest}` or `{proxy+}`
+    Rest,
+}
+
+impl Segment {
+    fn new(segment: &str) -> Self {
+        if segment.starts_with("{*") || segment.ends_with("+}") {
+            Segment::Rest
+        } else if segment == "*" || (segment.starts_with('{') && segment.ends_with('}')) {
+            Segment::Param
+        } else {
+            Segment::Literal(segment.to_string())
+        }
+    }
+}
+
+struct Route {
+    segments: Vec<Segment>,
+    function: Function,
+}
+
+impl Route {
+    /// Whether the path is the endpoint's one or a subpath of it, the same as in CloudFront
+    fn is_match(&self, path: &[&str]) -> bool {
+        let mut path = path.iter();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Rest => return true,
+                Segment::Param => {
+                    if path.next().is_none() {
+                        return false;
+                    }
+                }
+                Segment::Literal(literal) => {
+                    if path.next() != Some(&literal.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// The more segments and literals the path has, the more specific it is
+    fn specificity(&self) -> (usize, usize) {
+        let literals = self
+            .segments
+            .iter()
+            .filter(|segment| matches!(segment, Segment::Literal(_)))
+            .count();
+
+        (self.segments.len(), literals)
+    }
+}
+
+/// Routes requests to endpoints by their URL paths
+pub(crate) struct Router {
+    /// Sorted from the most specific to the least one
+    routes: Vec<Route>,
+
+    /// Serves paths not matched by any other endpoint
+    catch_all: Option<Function>,
+}
+
+impl Router {
+    pub(crate) fn new(endpoints: &[Function]) -> Self {
+        let mut routes = vec![];
+        let mut catch_all = None;
+
+        for function in endpoints {
+            let Params::Endpoint(params) = &function.params else {
+                continue;
+            };
+
+            if params.catch_all {
+                catch_all = Some(function.clone());
+                continue;
+            }
+
+            // Aliases are routed the same as the main path
+            for url_path in params.url_paths() {
+                routes.push(Route {
+                    segments: split(url_path).map(Segment::new).collect(),
+                    function: function.clone(),
+                });
+            }
+        }
+
+        routes.sort_by_key(|route| std::cmp::Reverse(route.specificity()));
+        Router { routes, catch_all }
+    }
+
+    /// The endpoint serving the path, if any
+    pub(crate) fn find(&self, path: &str) -> Option<&Function> {
+        let path = split(path).collect::<Vec<_>>();
+
+        self.routes
+            .iter()
+            .find(|route| route.is_match(&path))
+            .map(|route| &route.function)
+            .or(self.catch_all.as_ref())
+    }
+}
+
+/// Non-empty segments of a URL path
+fn split(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kinetics_parser::Role;
+
+    fn endpoint(name: &str, params: &str) -> Function {
+        Function {
+            name: name.to_string(),
+            is_deploying: true,
+            role: Role::Endpoint,
+            params: Params::Endpoint(syn::parse_str(params).unwrap()),
+            project: serde_json::from_value(serde_json::json!({
+                "name": "test",
+                "url": "https://test.kinetics.app",
+                "kvdb": [],
+            }))
+            .unwrap(),
+        }
+    }
+
+    fn find<'a>(router: &'a Router, path: &str) -> Option<&'a str> {
+        router.find(path).map(|function| function.name.as_str())
+    }
+
+    #[test]
+    fn matches_params() {
+        let router = Router::new(&[endpoint("User", r#"url_path = "/users/{id}""#)]);
+
+        assert_eq!(find(&router, "/users/1"), Some("User"));
+        assert_eq!(find(&router, "/users/1/"), Some("User"));
+        assert_eq!(find(&router, "/users"), None);
+        assert_eq!(find(&router, "/orders/1"), None);
+    }
+
+    #[test]
+    fn matches_rest_segments() {
+        let router = Router::new(&[
+            endpoint("Files", r#"url_path = "/files/{proxy+}""#),
+            endpoint("Assets", r#"url_path = "/assets/{*rest}""#),
+        ]);
+
+        assert_eq!(find(&router, "/files/a/b/c.txt"), Some("Files"));
+        assert_eq!(find(&router, "/files"), Some("Files"));
+        assert_eq!(find(&router, "/assets/css/main.css"), Some("Assets"));
+        assert_eq!(find(&router, "/images/logo.png"), None);
+    }
+
+    #[test]
+    fn matches_subpaths() {
+        let router = Router::new(&[endpoint("Users", r#"url_path = "/users""#)]);
+
+        assert_eq!(find(&router, "/users"), Some("Users"));
+        assert_eq!(find(&router, "/users/1/orders"), Some("Users"));
+        assert_eq!(find(&router, "/"), None);
+    }
+
+    #[test]
+    fn matches_aliases() {
+        let router = Router::new(&[endpoint(
+            "Users",
+            r#"url_path = ["/users", "/v1/users/{id}"]"#,
+        )]);
+
+        assert_eq!(find(&router, "/users"), Some("Users"));
+        assert_eq!(find(&router, "/v1/users/1"), Some("Users"));
+        assert_eq!(find(&router, "/v1"), None);
+    }
+
+    #[test]
+    fn prefers_more_specific_paths() {
+        // Declared from the least specific one, so the order is up to the router
+        let router = Router::new(&[
+            endpoint("Root", r#"url_path = "/{proxy+}""#),
+            endpoint("Users", r#"url_path = "/users""#),
+            endpoint("User", r#"url_path = "/users/{id}""#),
+            endpoint("Me", r#"url_path = "/users/me""#),
+        ]);
+
+        assert_eq!(find(&router, "/users/me"), Some("Me"));
+        assert_eq!(find(&router, "/users/1"), Some("User"));
+        assert_eq!(find(&router, "/users"), Some("Users"));
+        assert_eq!(find(&router, "/orders"), Some("Root"));
+    }
+
+    #[test]
+    fn falls_back_to_catch_all() {
+        let router = Router::new(&[
+            endpoint("Fallback", r#"url_path = "/app", catch_all = true"#),
+            endpoint("Users", r#"url_path = "/users""#),
+        ]);
+
+        assert_eq!(find(&router, "/users/1"), Some("Users"));
+        assert_eq!(find(&router, "/orders"), Some("Fallback"));
+        assert_eq!(find(&router, "/"), Some("Fallback"));
+    }
+}
@@ -0,0 +1,412 @@
+use super::router::Router;
+use crate::commands::invoke::{Docker, LocalServices};
+use crate::commands::serve::ServeCommand;
+use crate::config::build_config;
+use crate::error::Error;
+use crate::function::{Function, Role};
+use crate::process::Process;
+use crate::project::{Project, Watcher};
+use crate::runner::Runner;
+use crate::tools::http::{LocalResponse, LOCAL_RESPONSE_FILE_ENV};
+use crate::writer::Writer;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bytes::Bytes;
+use eyre::Context;
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Incoming, server::conn::http1, service::service_fn, Request, Response};
+use hyper_util::rt::TokioIo;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+
+pub(crate) struct ServeRunner<'a> {
+    pub(crate) command: ServeCommand,
+    pub(crate) writer: &'a Writer,
+}
+
+/// Everything needed to handle a request, shared by all connections
+struct Server {
+    router: Router,
+
+    /// JSON body of 404 responses, from kinetics.toml
+    not_found: Option<String>,
+
+    /// The staged crate with local binaries of the endpoints
+    invoke_dir: PathBuf,
+
+    /// Env vars of the local services
+    environment: HashMap<String, String>,
+
+    writer: Writer,
+}
+
+impl Server {
+    /// Run the endpoint matching the request, or respond with 404
+    async fn handle(&self, request: Request<Incoming>) -> Response<Full<Bytes>> {
+        let method = request.method().to_string();
+
+        let url_path = request
+            .uri()
+            .path_and_query()
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| "/".into());
+
+        let Some(function) = self.router.find(request.uri().path()) else {
+            self.log(&method, &url_path, "-", 404);
+
+            return json_response(
+                404,
+                self.not_found
+                    .clone()
+                    .unwrap_or_else(|| r#"{"errors": ["Not found"]}"#.into()),
+            );
+        };
+
+        match self.invoke(function, request).await {
+            Ok(response) => {
+                self.log(
+                    &method,
+                    &url_path,
+                    &function.name,
+                    response.status().as_u16(),
+                );
+                response
+            }
+            Err(e) => {
+                self.log(&method, &url_path, &function.name, 502);
+
+                let _ = self.writer.text(&format!(
+                    "{}\n{e:#}\n",
+                    console::style("Error:").red().bold()
+                ));
+
+                json_response(
+                    502,
+                    serde_json::json!({"errors": [format!("Endpoint {} failed", function.name)]})
+                        .to_string(),
+                )
+            }
+        }
+    }
+
+    /// Run the local binary of the endpoint with the request, and read the response it recorded
+    async fn invoke(
+        &self,
+        function: &Function,
+        request: Request<Incoming>,
+    ) -> eyre::Result<Response<Full<Bytes>>> {
+        let (parts, body) = request.into_parts();
+
+        let body = body
+            .collect()
+            .await
+            .wrap_err("Failed to read request body")?
+            .to_bytes();
+
+        // Pass binary bodies base64-encoded, same as function URLs do
+        let payload_env = match std::str::from_utf8(&body) {
+            Ok(text) => ("KINETICS_INVOKE_PAYLOAD", text.to_string()),
+            Err(_) => (
+                "KINETICS_INVOKE_PAYLOAD_BASE64",
+                BASE64_STANDARD.encode(&body),
+            ),
+        };
+
+        let headers = parts
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect::<HashMap<_, _>>();
+
+        let url_path = parts
+            .uri
+            .path_and_query()
+            .map(|path| path.to_string())
+            .unwrap_or_else(|| "/".into());
+
+        let response_path =
+            std::env::temp_dir().join(format!("kinetics-serve-{}.json", uuid::Uuid::new_v4()));
+
+        let output = tokio::process::Command::new("cargo")
+            .args([
+                "run",
+                "--quiet",
+                "--bin",
+                &format!("{}Local", function.name),
+            ])
+            .envs(&self.environment)
+            .envs(function.environment())
+            .env(payload_env.0, payload_env.1)
+            .env("KINETICS_INVOKE_HEADERS", serde_json::to_string(&headers)?)
+            .env("KINETICS_INVOKE_URL_PATH", url_path)
+            .env("KINETICS_INVOKE_METHOD", parts.method.as_str())
+            .env(LOCAL_RESPONSE_FILE_ENV, &response_path)
+            .current_dir(&self.invoke_dir)
+            .output()
+            .await
+            .wrap_err("Failed to execute cargo run")?;
+
+        // Show logs of the endpoint, the same as `kinetics invoke` does
+        for line in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .chain(String::from_utf8_lossy(&output.stderr).lines())
+        {
+            self.writer
+                .text(&format!("{}\n", console::style(line).dim()))?;
+        }
+
+        // The response is not recorded when the endpoint fails
+        let recorded = std::fs::read(&response_path).map_err(|_| {
+            eyre::eyre!(
+                "Endpoint returned no response, exited with {}",
+                output.status
+            )
+        });
+
+        let _ = std::fs::remove_file(&response_path);
+
+        let local_response = serde_json::from_slice::<LocalResponse>(&recorded?)
+            .wrap_err("Failed to parse the endpoint response")?;
+
+        let mut response = Response::builder().status(local_response.status);
+
+        for (name, value) in local_response.headers {
+            response = response.header(name, value);
+        }
+
+        Ok(response.body(Full::new(Bytes::from(
+            BASE64_STANDARD
+                .decode(local_response.body)
+                .wrap_err("Failed to decode the endpoint response body")?,
+        )))?)
+    }
+
+    /// Print a line per request, with the endpoint that served it
+    fn log(&self, method: &str, url_path: &str, function: &str, status: u16) {
+        let status = if status < 400 {
+            console::style(status).green()
+        } else {
+            console::style(status).red()
+        };
+
+        let _ = self.writer.text(&format!(
+            "{} {} {} {status}\n",
+            console::style(method).bold(),
+            url_path,
+            console::style(format!("→ {function}")).dim(),
+        ));
+    }
+}
+
+/// A response with JSON body, returned by the server itself
+fn json_response(status: u16, body: String) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = http::StatusCode::from_u16(status).unwrap_or_default();
+
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+
+    response
+}
+
+impl ServeRunner<'_> {
+    /// Build local binaries of all the endpoints at once, instead of on the first request
+    fn build(&self, endpoints: &[Function], invoke_dir: &Path) -> eyre::Result<()> {
+        self.writer
+            .text(&format!(
+                "\n{} {}...\n",
+                console::style("Building").green().bold(),
+                console::style(format!("{} endpoint(s)", endpoints.len())).bold(),
+            ))
+            .map_err(|e| eyre::eyre!(e))?;
+
+        let mut args = vec!["build".to_string()];
+
+        for endpoint in endpoints {
+            args.extend(["--bin".to_string(), format!("{}Local", endpoint.name)]);
+        }
+
+        let child = Command::new("cargo")
+            .args(args)
+            .current_dir(invoke_dir)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .wrap_err("Failed to execute cargo build")?;
+
+        let mut process = Process::new(child, self.writer);
+        let status = process.log()?;
+
+        if !status.success() {
+            process.print_error()?;
+
+            return Err(eyre::eyre!(
+                "Build failed with status {}: {}",
+                status,
+                process.errors_output()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Parse the project, build the endpoints and start the local services for them
+    ///
+    /// The services are stopped once the returned docker instance is dropped.
+    async fn start(&self, project: &Project) -> eyre::Result<(Arc<Server>, Docker<'_>)> {
+        let kinetics_path = PathBuf::from(build_config()?.kinetics_path);
+
+        let endpoints = project
+            .parse(kinetics_path.clone(), &[], false)?
+            .into_iter()
+            .filter(|function| matches!(function.role, Role::Endpoint))
+            .collect::<Vec<_>>();
+
+        if endpoints.is_empty() {
+            return Err(Error::new(
+                "No endpoints",
+                Some("The project has no endpoints to serve."),
+            )
+            .into());
+        }
+
+        let invoke_dir = kinetics_path.join(&project.name);
+        self.build(&endpoints, &invoke_dir)?;
+
+        // If --with-migrations comes with the default "" value, the default path is used
+        let migrations_path = self
+            .command
+            .with_migrations
+            .clone()
+            .filter(|path| !path.is_empty());
+
+        let (docker, environment) = LocalServices {
+            with_database: self.command.with_database,
+            with_migrations: self.command.with_migrations.is_some(),
+            migrations_path,
+            with_queue: self.command.with_queue,
+            table: None,
+        }
+        .start(project, self.writer)
+        .await?;
+
+        let server = Server {
+            router: Router::new(&endpoints),
+            not_found: project.not_found.clone(),
+            invoke_dir,
+            environment,
+            writer: Writer::new(self.writer.is_structured()),
+        };
+
+        Ok((Arc::new(server), docker))
+    }
+}
+
+/// Wait for changes of the source code, forever if not watching
+async fn changed(watcher: Option<&mut Watcher>) -> eyre::Result<()> {
+    match watcher {
+        Some(watcher) => watcher.changed().await,
+        None => std::future::pending().await,
+    }
+}
+
+impl Runner for ServeRunner<'_> {
+    /// Serve all endpoints of the project locally, until interrupted with Ctrl-C
+    ///
+    /// In watch mode the endpoints are rebuilt and the services restarted on every change.
+    async fn run(&mut self) -> Result<(), Error> {
+        let project = self.project().await?;
+
+        let listener = TcpListener::bind(("127.0.0.1", self.command.port))
+            .await
+            .wrap_err(format!("Failed to listen on port {}", self.command.port))?;
+
+        let mut watcher = match self.command.watch {
+            true => Some(Watcher::new(&project)?),
+            false => None,
+        };
+
+        'serve: loop {
+            // Failures, e.g. compilation errors, are expected to be fixed by the next change
+            let running = match self.start(&project).await {
+                Ok(running) => Some(running),
+                Err(e) if watcher.is_some() => {
+                    self.writer.text(&format!(
+                        "\n{} {}\n",
+                        console::style("Error:").red().bold(),
+                        Error::from(e)
+                    ))?;
+
+                    None
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            if running.is_some() {
+                self.writer.text(&format!(
+                    "\n{} {}\n",
+                    console::style("Serving at").green().bold(),
+                    console::style(format!("http://localhost:{}", self.command.port)).underlined(),
+                ))?;
+            }
+
+            self.writer.text(&format!(
+                "{}\n\n",
+                console::style(if watcher.is_some() {
+                    "Watching for changes, press Ctrl-C to stop"
+                } else {
+                    "Press Ctrl-C to stop"
+                })
+                .dim(),
+            ))?;
+
+            loop {
+                tokio::select! {
+                    accepted = listener.accept(), if running.is_some() => {
+                        let (stream, _) = accepted.wrap_err("Failed to accept a connection")?;
+                        let Some((server, _)) = &running else { continue };
+                        let server = server.clone();
+
+                        tokio::spawn(async move {
+                            let service = service_fn(move |request| {
+                                let server = server.clone();
+                                async move { Ok::<_, Infallible>(server.handle(request).await) }
+                            });
+
+                            if let Err(e) = http1::Builder::new()
+                                .serve_connection(TokioIo::new(stream), service)
+                                .await
+                            {
+                                log::debug!("Connection failed: {e:?}");
+                            }
+                        });
+                    }
+
+                    result = changed(watcher.as_mut()) => {
+                        result?;
+                        break;
+                    }
+
+                    _ = tokio::signal::ctrl_c() => break 'serve,
+                }
+            }
+
+            self.writer.text(&format!(
+                "\n{}\n",
+                console::style("Changes detected, restarting...")
+                    .green()
+                    .bold()
+            ))?;
+        }
+
+        self.writer
+            .text(&format!("\n{}\n", console::style("Stopped").green().bold()))?;
+
+        Ok(())
+    }
+}
@@ -2,6 +2,41 @@ use crate::{function::Function, project::Project};
 use async_trait::async_trait;
 use std::collections::HashMap;
 
+/// Progress of a deployment, reported to [DeployConfig::on_event]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeployEvent {
+    /// The function has been built and bundled
+    Built {
+        function: String,
+    },
+
+    /// The bundle of the function has been uploaded
+    Uploaded {
+        function: String,
+    },
+
+    /// The bundle of the function has not changed since the last upload
+    UploadSkipped {
+        function: String,
+    },
+
+    UploadFailed {
+        function: String,
+        error: String,
+    },
+
+    /// All bundles are uploaded and the stack is being provisioned
+    ProvisioningStarted,
+
+    /// The stack is already up to date
+    ProvisioningSkipped,
+
+    ProvisioningFinished,
+    ProvisioningFailed {
+        error: String,
+    },
+}
+
 #[async_trait]
 pub trait DeployConfig: Send + Sync {
     async fn deploy(
@@ -11,4 +46,10 @@ pub trait DeployConfig: Send + Sync {
         functions: &[Function],
     ) -> eyre::Result<bool>;
     async fn upload(&self, function: &mut Function) -> eyre::Result<bool>;
+
+    /// Called on every step of the deployment, e.g. to render the progress in a custom UI
+    ///
+    /// Events of different functions might arrive concurrently, as the functions are
+    /// uploaded in parallel. Does nothing by default.
+    fn on_event(&self, _event: DeployEvent) {}
 }
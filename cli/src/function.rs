@@ -1,5 +1,6 @@
 use crate::api::upload;
 use crate::api::{client::Client, func};
+use crate::config::build_config;
 use crate::config::deploy::DeployConfig;
 use crate::error::Error;
 use crate::project::Project;
@@ -8,9 +9,10 @@ use crc_fast::{CrcAlgorithm::Crc64Nvme, Digest};
 use eyre::{eyre, ContextCompat, WrapErr};
 use reqwest::StatusCode;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
+use users::{get_current_gid, get_current_uid};
 
 // Re-export types from kinetics-parser
 pub use kinetics_parser::{Params, ParsedFunction, Role};
@@ -70,6 +72,18 @@ impl Function {
             .join("bootstrap.zip")
     }
 
+    /// Checksum of the bundle, the same one the backend uses to detect changes
+    pub async fn bundle_checksum(&self) -> eyre::Result<String> {
+        let data = tokio::fs::read(self.bundle_path()).await?;
+        Ok(Self::checksum(&data))
+    }
+
+    fn checksum(data: &[u8]) -> String {
+        let mut digest = Digest::new(Crc64Nvme);
+        digest.update(data);
+        base64::prelude::BASE64_STANDARD.encode(digest.finalize().to_be_bytes())
+    }
+
     /// Call the /upload endpoint to get the presigned URL and upload the file
     /// Returns a boolean indicating whether the resource has been updated.
     pub async fn upload(
@@ -84,11 +98,9 @@ impl Function {
         let path = self.bundle_path();
         let data = tokio::fs::read(&path).await?;
 
-        let mut digest = Digest::new(Crc64Nvme);
-        digest.update(&data);
         let body = upload::Request {
             name: self.name.clone(),
-            checksum: base64::prelude::BASE64_STANDARD.encode(digest.finalize().to_be_bytes()),
+            checksum: Self::checksum(&data),
         };
 
         log::debug!(
@@ -130,10 +142,13 @@ impl Function {
     /// Env vars to be added to function's runtime
     ///
     /// These are env vars assigned to the function in macro definition
-    /// as well as those defined globally in .env file.
+    /// as well as those defined globally in kinetics.toml and .env file.
     pub fn environment(&self) -> HashMap<String, String> {
         let mut env = self.project.environment().clone();
         env.extend(self.params.environment().clone());
+
+        // The function can't override vars kinetics relies on, e.g. to find FIFO queues
+        env.extend(self.project.reserved_environment());
         env
     }
 
@@ -188,16 +203,140 @@ impl Function {
     }
 }
 
+/// Image with a pinned toolchain for reproducible builds
+const BUILD_IMAGE: &str = "ghcr.io/cargo-lambda/cargo-lambda:1.8.5";
+
+/// Dir in the build root used as cargo home in Docker, caching the registry across builds
+///
+/// A host dir rather than a named volume, so that it's writable by the user the container runs as.
+const BUILD_CARGO_HOME: &str = ".docker-cargo";
+
+/// Closest dir of a cargo workspace containing the crate, or the crate dir itself
+fn workspace_root(path: &Path) -> &Path {
+    path.ancestors()
+        .find(|dir| {
+            std::fs::read_to_string(dir.join("Cargo.toml"))
+                .ok()
+                .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+                .is_some_and(|manifest| manifest.contains_key("workspace"))
+        })
+        .unwrap_or(path)
+}
+
+/// Check whether Docker daemon is available
+async fn is_docker_available() -> bool {
+    tokio::process::Command::new("docker")
+        .arg("info")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .is_ok_and(|status| status.success())
+}
+
+/// Group compiler diagnostics by function
+///
+/// Binaries are compiled in parallel, so their diagnostics come interleaved. Diagnostics
+/// pointing to a function's bin file are printed together under the function name,
+/// the rest (e.g. errors in the shared library code) go first in the original order.
+fn group_diagnostics(lines: &[String], functions: &[Function]) -> String {
+    let mut shared = vec![];
+    let mut grouped: Vec<(&str, Vec<String>)> = vec![];
+
+    for block in lines.split(|line| line.trim().is_empty()) {
+        if block.is_empty() {
+            continue;
+        }
+
+        let block_text = block.join("\n");
+
+        let function = functions.iter().find(|f| {
+            block
+                .iter()
+                .any(|line| line.contains(&format!("src/bin/{}.rs", f.name)))
+        });
+
+        match function {
+            Some(function) => match grouped.iter_mut().find(|(name, _)| *name == function.name) {
+                Some((_, blocks)) => blocks.push(block_text),
+                None => grouped.push((&function.name, vec![block_text])),
+            },
+
+            None => shared.push(block_text),
+        }
+    }
+
+    let mut output = shared;
+
+    for (name, blocks) in grouped {
+        output.push(format!(
+            "{}\n\n{}",
+            console::style(name).bold(),
+            blocks.join("\n\n")
+        ));
+    }
+
+    output.join("\n\n")
+}
+
 pub async fn build(
     functions: &[Function],
     total_progress: &indicatif::ProgressBar,
+
+    // Build inside a Docker container instead of the host toolchain
+    is_docker: bool,
 ) -> eyre::Result<()> {
     let Some(Function { project, .. }) = functions.iter().next() else {
         return Err(eyre!("Attempted to build an empty function list"));
     };
 
-    total_progress.set_message("Starting cargo...");
-    let mut cmd = tokio::process::Command::new("cargo");
+    let is_docker = if is_docker && !is_docker_available().await {
+        total_progress.println(format!(
+            "    {} Docker is not available, building on the host",
+            console::style("Warning").yellow().bold(),
+        ));
+
+        false
+    } else {
+        is_docker
+    };
+
+    let mut cmd = if is_docker {
+        total_progress.set_message("Starting cargo in Docker...");
+        let mut cmd = tokio::process::Command::new("docker");
+        let root = workspace_root(&project.path);
+        let cargo_home = PathBuf::from(build_config()?.kinetics_path).join(BUILD_CARGO_HOME);
+
+        // Otherwise Docker creates the dir owned by root
+        std::fs::create_dir_all(&cargo_home).wrap_err("Failed to create cargo dir for Docker")?;
+
+        // The workspace is mounted at the same path as on the host, so that the target dir
+        // is reused across builds and compiler diagnostics point to the host files. The build
+        // runs as the current user, otherwise the artifacts on the host end up owned by root.
+        cmd.arg("run")
+            .arg("--rm")
+            .arg("--user")
+            .arg(format!("{}:{}", get_current_uid(), get_current_gid()))
+            .arg("--volume")
+            .arg(format!("{}:{}", root.display(), root.display()))
+            .arg("--volume")
+            .arg(format!("{}:/cargo", cargo_home.display()))
+            .arg("--env")
+            .arg("CARGO_HOME=/cargo")
+            // The user has no home dir in the image
+            .arg("--env")
+            .arg("HOME=/tmp")
+            .arg("--workdir")
+            .arg(&project.path)
+            .arg(BUILD_IMAGE)
+            .arg("cargo");
+
+        cmd
+    } else {
+        total_progress.set_message("Starting cargo...");
+        tokio::process::Command::new("cargo")
+    };
+
     cmd.arg("lambda")
         .arg("build")
         .arg("--release")
@@ -209,6 +348,11 @@ pub async fn build(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
+    // Same features the functions have been parsed with, so that gated code is compiled
+    if !project.features.is_empty() {
+        cmd.arg("--features").arg(project.features.join(","));
+    }
+
     for function in functions {
         cmd.arg("--bin").arg(&function.name);
     }
@@ -238,8 +382,90 @@ pub async fn build(
     let status = child.wait().await?;
 
     if !status.success() {
-        return Err(eyre!("{}", error_message_lines.join("\n")));
+        // Cargo reports a failed binary as: could not compile `crate` (bin "Name") due to ...
+        let regex = regex::Regex::new(r#"could not compile `[^`]+` \(bin "([^"]+)"\)"#)?;
+
+        let failed = error_message_lines
+            .iter()
+            .filter_map(|line| regex.captures(line))
+            .map(|captures| captures[1].to_string())
+            .filter(|name| functions.iter().any(|f| &f.name == name))
+            .collect::<Vec<_>>();
+
+        let title = if failed.is_empty() {
+            format!("Failed to build `{}`", project.name)
+        } else {
+            format!("Failed to build {}", failed.join(", "))
+        };
+
+        return Err(eyre!(
+            "{title}\n\n{}",
+            group_diagnostics(&error_message_lines, functions)
+        ));
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_precedence() {
+        let mut project: Project = serde_json::from_value(serde_json::json!({
+            "name": "test",
+            "url": "https://test.kinetics.app",
+            "kvdb": [],
+        }))
+        .unwrap();
+
+        project.fifo_queues = vec!["OrderWorker".into()];
+
+        project.shared_environment = HashMap::from([
+            ("LOG_LEVEL".into(), "info".into()),
+            ("REGION".into(), "eu".into()),
+            ("KINETICS_FIFO_QUEUES".into(), "Shared".into()),
+        ]);
+
+        let params = r#"environment = {"LOG_LEVEL": "debug", "KINETICS_FIFO_QUEUES": "Own"}"#;
+
+        let function = Function {
+            name: "Work".into(),
+            is_deploying: true,
+            role: Role::Worker,
+            params: Params::Worker(syn::parse_str(params).unwrap()),
+            project,
+        };
+
+        let env = function.environment();
+
+        // Shared vars are overridden by the function's ones, and reserved vars by neither
+        assert_eq!(env["REGION"], "eu");
+        assert_eq!(env["LOG_LEVEL"], "debug");
+        assert_eq!(env["KINETICS_FIFO_QUEUES"], "OrderWorker");
+    }
+
+    #[test]
+    fn workspace_root_of_crate() {
+        let dir = std::env::temp_dir().join(format!("kinetics-workspace-{}", std::process::id()));
+        let member = dir.join("workspace").join("member");
+        let standalone = dir.join("standalone");
+        std::fs::create_dir_all(&member).unwrap();
+        std::fs::create_dir_all(&standalone).unwrap();
+
+        std::fs::write(
+            dir.join("workspace").join("Cargo.toml"),
+            "[workspace]\nmembers = [\"member\"]\n",
+        )
+        .unwrap();
+
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"\n").unwrap();
+        std::fs::write(standalone.join("Cargo.toml"), "[package]\nname = \"one\"\n").unwrap();
+
+        assert_eq!(workspace_root(&member), dir.join("workspace"));
+        assert_eq!(workspace_root(&standalone), standalone);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
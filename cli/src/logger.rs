@@ -2,6 +2,7 @@ use std::sync::OnceLock;
 
 use indicatif::MultiProgress;
 use indicatif_log_bridge::LogWrapper;
+use log::LevelFilter;
 
 /// Set up log levels, formatting, and other configurations for the logger
 pub struct Logger {
@@ -12,11 +13,26 @@ static LOGGER: OnceLock<Logger> = OnceLock::new();
 
 impl<'a> Logger {
     pub fn init() -> &'a Self {
+        Self::init_with_verbosity(0)
+    }
+
+    /// Init with the level set by the number of `-v` flags: warn, info, debug, trace
+    ///
+    /// RUST_LOG env var takes precedence over the flags.
+    pub fn init_with_verbosity(verbosity: u8) -> &'a Self {
         LOGGER.get_or_init(|| {
+            let level = match verbosity {
+                0 => LevelFilter::Off,
+                1 => LevelFilter::Warn,
+                2 => LevelFilter::Info,
+                3 => LevelFilter::Debug,
+                _ => LevelFilter::Trace,
+            };
+
             let logger = env_logger::Builder::from_env(
                 // No logs shown by default, only human-friendly messages
-                // Enable logs output with "export RUST_LOG=error" in terminal
-                env_logger::Env::default().default_filter_or("off"),
+                // Enable logs output with "export RUST_LOG=error" in terminal, or with -v flags
+                env_logger::Env::default().default_filter_or(level.as_str()),
             )
             .build();
 
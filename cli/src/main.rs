@@ -38,6 +38,12 @@ struct Cli {
     #[arg(short, long, action = ArgAction::SetTrue)]
     json: bool,
 
+    /// Show logs, repeat for more details: -v warn, -vv info, -vvv debug, -vvvv trace
+    ///
+    /// Set anywhere in the command, e.g. `kinetics deploy -vv`. RUST_LOG env var takes precedence.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
     /// Writer for all outputs
     #[arg(skip)]
     writer: Writer,
@@ -78,8 +84,8 @@ impl Cli {
 
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    Logger::init();
     let mut cli = Cli::parse();
+    Logger::init_with_verbosity(cli.verbose);
     let writer = Writer::new(cli.json);
     cli.set_writer(writer);
 
@@ -100,6 +106,11 @@ async fn main() -> Result<(), Error> {
 
         Commands::Envs(envs) => match envs {
             commands::envs::EnvsCommands::List(cmd) => cli.run(cmd).await,
+            commands::envs::EnvsCommands::Diff(cmd) => cli.run(cmd).await,
+        },
+
+        Commands::Export(export) => match export {
+            commands::export::ExportCommands::Typescript(cmd) => cli.run(cmd).await,
         },
 
         Commands::Func(func) => match func {
@@ -117,15 +128,42 @@ async fn main() -> Result<(), Error> {
 
         Commands::Proj(proj) => match proj {
             commands::proj::ProjCommands::Destroy(cmd) => cli.run(cmd).await,
+            commands::proj::ProjCommands::Export(cmd) => cli.run(cmd).await,
+            commands::proj::ProjCommands::Import(cmd) => cli.run(cmd).await,
             commands::proj::ProjCommands::Rollback(cmd) => cli.run(cmd).await,
             commands::proj::ProjCommands::List(cmd) => cli.run(cmd).await,
+            commands::proj::ProjCommands::Status(cmd) => cli.run(cmd).await,
             commands::proj::ProjCommands::Versions(cmd) => cli.run(cmd).await,
+            commands::proj::ProjCommands::LocalDiff(cmd) => cli.run(cmd).await,
         },
 
         Commands::Init(cmd) => cli.run(cmd).await,
         Commands::Invoke(cmd) => cli.run(cmd).await,
+        Commands::Serve(cmd) => cli.run(cmd).await,
         Commands::Deploy(cmd) => cli.run(cmd).await,
         Commands::Build(cmd) => cli.run(cmd).await,
         Commands::Login(cmd) => cli.run(cmd).await,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn args_of_commands_dont_conflict() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn verbose_is_accepted_anywhere() {
+        for args in [
+            vec!["kinetics", "-vv", "deploy"],
+            vec!["kinetics", "deploy", "-vv"],
+            vec!["kinetics", "func", "list", "-vv"],
+        ] {
+            assert_eq!(Cli::try_parse_from(&args).unwrap().verbose, 2, "{args:?}");
+        }
+    }
+}
@@ -1,7 +1,16 @@
 mod cache;
 mod config_file;
+mod deploy_state;
+mod export;
 mod filehash;
+#[cfg(test)]
+pub(crate) mod fixture;
+mod git;
+mod limits;
 mod parse;
+mod timings;
+mod typescript;
+mod watcher;
 
 /// Runtime templates for different workloads
 mod templates;
@@ -17,11 +26,16 @@ use crate::function::Function;
 use crate::secrets::Secrets;
 use cache::Cache;
 use config_file::ConfigFile;
+pub use deploy_state::DeployState;
+use git::Git;
 use eyre::WrapErr;
 use http::StatusCode;
+use kinetics_parser::Params;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
+pub use timings::Timings;
+pub use watcher::Watcher;
 
 /// Managing user's project
 ///
@@ -41,6 +55,38 @@ pub struct Project {
     pub kvdb: Vec<Kvdb>,
 
     pub observability: Option<Observability>,
+
+    /// Cargo features enabled in the crate, the default ones and the ones requested with `--features`
+    #[serde(skip)]
+    pub features: Vec<String>,
+
+    /// Env vars of all functions from `[environment]` in kinetics.toml
+    #[serde(skip)]
+    pub shared_environment: HashMap<String, String>,
+
+    /// Embed git commit SHA and branch into functions' environment
+    #[serde(skip)]
+    pub embed_git: bool,
+
+    /// Git metadata of the source code, only resolved when embed_git is enabled
+    #[serde(skip)]
+    pub git: Option<Git>,
+
+    /// Names of workers with FIFO queues, resolved on build
+    #[serde(skip)]
+    pub fifo_queues: Vec<String>,
+
+    /// JSON body of 404 responses to paths not matched by any endpoint
+    ///
+    /// Served by CloudFront without invoking functions. A catch-all endpoint takes precedence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_found: Option<String>,
+
+    /// Grant functions access only to the resources they declare, even if they declare none
+    ///
+    /// Otherwise functions declaring no `kvdb` or `secrets` get access to all of them.
+    #[serde(default)]
+    pub strict_access: bool,
 }
 
 /// Project's settings for observability
@@ -57,6 +103,13 @@ impl Project {
             url: String::new(),
             kvdb: Vec::new(),
             observability: None,
+            features: Vec::new(),
+            shared_environment: HashMap::new(),
+            embed_git: false,
+            git: None,
+            fifo_queues: Vec::new(),
+            not_found: None,
+            strict_access: false,
         }
     }
 
@@ -70,6 +123,50 @@ impl Project {
         self
     }
 
+    fn set_features(mut self, features: Vec<String>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Enable cargo features on top of the default ones, e.g. requested with `--features`
+    pub fn with_features(mut self, features: &[String]) -> Self {
+        if !features.is_empty() {
+            self.features = ConfigFile::cargo_toml_features(&self.path, features);
+        }
+
+        self
+    }
+
+    fn set_shared_environment(mut self, environment: HashMap<String, String>) -> Self {
+        self.shared_environment = environment;
+        self
+    }
+
+    fn set_embed_git(mut self, embed_git: bool) -> Self {
+        self.embed_git = embed_git;
+        self
+    }
+
+    fn set_git(mut self, git: Option<Git>) -> Self {
+        self.git = git;
+        self
+    }
+
+    fn set_fifo_queues(mut self, fifo_queues: Vec<String>) -> Self {
+        self.fifo_queues = fifo_queues;
+        self
+    }
+
+    fn set_strict_access(mut self, strict_access: bool) -> Self {
+        self.strict_access = strict_access;
+        self
+    }
+
+    fn set_not_found(mut self, not_found: Option<String>) -> Self {
+        self.not_found = not_found;
+        self
+    }
+
     /// Creates a new project instance by reading `kinetics.toml` from a given file `path`
     ///
     /// Returns default config if kinetics.toml does not exist. In that case the name will be taken
@@ -105,8 +202,8 @@ impl Project {
             .map(|cache| cache.projects.into_values().collect())
     }
 
-    pub fn clear_cache() -> eyre::Result<()> {
-        Cache::clear()
+    pub async fn clear_cache() -> eyre::Result<()> {
+        Cache::clear().await
     }
 
     /// Destroy the project by sending a DELETE request to /projects/{name}
@@ -134,26 +231,12 @@ impl Project {
         version_message: Option<String>,
     ) -> eyre::Result<bool> {
         let client = Client::new(deploy_config.is_some()).await?;
-        let secrets = Secrets::load();
 
         if let Some(config) = deploy_config {
-            return config.deploy(self, secrets, functions).await;
+            return config.deploy(self, Secrets::load(), functions).await;
         }
 
-        let request = stack::deploy::Request {
-            is_hotswap,
-            secrets,
-            version_message,
-            functions: functions
-                .iter()
-                .map(|f| f.into())
-                .collect::<Vec<stack::deploy::FunctionRequest>>(),
-            project: self.clone(),
-        };
-
-        if let Some(errors) = request.validate() {
-            return Err(Error::new("Validation failed", Some(&errors.join("\n"))).into());
-        }
+        let request = self.deploy_request(functions, is_hotswap, version_message)?;
 
         log::debug!(
             "Sending request to deploy:\n{}",
@@ -173,11 +256,26 @@ impl Project {
 
         let status = result.status();
         log::info!("got status from /stack/deploy: {status}");
-        log::info!("got response from /stack/deploy: {}", result.text().await?);
+        let text = result.text().await?;
+        log::info!("got response from /stack/deploy: {text}");
 
         match status {
             StatusCode::OK => eyre::Ok(true),
             StatusCode::NOT_MODIFIED => eyre::Ok(false),
+
+            // Rejected configuration, e.g. an invalid resource, won't pass on retry
+            StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => Err(Error::new(
+                "Deployment request rejected",
+                Some(
+                    serde_json::from_str::<serde_json::Value>(&text)
+                        .ok()
+                        .and_then(|response| response.get("error")?.as_str().map(String::from))
+                        .as_deref()
+                        .unwrap_or("Check the configuration of the project."),
+                ),
+            )
+            .into()),
+
             _ => Err(Error::new(
                 "Deployment request failed",
                 Some("Try again in a few seconds."),
@@ -186,6 +284,44 @@ impl Project {
         }
     }
 
+    /// Validated request to deploy the functions
+    fn deploy_request(
+        &self,
+        functions: &[Function],
+        is_hotswap: bool,
+        version_message: Option<String>,
+    ) -> eyre::Result<stack::deploy::Request> {
+        // Endpoints are routed in the order they come in, so keep it stable between deploys,
+        // otherwise the routing is updated even if nothing has changed
+        let mut functions = functions.iter().collect::<Vec<_>>();
+
+        functions.sort_by_key(|f| {
+            let url_path = match &f.params {
+                Params::Endpoint(params) => Some(&params.url_path),
+                _ => None,
+            };
+
+            (url_path, &f.name)
+        });
+
+        let request = stack::deploy::Request {
+            is_hotswap,
+            secrets: Secrets::load().into_iter().collect(),
+            version_message,
+            functions: functions
+                .into_iter()
+                .map(|f| f.into())
+                .collect::<Vec<stack::deploy::FunctionRequest>>(),
+            project: self.clone(),
+        };
+
+        if let Some(errors) = request.validate() {
+            return Err(Error::new("Validation failed", Some(&errors.join("\n"))).into());
+        }
+
+        Ok(request)
+    }
+
     pub async fn status(&self) -> eyre::Result<stack::status::Response> {
         Self::status_by_name(&self.name).await
     }
@@ -225,10 +361,109 @@ impl Project {
         self.url.to_lowercase()
     }
 
-    /// Globally applied env vars sourced from .env file
+    /// Globally applied env vars sourced from kinetics.toml and .env file
     ///
-    /// No need to store it in Project props, it's not going to be loaded frequently
+    /// No need to store the .env ones in Project props, they are not going to be loaded
+    /// frequently. Vars of .env override the shared ones of kinetics.toml, and the reserved
+    /// ones override both.
     pub fn environment(&self) -> HashMap<String, String> {
-        Envs::load()
+        let mut env = self.shared_environment.clone();
+        env.extend(Envs::load());
+        env.extend(self.reserved_environment());
+        env
+    }
+
+    /// Env vars set by kinetics itself, which take precedence over the ones set by the user
+    ///
+    /// Git metadata is added as KINETICS_GIT_SHA and KINETICS_GIT_BRANCH, if resolved.
+    /// Names of FIFO workers are added as KINETICS_FIFO_QUEUES, for queue clients to find them.
+    pub fn reserved_environment(&self) -> HashMap<String, String> {
+        let mut env = HashMap::new();
+
+        if let Some(git) = &self.git {
+            env.insert("KINETICS_GIT_SHA".into(), git.sha.clone());
+            env.insert("KINETICS_GIT_BRANCH".into(), git.branch.clone());
+        }
+
+        if !self.fifo_queues.is_empty() {
+            env.insert("KINETICS_FIFO_QUEUES".into(), self.fifo_queues.join(","));
+        }
+
+        env
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kinetics_parser::Role;
+
+    fn project() -> Project {
+        serde_json::from_value(serde_json::json!({
+            "name": "test",
+            "url": "https://test.kinetics.app",
+            "kvdb": [],
+        }))
+        .unwrap()
+    }
+
+    fn function(name: &str, role: Role, params: &str) -> Function {
+        let params = match role {
+            Role::Endpoint => Params::Endpoint(syn::parse_str(params).unwrap()),
+            Role::Worker => Params::Worker(syn::parse_str(params).unwrap()),
+            Role::Cron => Params::Cron(syn::parse_str(params).unwrap()),
+        };
+
+        Function {
+            name: name.to_string(),
+            is_deploying: true,
+            role,
+            params,
+            project: project(),
+        }
+    }
+
+    fn functions() -> Vec<Function> {
+        vec![
+            function(
+                "Users",
+                Role::Endpoint,
+                r#"url_path = "/users", environment = {"B": "2", "A": "1", "C": "3"}"#,
+            ),
+            function("Report", Role::Cron, r#"schedule = "rate(1 hour)""#),
+            function(
+                "Orders",
+                Role::Endpoint,
+                r#"url_path = "/orders", headers = {"X-Frame-Options": "DENY", "Cache-Control": "no-store"}"#,
+            ),
+            function("Emails", Role::Worker, ""),
+        ]
+    }
+
+    #[test]
+    fn deploy_request_is_byte_stable() {
+        let request = |functions: &[Function]| {
+            let request = project().deploy_request(functions, false, None).unwrap();
+            serde_json::to_string(&request).unwrap()
+        };
+
+        let mut reversed = functions();
+        reversed.reverse();
+
+        assert_eq!(request(&functions()), request(&functions()));
+        assert_eq!(request(&functions()), request(&reversed));
+    }
+
+    #[test]
+    fn deploy_request_sorts_endpoints_by_path() {
+        let request = project().deploy_request(&functions(), false, None).unwrap();
+
+        let names = request
+            .functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["Emails", "Report", "Orders", "Users"]);
     }
 }
@@ -8,10 +8,18 @@ use chrono::{DateTime, Utc};
 use eyre::{ContextCompat, WrapErr};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File, TryLockError};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 const CACHE_EXPIRES_IN: Duration = Duration::minutes(10);
 
+/// How often to check whether another process has released the lock of the cache
+const LOCK_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Max time to wait for the lock, e.g. while another process is refreshing the cache
+const LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// The structure of entire cache file
 ///
 /// The cache is stored in a file, and gets refreshed automatically when it expires
@@ -23,55 +31,81 @@ pub(super) struct Cache {
 
 impl Cache {
     /// Load the project cache from disk with automatic refresh logic
+    ///
+    /// Concurrent CLI invocations (e.g. in CI matrices) are serialized by an advisory lock,
+    /// so that only one of them refreshes and writes the file at a time.
     pub(super) async fn new() -> eyre::Result<Self> {
-        let cache_path = Self::path()?;
+        Self::from_file(&Self::path()?, Self::load).await
+    }
 
-        let cache: Option<Self> = if !cache_path.exists() {
-            // Create the cache directory if it doesn't exist
-            if let Some(parent) = cache_path.parent() {
-                fs::create_dir_all(parent)
-                    .inspect_err(|e| {
-                        log::error!("Failed to create cache directory {parent:?}: {e:?}")
-                    })
-                    .wrap_err("Failed to create project cache")?;
-            }
+    /// Read the cache file, or refresh it with `load` when it is missing, expired or malformed
+    async fn from_file<F, Fut>(cache_path: &Path, load: F) -> eyre::Result<Self>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = eyre::Result<Self>>,
+    {
+        // The file is replaced atomically, so a fresh cache is read without locking
+        if let Some(cache) = Self::read(cache_path)? {
+            return Ok(cache);
+        }
 
-            None
-        } else {
-            // Read existing cache
-            let cache_content = fs::read_to_string(&cache_path)
-                .inspect_err(|e| log::error!("Failed to read cache file {cache_path:?}: {e:?}"))
-                .wrap_err("Failed to load project cache")?;
+        // Create the cache directory if it doesn't exist
+        if let Some(parent) = cache_path.parent() {
+            fs::create_dir_all(parent)
+                .inspect_err(|e| log::error!("Failed to create cache directory {parent:?}: {e:?}"))
+                .wrap_err("Failed to create project cache")?;
+        }
 
-            match serde_json::from_str(&cache_content) {
-                Ok::<Cache, _>(cache) if Utc::now() - cache.last_updated < CACHE_EXPIRES_IN => {
-                    Some(cache)
-                }
-                // The cache will be updated
-                _ => None,
-            }
-        };
+        // Released when dropped at the end of the function
+        let _lock = Self::lock(&cache_path.with_extension("lock")).await?;
 
-        // Load projects and populate cache if failed to read from disk
-        let cache = match cache {
-            Some(x) => x,
-            None => Self::load().await?,
-        };
+        // Another process might have refreshed the cache while this one was waiting for the lock
+        if let Some(cache) = Self::read(cache_path)? {
+            return Ok(cache);
+        }
+
+        let cache = load().await?;
 
         // Save cache to the file
         let cache_json = serde_json::to_string_pretty(&cache)
             .inspect_err(|e| log::error!("Failed to serialize project cache: {e:?}"))
             .wrap_err("Failed to process cache")?;
 
-        let cache_path = Self::path()?;
+        // Write to a temp file and rename it, so that the cache file is never partially written
+        let tmp_path = cache_path.with_extension("tmp");
 
-        fs::write(&cache_path, cache_json)
+        fs::write(&tmp_path, cache_json)
+            .and_then(|_| fs::rename(&tmp_path, cache_path))
             .inspect_err(|e| log::error!("Failed to write cache file {cache_path:?}: {e:?}"))
             .wrap_err("Failed to write cache")?;
 
         Ok(cache)
     }
 
+    /// Read the cache file, None if it is missing, expired or malformed
+    fn read(cache_path: &Path) -> eyre::Result<Option<Self>> {
+        if !cache_path.exists() {
+            return Ok(None);
+        }
+
+        let cache_content = fs::read_to_string(cache_path)
+            .inspect_err(|e| log::error!("Failed to read cache file {cache_path:?}: {e:?}"))
+            .wrap_err("Failed to load project cache")?;
+
+        Ok(match serde_json::from_str(&cache_content) {
+            Ok::<Cache, _>(cache) if Utc::now() - cache.last_updated < CACHE_EXPIRES_IN => {
+                Some(cache)
+            }
+            Ok(_) => None,
+
+            // A corrupted cache is discarded and reloaded
+            Err(e) => {
+                log::warn!("Discarding malformed cache file {cache_path:?}: {e:?}");
+                None
+            }
+        })
+    }
+
     /// Get a project from cache
     pub(super) fn get(&self, project_name: &str) -> eyre::Result<Project> {
         self.projects
@@ -80,10 +114,13 @@ impl Cache {
             .cloned()
     }
 
-    pub(super) fn clear() -> eyre::Result<()> {
+    pub(super) async fn clear() -> eyre::Result<()> {
         let cache_path = Self::path()?;
 
         if cache_path.exists() {
+            // Wait for an ongoing refresh, so that it doesn't write the cache back afterwards
+            let _lock = Self::lock(&cache_path.with_extension("lock")).await?;
+
             fs::remove_file(&cache_path)
                 .inspect_err(|e| log::error!("Failed to remove cache file {cache_path:?}: {e:?}"))
                 .wrap_err("Failed to clear the projects cache")?;
@@ -92,6 +129,43 @@ impl Cache {
         Ok(())
     }
 
+    /// Acquire an exclusive lock of the cache, waits until other processes release it
+    ///
+    /// Polled with `try_lock`, so that waiting doesn't block the async runtime. Other tasks
+    /// of the same process keep running, including the one holding the lock.
+    async fn lock(lock_path: &Path) -> eyre::Result<File> {
+        let file = File::create(lock_path)
+            .inspect_err(|e| log::error!("Failed to open lock file {lock_path:?}: {e:?}"))
+            .wrap_err("Failed to lock project cache")?;
+
+        let started = Instant::now();
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(file),
+
+                Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => {
+                    tokio::time::sleep(LOCK_RETRY_INTERVAL).await;
+                }
+
+                Err(TryLockError::WouldBlock) => {
+                    return Err(Error::new(
+                        "Timed out waiting for the project cache",
+                        Some(&format!(
+                            "Another kinetics process is holding {lock_path:?}, try again later."
+                        )),
+                    )
+                    .into());
+                }
+
+                Err(TryLockError::Error(e)) => {
+                    log::error!("Failed to lock {lock_path:?}: {e:?}");
+                    return Err(e).wrap_err("Failed to lock project cache");
+                }
+            }
+        }
+    }
+
     /// Get the static cache path for storing project information.
     fn path() -> eyre::Result<PathBuf> {
         Ok(PathBuf::from(build_config()?.kinetics_path).join(".projects"))
@@ -120,3 +194,117 @@ impl Cache {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn waits_for_lock_without_blocking_runtime() {
+        let lock_path =
+            std::env::temp_dir().join(format!("kinetics-cache-{}.lock", uuid::Uuid::new_v4()));
+
+        let first = Cache::lock(&lock_path).await.unwrap();
+
+        // The test runtime is single-threaded, a blocking wait would never let the lock go
+        let second = tokio::spawn({
+            let lock_path = lock_path.clone();
+            async move { Cache::lock(&lock_path).await }
+        });
+
+        tokio::time::sleep(LOCK_RETRY_INTERVAL * 3).await;
+        assert!(!second.is_finished());
+
+        drop(first);
+
+        let second = tokio::time::timeout(LOCK_RETRY_INTERVAL * 10, second)
+            .await
+            .expect("Lock is not acquired after release")
+            .unwrap();
+
+        assert!(second.is_ok());
+        fs::remove_file(&lock_path).unwrap();
+    }
+
+    fn cache_path() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kinetics-cache-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(".projects")
+    }
+
+    /// Loaded cache with a single project, counting the loads
+    async fn load(loads: Arc<AtomicUsize>) -> eyre::Result<Cache> {
+        loads.fetch_add(1, Ordering::SeqCst);
+
+        // Long enough for the other callers to find the cache missing and wait for the lock
+        tokio::time::sleep(LOCK_RETRY_INTERVAL * 3).await;
+
+        Ok(Cache {
+            projects: HashMap::from([(
+                "orders".to_string(),
+                Project::new(PathBuf::new(), "orders".into()),
+            )]),
+            last_updated: Utc::now(),
+        })
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_refreshes_write_valid_cache() {
+        let cache_path = cache_path();
+        let loads = Arc::new(AtomicUsize::new(0));
+
+        let handles = (0..8).map(|_| {
+            let cache_path = cache_path.clone();
+            let loads = Arc::clone(&loads);
+            tokio::spawn(async move { Cache::from_file(&cache_path, || load(loads)).await })
+        });
+
+        for cache in future::join_all(handles).await {
+            assert!(cache.unwrap().unwrap().projects.contains_key("orders"));
+        }
+
+        // The first one refreshes the cache, the rest read it after waiting for the lock
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        let cache = Cache::read(&cache_path)
+            .unwrap()
+            .expect("Cache is not readable");
+        assert!(cache.projects.contains_key("orders"));
+        assert!(!cache_path.with_extension("tmp").exists());
+
+        fs::remove_dir_all(cache_path.parent().unwrap()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn discards_malformed_and_expired_cache() {
+        let cache_path = cache_path();
+        let loads = Arc::new(AtomicUsize::new(0));
+
+        // E.g. a partially written file of an older version
+        fs::write(&cache_path, r#"{"projects": {"orders": "#).unwrap();
+        assert!(Cache::read(&cache_path).unwrap().is_none());
+
+        let cache = Cache::from_file(&cache_path, || load(Arc::clone(&loads)))
+            .await
+            .unwrap();
+
+        assert!(cache.projects.contains_key("orders"));
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        // The reloaded cache replaces the malformed file
+        assert!(Cache::read(&cache_path).unwrap().is_some());
+
+        let expired = Cache {
+            last_updated: Utc::now() - CACHE_EXPIRES_IN,
+            ..cache
+        };
+
+        fs::write(&cache_path, serde_json::to_string(&expired).unwrap()).unwrap();
+        assert!(Cache::read(&cache_path).unwrap().is_none());
+
+        fs::remove_dir_all(cache_path.parent().unwrap()).unwrap();
+    }
+}
@@ -2,12 +2,16 @@ use crate::api::projects::Kvdb;
 use crate::error::Error;
 use crate::project::Project;
 use eyre::{ContextCompat, WrapErr};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// Max number of global secondary indexes of a DynamoDB table
+const MAX_KVDB_INDEXES: usize = 20;
+
 /// FileConfig is the structure of kinetics.toml
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub(super) struct ConfigFile {
     #[serde(default)]
     project: ProjectSection,
@@ -18,22 +22,52 @@ pub(super) struct ConfigFile {
     #[serde(default)]
     kvdb: Vec<Kvdb>,
 
+    /// Env vars shared by all functions, overridden by the ones of a function
+    #[serde(default)]
+    environment: HashMap<String, String>,
+
     #[serde(skip)]
     path: PathBuf,
+
+    /// Cargo features enabled by default in Cargo.toml
+    #[serde(skip)]
+    features: Vec<String>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 struct ProjectSection {
     name: String,
+
+    /// Add git commit SHA and branch to functions' environment
+    #[serde(default)]
+    embed_git: bool,
+
+    /// JSON body of 404 responses to unmatched paths
+    not_found: Option<String>,
+
+    /// Don't grant all resources to functions which declare none
+    #[serde(default)]
+    strict_access: bool,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 struct ObservabilitySection {
     dd_api_key_env: String,
 }
 
 /// FileConfig is the structure of kinetics.toml
 impl ConfigFile {
+    /// Path of kinetics.toml in the project dir
+    pub(super) fn file_path(path: &Path) -> PathBuf {
+        path.join("kinetics.toml")
+    }
+
+    /// The config with the values of `[environment]` cleared, only the names are kept
+    pub(super) fn without_environment_values(mut self) -> Self {
+        self.environment.values_mut().for_each(String::clear);
+        self
+    }
+
     /// Reads a `FileConfig` instance from a given directory path
     ///
     /// This function looks for a `kinetics.toml` file in the specified directory.
@@ -42,14 +76,16 @@ impl ConfigFile {
     /// the project name, the function will fallback to extracting the name from a `Cargo.toml`
     /// file in the same directory.
     pub(super) fn from_path(path: PathBuf) -> eyre::Result<Self> {
-        let config_toml_path = path.join("kinetics.toml");
+        let config_toml_path = Self::file_path(&path);
 
         let Ok(toml_string) = fs::read_to_string(&config_toml_path) else {
             // Return default config if kinetics.toml is not found
             return Ok(Self {
                 project: ProjectSection {
                     name: Self::cargo_toml_name(path.as_path())?,
+                    ..Default::default()
                 },
+                features: Self::cargo_toml_features(path.as_path(), &[]),
                 path,
                 ..Default::default()
             });
@@ -68,6 +104,7 @@ impl ConfigFile {
 
         // Set the path to the directory containing kinetics.toml
         config.path = path.clone();
+        config.features = Self::cargo_toml_features(path.as_path(), &[]);
 
         match config.observability.clone() {
             Some(observability) => {
@@ -81,6 +118,73 @@ impl ConfigFile {
             None => {}
         }
 
+        if let Some(not_found) = &config.project.not_found {
+            if let Err(e) = serde_json::from_str::<serde_json::Value>(not_found) {
+                return Err(eyre::eyre!(
+                    "Invalid not_found in kinetics.toml: {e}
+                    The body of 404 responses must be a valid JSON"
+                ));
+            }
+        }
+
+        for kvdb in &config.kvdb {
+            Self::validate_indexes(kvdb)?;
+
+            if let Some(ttl_attribute) = &kvdb.ttl_attribute {
+                if !(1..=255).contains(&ttl_attribute.len()) {
+                    return Err(eyre::eyre!(
+                        "Invalid ttl_attribute of kvdb {} in kinetics.toml
+                        Attribute names must be 1-255 characters long",
+                        kvdb.name
+                    ));
+                }
+            }
+
+            let Some(arn) = &kvdb.existing_arn else {
+                continue;
+            };
+
+            if !Self::is_valid_table_arn(arn) {
+                return Err(eyre::eyre!(
+                    "Invalid existing_arn of kvdb {} in kinetics.toml: {arn}
+                    Expected a DynamoDB table ARN, e.g. arn:aws:dynamodb:us-east-1:123456789012:table/name",
+                    kvdb.name
+                ));
+            }
+
+            if !kvdb.indexes.is_empty() {
+                return Err(eyre::eyre!(
+                    "kvdb {} in kinetics.toml can't have both existing_arn and indexes
+                    The indexes of an existing table are defined by the table itself",
+                    kvdb.name
+                ));
+            }
+
+            if kvdb.ttl_attribute.is_some() {
+                return Err(eyre::eyre!(
+                    "kvdb {} in kinetics.toml can't have both existing_arn and ttl_attribute
+                    TTL of an existing table is managed by its owner",
+                    kvdb.name
+                ));
+            }
+
+            if kvdb.billing_mode.is_some() {
+                return Err(eyre::eyre!(
+                    "kvdb {} in kinetics.toml can't have both existing_arn and billing_mode
+                    The billing of an existing table is managed by its owner",
+                    kvdb.name
+                ));
+            }
+
+            if kvdb.range_key.is_some() {
+                return Err(eyre::eyre!(
+                    "kvdb {} in kinetics.toml can't have both existing_arn and range_key
+                    The keys of an existing table are defined by the table itself",
+                    kvdb.name
+                ));
+            }
+        }
+
         // If project name is explicitly set in kinetics.toml, return it right away
         if !config.project.name.is_empty() {
             return Ok(config);
@@ -90,6 +194,51 @@ impl ConfigFile {
         Ok(config)
     }
 
+    /// Checks the names of the table's indexes against DynamoDB rules
+    fn validate_indexes(kvdb: &Kvdb) -> eyre::Result<()> {
+        if kvdb.indexes.len() > MAX_KVDB_INDEXES {
+            return Err(eyre::eyre!(
+                "kvdb {} in kinetics.toml has {} indexes, at most {MAX_KVDB_INDEXES} are allowed",
+                kvdb.name,
+                kvdb.indexes.len()
+            ));
+        }
+
+        let is_valid_name = regex::Regex::new(r"^[a-zA-Z0-9_.-]{3,255}$")
+            .is_ok_and(|re| kvdb.indexes.iter().all(|index| re.is_match(&index.name)));
+
+        if !is_valid_name {
+            return Err(eyre::eyre!(
+                "Invalid index name of kvdb {} in kinetics.toml
+                Index names must be 3-255 characters long and consist of letters, digits, underscores, dots and hyphens",
+                kvdb.name
+            ));
+        }
+
+        for (i, index) in kvdb.indexes.iter().enumerate() {
+            if kvdb.indexes[..i]
+                .iter()
+                .any(|other| other.name == index.name)
+            {
+                return Err(eyre::eyre!(
+                    "Duplicate index {} of kvdb {} in kinetics.toml",
+                    index.name,
+                    kvdb.name
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks the ARN is of a DynamoDB table, access to it is checked by the backend
+    fn is_valid_table_arn(arn: &str) -> bool {
+        regex::Regex::new(
+            r"^arn:aws[a-z-]*:dynamodb:[a-z0-9-]+:\d{12}:table/[a-zA-Z0-9_.-]{3,255}$",
+        )
+        .is_ok_and(|re| re.is_match(arn))
+    }
+
     /// Reads Cargo.toml in a given directory and returns the name
     fn cargo_toml_name(path: &Path) -> eyre::Result<String> {
         let cargo_toml_path = path.join("Cargo.toml");
@@ -119,13 +268,64 @@ impl ConfigFile {
 
         Ok(name)
     }
+
+    /// Reads Cargo.toml in a given directory and returns the enabled features
+    ///
+    /// The default features and the `requested` ones are expanded recursively, e.g. with
+    /// `default = ["beta"]` and `beta = ["experimental"]` both `beta` and `experimental` are returned.
+    pub(super) fn cargo_toml_features(path: &Path, requested: &[String]) -> Vec<String> {
+        let mut enabled: Vec<String> = Vec::new();
+
+        for feature in requested {
+            if !enabled.contains(feature) {
+                enabled.push(feature.to_owned());
+            }
+        }
+
+        let Some(features) = fs::read_to_string(path.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Value>().ok())
+            .and_then(|cargo_toml| cargo_toml.get("features").cloned())
+        else {
+            return enabled;
+        };
+
+        let mut queue = vec!["default".to_string()];
+        queue.extend(enabled.iter().cloned());
+
+        while let Some(feature) = queue.pop() {
+            let Some(items) = features.get(&feature).and_then(|f| f.as_array()) else {
+                continue;
+            };
+
+            // Skip dependency features like "dep:name" and "name/feature"
+            for item in items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .filter(|item| !item.contains([':', '/']))
+            {
+                if !enabled.iter().any(|f| f == item) {
+                    enabled.push(item.to_string());
+                    queue.push(item.to_string());
+                }
+            }
+        }
+
+        enabled
+    }
 }
 
 impl TryFrom<ConfigFile> for Project {
     type Error = eyre::Report;
 
     fn try_from(cfg: ConfigFile) -> eyre::Result<Self> {
-        let mut project = Project::new(cfg.path, cfg.project.name).set_kvdb(cfg.kvdb);
+        let mut project = Project::new(cfg.path, cfg.project.name)
+            .set_kvdb(cfg.kvdb)
+            .set_shared_environment(cfg.environment)
+            .set_features(cfg.features)
+            .set_embed_git(cfg.project.embed_git)
+            .set_not_found(cfg.project.not_found)
+            .set_strict_access(cfg.project.strict_access);
 
         if cfg.observability.is_some() {
             let observability = cfg.observability.unwrap();
@@ -139,3 +339,36 @@ impl TryFrom<ConfigFile> for Project {
         Ok(project)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_default_and_requested_features() {
+        let path = std::env::temp_dir().join(format!("kinetics-features-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+
+        fs::write(
+            path.join("Cargo.toml"),
+            r#"
+                [features]
+                default = ["beta"]
+                beta = ["experimental", "serde/derive"]
+                experimental = []
+                admin = ["audit"]
+                audit = []
+            "#,
+        )
+        .unwrap();
+
+        let mut default = ConfigFile::cargo_toml_features(&path, &[]);
+        let mut requested = ConfigFile::cargo_toml_features(&path, &["admin".to_string()]);
+        fs::remove_dir_all(&path).unwrap();
+
+        default.sort();
+        requested.sort();
+        assert_eq!(default, ["beta", "experimental"]);
+        assert_eq!(requested, ["admin", "audit", "beta", "experimental"]);
+    }
+}
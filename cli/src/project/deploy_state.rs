@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const DEPLOY_STATE_FILENAME: &str = ".deployed";
+
+/// Stores checksums of function bundles confirmed by the last successful deployment.
+///
+/// Unlike [super::filehash::FileHash], which tracks what has been built, this tracks what has
+/// been actually provisioned. A bundle uploaded by a deployment which failed afterwards is not
+/// confirmed. Only deployments waited for from this machine are recorded, so the state is not
+/// aware of the ones made elsewhere, e.g. in CI, or started with `--wait=false`.
+#[derive(Debug, Clone)]
+pub struct DeployState {
+    path: PathBuf,
+
+    /// Function name -> checksum of the deployed bundle
+    inner: HashMap<String, String>,
+}
+
+impl DeployState {
+    /// Load the state from the build dir of the project
+    pub fn new(dst: &Path) -> Self {
+        let path = dst.join(DEPLOY_STATE_FILENAME);
+
+        let inner = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        DeployState { path, inner }
+    }
+
+    /// Checksum of the deployed bundle of the function, if it has been deployed
+    pub fn checksum(&self, name: &str) -> Option<&str> {
+        self.inner.get(name).map(String::as_str)
+    }
+
+    /// Names of all deployed functions
+    pub fn functions(&self) -> impl Iterator<Item = &str> {
+        self.inner.keys().map(String::as_str)
+    }
+
+    /// Mark the bundle as deployed, after the stack has been provisioned
+    pub fn confirm(&mut self, name: &str, checksum: &str) {
+        self.inner.insert(name.to_owned(), checksum.to_owned());
+    }
+
+    /// Forget the functions which are not among the given ones
+    pub fn retain<'a>(&mut self, names: impl IntoIterator<Item = &'a str>) {
+        let names = names.into_iter().collect::<std::collections::HashSet<_>>();
+        self.inner.retain(|name, _| names.contains(name.as_str()));
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        Ok(fs::write(
+            &self.path,
+            serde_json::to_string_pretty(&self.inner)?,
+        )?)
+    }
+}
@@ -0,0 +1,232 @@
+use super::config_file::ConfigFile;
+use crate::error::Error;
+use crate::project::Project;
+use eyre::WrapErr;
+use rust_dotenv::dotenv::DotEnv;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+const ENV_FILENAME: &str = ".env";
+const SECRETS_FILENAME: &str = ".env.secrets";
+
+/// Portable configuration of a project
+///
+/// Holds the resolved kinetics.toml and only the names of env vars and secrets,
+/// the values are never exported. Neither are the values of `[environment]` in kinetics.toml.
+#[derive(Debug, Serialize, Deserialize)]
+struct Export {
+    #[serde(flatten)]
+    config: ConfigFile,
+
+    /// Names of env vars defined in .env
+    #[serde(default)]
+    env: Vec<String>,
+
+    /// Names of secrets defined in .env.secrets
+    #[serde(default)]
+    secrets: Vec<String>,
+}
+
+/// Files changed by import
+#[derive(Debug, Default, Serialize)]
+pub struct Imported {
+    pub config: bool,
+
+    /// Names of env vars added to .env, with empty values
+    pub env: Vec<String>,
+
+    /// Names of secrets added to .env.secrets, with empty values
+    pub secrets: Vec<String>,
+}
+
+impl Project {
+    /// Serialize the configuration of the project to TOML
+    pub fn export(&self) -> eyre::Result<String> {
+        let export = Export {
+            config: ConfigFile::from_path(self.path.clone())?.without_environment_values(),
+            env: Self::var_names(&self.path.join(ENV_FILENAME)),
+            secrets: Self::var_names(&self.path.join(SECRETS_FILENAME)),
+        };
+
+        toml::to_string_pretty(&export).wrap_err("Failed to serialize the project config")
+    }
+
+    /// Scaffold the configuration exported by [Project::export] into the project
+    ///
+    /// Existing kinetics.toml is never overwritten. Missing env vars and secrets are appended
+    /// with empty values, to be filled in by the user.
+    pub fn import(&self, content: &str) -> eyre::Result<Imported> {
+        let export: Export = toml::from_str(content).wrap_err(Error::new(
+            "Failed to parse the exported config",
+            Some("Make sure the file was created by `kinetics proj export`."),
+        ))?;
+
+        let mut imported = Imported::default();
+        let config_path = ConfigFile::file_path(&self.path);
+
+        if !config_path.exists() {
+            fs::write(&config_path, toml::to_string_pretty(&export.config)?)
+                .wrap_err(format!("Failed to write {config_path:?}"))?;
+
+            imported.config = true;
+        }
+
+        imported.env = Self::append_vars(&self.path.join(ENV_FILENAME), &export.env)?;
+        imported.secrets = Self::append_vars(&self.path.join(SECRETS_FILENAME), &export.secrets)?;
+        Ok(imported)
+    }
+
+    /// Sorted names of vars in a dotenv file, empty if the file does not exist
+    fn var_names(path: &Path) -> Vec<String> {
+        if !path.exists() {
+            return Vec::new();
+        }
+
+        let mut names = path
+            .to_str()
+            .and_then(|path| DotEnv::load_env(path).ok())
+            .unwrap_or_default()
+            .into_keys()
+            .collect::<Vec<_>>();
+
+        names.sort();
+        names
+    }
+
+    /// Append the vars missing in a dotenv file, returns the names of appended ones
+    fn append_vars(path: &Path, names: &[String]) -> eyre::Result<Vec<String>> {
+        let existing = Self::var_names(path);
+
+        let missing = names
+            .iter()
+            .filter(|name| !existing.contains(name))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(missing);
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .wrap_err(format!("Failed to open {path:?}"))?;
+
+        // Do not glue the first var to the last line of the file
+        if fs::read_to_string(path)
+            .is_ok_and(|content| !content.is_empty() && !content.ends_with('\n'))
+        {
+            writeln!(file).wrap_err(format!("Failed to write {path:?}"))?;
+        }
+
+        for name in &missing {
+            writeln!(file, "{name}=").wrap_err(format!("Failed to write {path:?}"))?;
+        }
+
+        Ok(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project_dir(files: &[(&str, &str)]) -> Project {
+        let path = std::env::temp_dir().join(format!("kinetics-export-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+
+        for (name, content) in files {
+            fs::write(path.join(name), content).unwrap();
+        }
+
+        Project::new(path, "orders".into())
+    }
+
+    fn exported() -> String {
+        let project = project_dir(&[
+            (
+                "kinetics.toml",
+                "[project]\nname = \"orders\"\n\n[environment]\nAPI_URL = \"https://internal.example.com\"\n",
+            ),
+            (".env", "LOG_LEVEL=debug\n"),
+            (".env.secrets", "API_TOKEN=hunter2\n"),
+        ]);
+
+        let content = project.export().unwrap();
+        fs::remove_dir_all(&project.path).unwrap();
+        content
+    }
+
+    #[test]
+    fn exports_names_without_values() {
+        let content = exported();
+
+        for value in ["https://internal.example.com", "debug", "hunter2"] {
+            assert!(!content.contains(value), "{value}");
+        }
+
+        for name in ["API_URL", "LOG_LEVEL", "API_TOKEN"] {
+            assert!(content.contains(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn imports_into_new_project() {
+        let project = project_dir(&[]);
+        let imported = project.import(&exported()).unwrap();
+
+        assert!(imported.config);
+        assert_eq!(imported.env, ["LOG_LEVEL"]);
+        assert_eq!(imported.secrets, ["API_TOKEN"]);
+
+        // The created kinetics.toml is a valid one, with the names of shared env vars
+        let config = ConfigFile::from_path(project.path.clone()).unwrap();
+        let imported_project = Project::try_from(config).unwrap();
+        assert_eq!(imported_project.name, "orders");
+        assert_eq!(imported_project.shared_environment["API_URL"], "");
+
+        assert_eq!(
+            fs::read_to_string(project.path.join(ENV_FILENAME)).unwrap(),
+            "LOG_LEVEL=\n"
+        );
+
+        fs::remove_dir_all(&project.path).unwrap();
+    }
+
+    #[test]
+    fn keeps_existing_config_and_vars() {
+        let config = "[project]\nname = \"payments\"\n";
+
+        let project = project_dir(&[
+            ("kinetics.toml", config),
+            (".env", "LOG_LEVEL=info"),
+            (".env.secrets", "OTHER_TOKEN=abc\n"),
+        ]);
+
+        let imported = project.import(&exported()).unwrap();
+
+        assert!(!imported.config);
+        assert!(imported.env.is_empty());
+        assert_eq!(imported.secrets, ["API_TOKEN"]);
+
+        assert_eq!(
+            fs::read_to_string(project.path.join("kinetics.toml")).unwrap(),
+            config
+        );
+
+        assert_eq!(
+            fs::read_to_string(project.path.join(ENV_FILENAME)).unwrap(),
+            "LOG_LEVEL=info"
+        );
+
+        assert_eq!(
+            fs::read_to_string(project.path.join(SECRETS_FILENAME)).unwrap(),
+            "OTHER_TOKEN=abc\nAPI_TOKEN=\n"
+        );
+
+        fs::remove_dir_all(&project.path).unwrap();
+    }
+}
@@ -14,14 +14,20 @@ pub struct FileHash {
 }
 
 impl FileHash {
-    pub fn new(dst: PathBuf) -> Self {
+    /// Load checksums from the destination dir
+    ///
+    /// With `is_cache_disabled` the existing checksums are ignored, so every file is considered
+    /// changed and gets rewritten. The checksums are then rebuilt from scratch on save.
+    pub fn new(dst: PathBuf, is_cache_disabled: bool) -> Self {
         let path = dst.join(CHECKSUMS_FILENAME);
 
         // Relative path -> hash of the file
         let checksums: HashMap<PathBuf, String> = {
             match fs::read_to_string(&path) {
-                Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-                Err(_) => HashMap::new(),
+                Ok(content) if !is_cache_disabled => {
+                    serde_json::from_str(&content).unwrap_or_default()
+                }
+                _ => HashMap::new(),
             }
         };
 
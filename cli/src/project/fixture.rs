@@ -0,0 +1,103 @@
+use crate::project::Project;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A project written to a temp dir, for tests which build the generated crate
+///
+/// The crate depends on this version of kinetics by path, its other dependencies are
+/// fetched from crates.io, so the tests using it are ignored by default.
+pub(crate) struct Fixture {
+    root: PathBuf,
+    pub(crate) project: Project,
+}
+
+impl Fixture {
+    /// Write the source `files`, e.g. `("src/lib.rs", "pub mod api;")`, along with Cargo.toml
+    pub(crate) fn new(files: &[(&str, &str)]) -> Self {
+        let root = std::env::temp_dir().join(format!("kinetics-fixture-{}", uuid::Uuid::new_v4()));
+        let src = root.join("fixture");
+
+        let manifest = format!(
+            r#"[package]
+name = "fixture"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+kinetics = {{ path = "{}" }}
+http = "^1.0"
+tower = "^0"
+serde_json = "1"
+
+[workspace]
+"#,
+            env!("CARGO_MANIFEST_DIR")
+        );
+
+        for (path, content) in [("Cargo.toml", manifest.as_str())]
+            .into_iter()
+            .chain(files.iter().copied())
+        {
+            let path = src.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        Fixture {
+            project: Project::from_path(src).unwrap(),
+            root,
+        }
+    }
+
+    /// Generate the crate of the functions into the build dir, returns the names of the functions
+    pub(crate) fn parse(&self) -> Vec<String> {
+        self.project
+            .parse(self.root.join("build"), &[], false)
+            .unwrap()
+            .into_iter()
+            .map(|function| function.name)
+            .collect()
+    }
+
+    /// Dir of the generated crate
+    fn crate_dir(&self) -> PathBuf {
+        self.root.join("build").join(&self.project.name)
+    }
+
+    /// Content of a file of the generated crate, e.g. `src/api.rs`
+    pub(crate) fn read(&self, path: &str) -> String {
+        fs::read_to_string(self.crate_dir().join(path)).unwrap()
+    }
+
+    /// Run cargo in the generated crate, e.g. `["build", "--bin", "ApiUsers"]`
+    ///
+    /// The target dir is shared by all fixtures, so the dependencies are compiled once.
+    pub(crate) fn cargo(&self, args: &[&str], envs: &[(&str, String)]) -> Output {
+        Command::new("cargo")
+            .args(args)
+            .env(
+                "CARGO_TARGET_DIR",
+                std::env::temp_dir().join("kinetics-fixture-target"),
+            )
+            .envs(envs.iter().map(|(name, value)| (name, value)))
+            .current_dir(self.crate_dir())
+            .output()
+            .unwrap()
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Fail the test with the output of cargo, unless it succeeded
+pub(crate) fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Git metadata of the project source code
+///
+/// Embedded into functions' environment when `[project] embed_git = true` is set in kinetics.toml.
+#[derive(Debug, Clone)]
+pub struct Git {
+    /// Commit SHA of the HEAD
+    pub sha: String,
+
+    /// Current branch name, "HEAD" in detached state
+    pub branch: String,
+}
+
+impl Git {
+    /// Read the metadata from the repository containing the `path`
+    ///
+    /// Returns None if the path is not in a git repo, or git is not available.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let sha = Self::rev_parse(path, &["HEAD"])?;
+        let branch = Self::rev_parse(path, &["--abbrev-ref", "HEAD"])?;
+        Some(Self { sha, branch })
+    }
+
+    fn rev_parse(path: &Path, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .arg("rev-parse")
+            .args(args)
+            .current_dir(path)
+            .output()
+            .inspect_err(|e| log::warn!("Failed to run git: {e:?}"))
+            .ok()?;
+
+        if !output.status.success() {
+            log::warn!(
+                "git rev-parse failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            return None;
+        }
+
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+}
@@ -0,0 +1,277 @@
+use super::Project;
+use crate::error::Error;
+use crate::function::Function;
+use kinetics_parser::Params;
+
+/// Default quota of cache behaviors per CloudFront distribution
+const MAX_CLOUDFRONT_BEHAVIORS: usize = 25;
+
+/// Each path of an endpoint is routed by two behaviors, for `/path` and `/path/*`
+const BEHAVIORS_PER_ENDPOINT: usize = 2;
+
+/// Quota of resources per CloudFormation stack
+///
+/// Neither Lambda nor SQS limit the number of functions or queues in an account,
+/// it's the stack of the project they all go into which runs out first.
+const MAX_STACK_RESOURCES: usize = 500;
+
+/// Share of a quota after which a warning is shown, in percent
+const WARNING_THRESHOLD: usize = 80;
+
+/// Resources created by a deployment, counted off the parsed functions
+#[derive(Debug, Clone, Default)]
+pub struct Resources {
+    /// Lambda functions, one per function of any role
+    pub functions: usize,
+
+    pub endpoints: usize,
+
+    /// SQS queues, one per worker
+    pub queues: usize,
+
+    pub crons: usize,
+
+    /// Cache behaviors of the project's CloudFront distribution
+    pub behaviors: usize,
+}
+
+impl Resources {
+    /// The least number of CloudFormation resources the stack is made of
+    ///
+    /// A Lambda function per function, a queue and its event source mapping per worker,
+    /// and a schedule per cron. The stack has more of them, e.g. roles and log groups,
+    /// so the actual count is reached earlier.
+    pub fn stack_resources(&self) -> usize {
+        self.functions + self.queues * 2 + self.crons
+    }
+
+    /// One-line summary, e.g. "4 functions, 3 endpoints (6 of 25 CloudFront behaviors), ..."
+    pub fn summary(&self) -> String {
+        format!(
+            "{} functions, {} endpoints ({} of {MAX_CLOUDFRONT_BEHAVIORS} CloudFront behaviors), \
+            {} queues, {} crons, at least {} of {MAX_STACK_RESOURCES} stack resources",
+            self.functions,
+            self.endpoints,
+            self.behaviors,
+            self.queues,
+            self.crons,
+            self.stack_resources()
+        )
+    }
+
+    /// Warnings about quotas which are about to be reached
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+
+        if self.behaviors * 100 >= MAX_CLOUDFRONT_BEHAVIORS * WARNING_THRESHOLD {
+            warnings.push(format!(
+                "{} of {MAX_CLOUDFRONT_BEHAVIORS} CloudFront behaviors are used, \
+                each endpoint path takes {BEHAVIORS_PER_ENDPOINT}",
+                self.behaviors
+            ));
+        }
+
+        if self.stack_resources() * 100 >= MAX_STACK_RESOURCES * WARNING_THRESHOLD {
+            warnings.push(format!(
+                "At least {} of {MAX_STACK_RESOURCES} stack resources are used \
+                by {} functions and {} queues",
+                self.stack_resources(),
+                self.functions,
+                self.queues
+            ));
+        }
+
+        warnings
+    }
+}
+
+impl Project {
+    /// Count the resources of the deployment and check them against AWS quotas
+    ///
+    /// Fails when a quota is exceeded, so that the deployment is not rejected halfway,
+    /// e.g. by CloudFront after the functions are already updated.
+    pub fn check_limits(functions: &[Function]) -> eyre::Result<Resources> {
+        let mut resources = Resources::default();
+
+        for function in functions {
+            resources.functions += 1;
+
+            match &function.params {
+                Params::Endpoint(params) => {
+                    resources.endpoints += 1;
+
+                    // The catch-all endpoint serves the default behavior, which is not counted
+                    if !params.catch_all {
+                        resources.behaviors += BEHAVIORS_PER_ENDPOINT * params.url_paths().count();
+                    }
+                }
+                Params::Worker(_) => resources.queues += 1,
+                Params::Cron(_) => resources.crons += 1,
+            }
+        }
+
+        if resources.behaviors > MAX_CLOUDFRONT_BEHAVIORS {
+            return Err(Error::new(
+                "Too many endpoints",
+                Some(&format!(
+                    "{} endpoints need {} CloudFront behaviors, the quota is {MAX_CLOUDFRONT_BEHAVIORS}. \
+                    Merge some endpoints, e.g. with a wildcard in url_path, or split the project.",
+                    resources.endpoints, resources.behaviors
+                )),
+            )
+            .into());
+        }
+
+        if resources.stack_resources() > MAX_STACK_RESOURCES {
+            return Err(Error::new(
+                "Too many functions",
+                Some(&format!(
+                    "{} functions and {} queues need at least {} CloudFormation resources, \
+                    the quota of a stack is {MAX_STACK_RESOURCES}. Split the project.",
+                    resources.functions,
+                    resources.queues,
+                    resources.stack_resources()
+                )),
+            )
+            .into());
+        }
+
+        Ok(resources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kinetics_parser::Role;
+
+    fn function(name: &str, role: Role, params: &str) -> Function {
+        let params = match role {
+            Role::Endpoint => Params::Endpoint(syn::parse_str(params).unwrap()),
+            Role::Worker => Params::Worker(syn::parse_str(params).unwrap()),
+            Role::Cron => Params::Cron(syn::parse_str(params).unwrap()),
+        };
+
+        Function {
+            name: name.to_string(),
+            is_deploying: true,
+            role,
+            params,
+            project: serde_json::from_value(serde_json::json!({
+                "name": "test",
+                "url": "https://test.kinetics.app",
+                "kvdb": [],
+            }))
+            .unwrap(),
+        }
+    }
+
+    fn endpoints(count: usize) -> Vec<Function> {
+        (0..count)
+            .map(|i| {
+                let params = format!(r#"url_path = "/users/{i}""#);
+                function(&format!("Users{i}"), Role::Endpoint, &params)
+            })
+            .collect()
+    }
+
+    fn workers(count: usize) -> Vec<Function> {
+        (0..count)
+            .map(|i| function(&format!("Worker{i}"), Role::Worker, ""))
+            .collect()
+    }
+
+    fn error(functions: &[Function]) -> String {
+        format!("{:?}", Project::check_limits(functions).unwrap_err())
+    }
+
+    #[test]
+    fn counts_resources_of_functions() {
+        let mut functions = endpoints(2);
+        functions.push(function(
+            "Aliased",
+            Role::Endpoint,
+            r#"url_path = ["/orders", "/v1/orders"]"#,
+        ));
+        functions.extend(workers(2));
+        functions.push(function(
+            "Report",
+            Role::Cron,
+            r#"schedule = "rate(1 hour)""#,
+        ));
+
+        let resources = Project::check_limits(&functions).unwrap();
+        assert_eq!(resources.functions, 6);
+        assert_eq!(resources.endpoints, 3);
+        assert_eq!(resources.behaviors, 8);
+        assert_eq!(resources.queues, 2);
+        assert_eq!(resources.crons, 1);
+        assert_eq!(resources.stack_resources(), 11);
+
+        assert_eq!(
+            resources.summary(),
+            "6 functions, 3 endpoints (8 of 25 CloudFront behaviors), 2 queues, 1 crons, \
+            at least 11 of 500 stack resources"
+        );
+
+        assert!(resources.warnings().is_empty());
+    }
+
+    #[test]
+    fn warns_about_behaviors_at_threshold() {
+        let resources = Project::check_limits(&endpoints(9)).unwrap();
+        assert!(resources.warnings().is_empty());
+
+        // 20 of 25 behaviors is exactly 80%
+        let resources = Project::check_limits(&endpoints(10)).unwrap();
+        assert_eq!(
+            resources.warnings(),
+            ["20 of 25 CloudFront behaviors are used, each endpoint path takes 2"]
+        );
+    }
+
+    #[test]
+    fn rejects_behaviors_over_quota() {
+        assert!(Project::check_limits(&endpoints(12)).is_ok());
+
+        let error = error(&endpoints(13));
+        assert!(error.contains("Too many endpoints"));
+        assert!(error.contains("13 endpoints need 26 CloudFront behaviors, the quota is 25"));
+    }
+
+    #[test]
+    fn does_not_count_behaviors_of_catch_all() {
+        let mut functions = endpoints(12);
+        functions.push(function(
+            "Fallback",
+            Role::Endpoint,
+            r#"url_path = "/", catch_all = true"#,
+        ));
+
+        let resources = Project::check_limits(&functions).unwrap();
+        assert_eq!(resources.endpoints, 13);
+        assert_eq!(resources.behaviors, 24);
+    }
+
+    #[test]
+    fn warns_about_stack_resources_at_threshold() {
+        let resources = Project::check_limits(&workers(133)).unwrap();
+        assert_eq!(resources.stack_resources(), 399);
+        assert!(resources.warnings().is_empty());
+
+        let resources = Project::check_limits(&workers(134)).unwrap();
+        assert_eq!(
+            resources.warnings(),
+            ["At least 402 of 500 stack resources are used by 134 functions and 134 queues"]
+        );
+    }
+
+    #[test]
+    fn rejects_stack_resources_over_quota() {
+        assert!(Project::check_limits(&workers(166)).is_ok());
+
+        let error = error(&workers(167));
+        assert!(error.contains("Too many functions"));
+        assert!(error.contains("167 functions and 167 queues need at least 501"));
+    }
+}
@@ -1,14 +1,20 @@
+use super::deploy_state::DEPLOY_STATE_FILENAME;
 use super::filehash::{FileHash, CHECKSUMS_FILENAME};
+use super::git::Git;
 use super::templates;
+use super::timings::Timings;
 use super::Project;
+use crate::error::Error;
 use crate::function::Function;
 use crate::tools::config::EndpointConfig;
-use eyre::Context;
+use eyre::{Context, ContextCompat};
 use kinetics_parser::{Params, ParsedFunction, Parser, Role};
 use regex::Regex;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Component, Path, PathBuf};
-use std::str::FromStr;
+use std::time::Instant;
+use syn::ext::IdentExt;
 use walkdir::WalkDir;
 
 /// Code parsing methods
@@ -23,23 +29,52 @@ impl Project {
         // This method always returns all functions defined in the project, but relies on this input param
         // to mark the requested functions as requested for deployment
         deploy_functions: &[String],
+
+        // Regenerate all files in the build folder, ignoring the saved checksums
+        is_cache_disabled: bool,
+    ) -> eyre::Result<Vec<Function>> {
+        self.parse_with_timings(
+            dst,
+            deploy_functions,
+            is_cache_disabled,
+            &mut Timings::default(),
+        )
+    }
+
+    /// Same as [Project::parse], recording the durations of the phases
+    pub fn parse_with_timings(
+        &self,
+        dst: PathBuf,
+        deploy_functions: &[String],
+        is_cache_disabled: bool,
+        timings: &mut Timings,
     ) -> eyre::Result<Vec<Function>> {
         // Parse functions from source code
-        let parsed_functions = Parser::new(Some(&self.path))?.functions;
+        let parsed_functions = timings
+            .measure("Parse source code", || {
+                Parser::new(Some(&self.path), &self.features)
+            })?
+            .functions;
+
+        // Functions with the same name would overwrite each other's binaries and resources
+        Self::validate_names(&parsed_functions)?;
+
         let src = &self.path;
         let dst = dst.join(&self.name);
         // Checksums of source files for preventing rewrite existing files
-        let mut checksum = FileHash::new(dst.to_path_buf());
+        let mut checksum = FileHash::new(dst.to_path_buf(), is_cache_disabled);
 
         // Clone user project into the build folder.
-        self.clone(src, &dst, &mut checksum)?;
+        timings.measure("Clone project", || self.clone(src, &dst, &mut checksum))?;
 
         // Create lib.rs exporting a containing module of each parsed function.
-        self.create_lib(src, &dst, &parsed_functions, &mut checksum)?;
+        timings.measure("Generate lib.rs", || {
+            self.create_lib(src, &dst, &parsed_functions, &mut checksum)
+        })?;
+
+        let started = Instant::now();
 
         let relative_manifest_path = Path::new("Cargo.toml");
-        let mut manifest: toml_edit::DocumentMut =
-            fs::read_to_string(src.join(relative_manifest_path))?.parse()?;
         let bin_dir = Path::new("src/bin");
         fs::create_dir_all(dst.join(bin_dir)).wrap_err("Create dir failed")?;
 
@@ -47,13 +82,16 @@ impl Project {
             for is_local in [false, true] {
                 // Create bin file for every parsed function
                 self.create_lambda_bin(&dst, bin_dir, parsed_function, is_local, &mut checksum)?;
-
-                // Add dependencies required to run a lambda to Cargo.toml
-                self.deps(parsed_function, is_local, &mut manifest)?;
             }
         }
 
-        let manifest_string = manifest.to_string();
+        // Cargo.lock is cloned into the build folder along with the source code
+        let manifest_string = Self::staged_manifest(
+            &fs::read_to_string(src.join(relative_manifest_path))?,
+            &parsed_functions,
+            Self::locked_versions(src),
+        )?;
+
         if checksum.update(
             relative_manifest_path.to_path_buf(),
             &FileHash::hash_from_bytes(&manifest_string)
@@ -63,13 +101,40 @@ impl Project {
                 .wrap_err("Failed to write Cargo.toml")?;
         }
 
-        checksum.save().wrap_err("Failed to save checksums")?;
-        self.clear_dir(&dst, &checksum)?;
+        timings.record("Generate bins and Cargo.toml", started);
+
+        timings.measure("Clean up build dir", || -> eyre::Result<()> {
+            checksum.save().wrap_err("Failed to save checksums")?;
+            self.clear_dir(&dst, &checksum)
+        })?;
+
+        // Git metadata is read from the source dir, the build directory is not a git repo
+        let git = if self.embed_git {
+            let git = Git::from_path(src);
+
+            if git.is_none() {
+                log::warn!("Not a git repo, skipping git metadata for functions");
+            }
+
+            git
+        } else {
+            None
+        };
+
+        // Queues of FIFO workers are named differently, and require a message group
+        let fifo_queues = parsed_functions
+            .iter()
+            .filter(|f| matches!(&f.params, Params::Worker(worker) if worker.fifo))
+            .map(|f| f.func_name(false))
+            .collect::<eyre::Result<Vec<_>>>()?;
 
         // Create a new project instance for the target build directory
-        let dst_project = Project::from_path(dst.to_path_buf())?;
+        let dst_project = Project::from_path(dst.to_path_buf())?
+            .set_features(self.features.clone())
+            .set_git(git)
+            .set_fifo_queues(fifo_queues);
 
-        parsed_functions
+        let functions = parsed_functions
             .into_iter()
             .map(|f| {
                 let name = f.func_name(false)?;
@@ -81,7 +146,163 @@ impl Project {
                     )
                 })
             })
-            .collect::<eyre::Result<Vec<_>>>()
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Self::validate_queues(&functions)?;
+        self.validate_kvdb(&functions)?;
+        self.validate_catch_all(&functions)?;
+        Ok(functions)
+    }
+
+    /// Check that every queue referenced by an endpoint belongs to a worker of the project
+    ///
+    /// Otherwise the endpoint would fail at runtime sending to a nonexistent queue.
+    fn validate_queues(functions: &[Function]) -> eyre::Result<()> {
+        let workers = functions
+            .iter()
+            .filter(|f| matches!(f.role, Role::Worker))
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+
+        let mut errors = vec![];
+
+        for function in functions {
+            let Params::Endpoint(params) = &function.params else {
+                continue;
+            };
+
+            for queue in params
+                .queues
+                .iter()
+                .filter(|queue| !workers.contains(&queue.as_str()))
+            {
+                let suggestion = workers
+                    .iter()
+                    .map(|worker| (strsim::levenshtein(queue, worker), worker))
+                    .filter(|(distance, _)| *distance <= 3)
+                    .min_by_key(|(distance, _)| *distance)
+                    .map(|(_, worker)| format!(", did you mean {worker}?"))
+                    .unwrap_or_default();
+
+                errors.push(format!(
+                    "{} sends to unknown queue {queue}{suggestion}",
+                    function.name
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            "Unknown queues",
+            Some(&format!(
+                "{}\nQueues must be named after worker functions: {}",
+                errors.join("\n"),
+                workers.join(", ")
+            )),
+        )
+        .into())
+    }
+
+    /// Check that every KV DB table referenced by a function is declared in kinetics.toml
+    fn validate_kvdb(&self, functions: &[Function]) -> eyre::Result<()> {
+        let tables = self
+            .kvdb
+            .iter()
+            .map(|kvdb| kvdb.name.as_str())
+            .collect::<Vec<_>>();
+
+        let errors = functions
+            .iter()
+            .flat_map(|function| {
+                function
+                    .params
+                    .kvdb()
+                    .iter()
+                    .filter(|name| !tables.contains(&name.as_str()))
+                    .map(|name| format!("{} accesses unknown kvdb {name}", function.name))
+            })
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            "Unknown kvdb",
+            Some(&format!(
+                "{}
+Tables must be declared with [[kvdb]] in kinetics.toml",
+                errors.join("\n")
+            )),
+        )
+        .into())
+    }
+
+    /// Check that generated names of functions are unique, including explicit `name` attributes
+    fn validate_names(functions: &[ParsedFunction]) -> eyre::Result<()> {
+        let mut sources = HashMap::<String, &ParsedFunction>::new();
+        let mut duplicates = vec![];
+
+        for function in functions {
+            let name = function.func_name(false)?;
+
+            match sources.get(&name) {
+                Some(other) => duplicates.push(format!(
+                    "{name} is the name of both {}::{} and {}::{}",
+                    other.relative_path,
+                    other.rust_function_name,
+                    function.relative_path,
+                    function.rust_function_name,
+                )),
+                None => {
+                    sources.insert(name, function);
+                }
+            }
+        }
+
+        if duplicates.is_empty() {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            "Duplicate function names",
+            Some(&format!(
+                "{}\nRename one of the functions or set a different `name` attribute.",
+                duplicates.join("\n")
+            )),
+        )
+        .into())
+    }
+
+    /// Check that at most one endpoint of the project handles unmatched paths
+    fn validate_catch_all(&self, functions: &[Function]) -> eyre::Result<()> {
+        let catch_all = functions
+            .iter()
+            .filter(|f| matches!(&f.params, Params::Endpoint(params) if params.catch_all))
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>();
+
+        if let (Some(name), Some(_)) = (catch_all.first(), &self.not_found) {
+            log::warn!(
+                "not_found in kinetics.toml is ignored, unmatched paths are served by {name}"
+            );
+        }
+
+        if catch_all.len() <= 1 {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            "Multiple catch-all endpoints",
+            Some(&format!(
+                "Only one endpoint can have catch_all = true, found: {}",
+                catch_all.join(", ")
+            )),
+        )
+        .into())
     }
 
     /// Clone the project dir to a new directory
@@ -116,7 +337,13 @@ impl Project {
 
             let dst_path = dst.join(src_relative);
 
-            if src_path.is_dir() {
+            // Links might point outside of the project, the build must not read or write there
+            if entry.path_is_symlink() {
+                log::warn!("Skipping symlink {src_relative:?}, only regular files are built");
+                continue;
+            }
+
+            if entry.file_type().is_dir() {
                 fs::create_dir_all(&dst_path).wrap_err("Create dir failed")?;
                 continue;
             }
@@ -130,7 +357,12 @@ impl Project {
 
     /// Remove files that are not present in the source directory
     /// but still exist in the target directory.
+    ///
+    /// Symlinks are never followed, only the links themselves are deleted.
     fn clear_dir(&self, dst: &Path, checksum: &FileHash) -> eyre::Result<()> {
+        let dst_canonical =
+            fs::canonicalize(dst).wrap_err(format!("Failed to resolve the build dir {dst:?}"))?;
+
         for entry in WalkDir::new(dst).into_iter().filter_map(|e| e.ok()) {
             let path = entry.path();
 
@@ -138,21 +370,45 @@ impl Project {
                 continue;
             };
 
+            if src_relative.as_os_str().is_empty() {
+                continue;
+            }
+
+            // The parent is resolved, so that nothing outside the build dir is ever deleted
+            let is_within_dst = path
+                .parent()
+                .and_then(|parent| fs::canonicalize(parent).ok())
+                .is_some_and(|parent| parent.starts_with(&dst_canonical));
+
+            if !is_within_dst {
+                log::warn!("Skipping {path:?} as it is outside of the build dir");
+                continue;
+            }
+
+            // Clone never creates links, so any of them is obsolete
+            if entry.path_is_symlink() {
+                fs::remove_file(path)
+                    .wrap_err(format!("Failed to delete a symlink {src_relative:?}"))?;
+
+                continue;
+            }
+
             // Leave intact:
             // - the `target` folder;
             // - `.checksums` file.
+            // - `.deployed` file.
             // - `Cargo.lock` file.
             // - non *.rs files
             if src_relative.extension().is_some_and(|ext| ext != "rs")
                 || src_relative.strip_prefix("target").is_ok()
-                || src_relative
-                    .to_str()
-                    .is_some_and(|p| p == CHECKSUMS_FILENAME || p == "Cargo.lock")
+                || src_relative.to_str().is_some_and(|p| {
+                    p == CHECKSUMS_FILENAME || p == DEPLOY_STATE_FILENAME || p == "Cargo.lock"
+                })
             {
                 continue;
             };
 
-            if path.is_dir() {
+            if entry.file_type().is_dir() {
                 // Delete all folders except those known from file paths in .checksums.
                 if !checksum.has_folder(src_relative) {
                     fs::remove_dir_all(path).wrap_err(format!(
@@ -185,19 +441,10 @@ impl Project {
         let relative_lib_path = Path::new("src").join("lib.rs");
         let src_lib_rs_path = src.join(&relative_lib_path);
 
-        let modules = functions.iter().filter_map(|f| {
-            // Take the first path component from each module in the src folder, and export it.
-            match Path::new(&f.relative_path)
-                .strip_prefix("src")
-                .ok()?
-                .with_extension("")
-                .components()
-                .next()
-            {
-                Some(Component::Normal(comp)) => comp.to_str().map(String::from),
-                _ => None,
-            }
-        });
+        // Take the top-level module of each function in the src folder, and export it.
+        let modules = functions
+            .iter()
+            .filter_map(|f| Self::module_path(&f.relative_path).into_iter().next());
 
         let lib = if src_lib_rs_path.exists() {
             // Make sure all modules with functions are exported.
@@ -235,9 +482,116 @@ impl Project {
             fs::write(dst.join(&relative_lib_path), lib).wrap_err("Failed to write src/lib.rs")?;
         }
 
+        // Bins import functions by the full module path, e.g. `api::users::create`,
+        // so the nested modules must be public in their parents as well.
+        for function in functions {
+            let module_path = Self::module_path(&function.relative_path);
+
+            // Inline modules are declared in the file of the function itself
+            if !function.inline_modules.is_empty() {
+                let path = dst.join(&function.relative_path);
+
+                let content = fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("Failed to read {path:?}"))?;
+
+                if let Some(exported) = Self::export_module(&content, &function.inline_modules)
+                    .wrap_err_with(|| format!("Failed to export modules of {path:?}"))?
+                {
+                    fs::write(&path, exported)
+                        .wrap_err_with(|| format!("Failed to write {path:?}"))?;
+                }
+            }
+
+            for depth in 1..module_path.len() {
+                let parent = dst
+                    .join("src")
+                    .join(module_path[..depth].iter().collect::<PathBuf>());
+
+                // Either `api.rs` or `api/mod.rs` declares the child modules of `api`
+                let Some(parent_path) = [parent.with_extension("rs"), parent.join("mod.rs")]
+                    .into_iter()
+                    .find(|path| path.exists())
+                else {
+                    continue;
+                };
+
+                let parent = fs::read_to_string(&parent_path)
+                    .wrap_err_with(|| format!("Failed to read {parent_path:?}"))?;
+
+                // The cloned file is rewritten when the source changes, so export it every time
+                if let Some(exported) = Self::export_module(&parent, &module_path[depth..=depth])
+                    .wrap_err_with(|| format!("Failed to export modules of {parent_path:?}"))?
+                {
+                    fs::write(&parent_path, exported)
+                        .wrap_err_with(|| format!("Failed to write {parent_path:?}"))?;
+                }
+            }
+        }
+
         Ok(())
     }
 
+    /// Make the declarations of a module public, e.g. `mod users;` or `pub(crate) mod users {`
+    ///
+    /// The module is looked up by its path within the file, e.g. `["admin", "users"]` for
+    /// `mod admin { mod users { .. } }`, and all the modules along the path are exported.
+    /// Declarations under different `#[cfg]` are exported all. Returns None if there is nothing
+    /// to change, i.e. the modules are public already or not declared in the file.
+    fn export_module(content: &str, path: &[String]) -> eyre::Result<Option<String>> {
+        // The BOM is not a part of the parsed source, so the offsets are shifted by it
+        let source = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let offset = content.len() - source.len();
+        let file = syn::parse_str::<syn::File>(source).wrap_err("Failed to parse the module")?;
+
+        // Byte ranges of visibilities to replace with `pub`
+        let mut replacements = vec![];
+        let mut items = vec![&file.items];
+
+        for name in path {
+            let modules = items
+                .iter()
+                .flat_map(|items| items.iter())
+                .filter_map(|item| match item {
+                    syn::Item::Mod(item) if item.ident.unraw() == name => Some(item),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            for module in &modules {
+                match &module.vis {
+                    syn::Visibility::Public(_) => {}
+                    syn::Visibility::Restricted(vis) => replacements.push(
+                        vis.pub_token.span.byte_range().start
+                            ..vis.paren_token.span.close().byte_range().end,
+                    ),
+                    syn::Visibility::Inherited => {
+                        let start = module.mod_token.span.byte_range().start;
+                        replacements.push(start..start);
+                    }
+                }
+            }
+
+            items = modules
+                .into_iter()
+                .filter_map(|module| module.content.as_ref().map(|(_, items)| items))
+                .collect();
+        }
+
+        if replacements.is_empty() {
+            return Ok(None);
+        }
+
+        let mut exported = content.to_owned();
+
+        // From the end, so that the offsets of the rest stay valid
+        for range in replacements.into_iter().rev() {
+            let replacement = if range.is_empty() { "pub " } else { "pub" };
+            exported.replace_range(range.start + offset..range.end + offset, replacement);
+        }
+
+        Ok(Some(exported))
+    }
+
     /// Create a function with the code necessary to build lambda
     ///
     /// Set up the function according to cargo lambda guides
@@ -254,20 +608,36 @@ impl Project {
         let lambda_path_local = bin_dir.join(format!("{}.rs", function_name));
         let lambda_path = dst.join(&lambda_path_local);
 
-        let fn_import = self.import_statement(
-            &parsed_function.relative_path,
-            &parsed_function.rust_function_name,
-            &self.name,
-        )?;
+        let fn_import = self.import_statement(parsed_function, &self.name)?;
 
         let rust_function_name = parsed_function.rust_function_name.clone();
         let main_code = match &parsed_function.params {
             Params::Endpoint(params) => {
-                let endpoint_config = EndpointConfig::new(&params.url_path);
-                templates::endpoint(&fn_import, &rust_function_name, endpoint_config, is_local)
+                let endpoint_config =
+                    EndpointConfig::new(&params.url_path, &params.url_path_aliases);
+
+                if let Some(schema) = &params.schema {
+                    if !dst.join(schema).is_file() {
+                        return Err(eyre::eyre!(
+                            "Schema file {schema} of function {rust_function_name} not found"
+                        ));
+                    }
+                }
+
+                templates::endpoint(
+                    &fn_import,
+                    &rust_function_name,
+                    endpoint_config,
+                    params,
+                    is_local,
+                )
+            }
+            Params::Worker(params) => {
+                templates::worker(&fn_import, &rust_function_name, params, is_local)
+            }
+            Params::Cron(params) => {
+                templates::cron(&fn_import, &rust_function_name, params, is_local)
             }
-            Params::Worker(_) => templates::worker(&fn_import, &rust_function_name, is_local),
-            Params::Cron(_) => templates::cron(&fn_import, &rust_function_name, is_local),
         };
 
         let item: syn::File = syn::parse_str(&main_code)?;
@@ -283,146 +653,262 @@ impl Project {
         Ok(())
     }
 
-    /// Write dependencies required to run a lambda into Cargo.toml
+    /// Add dependencies required to run the functions to the manifest of the crate
+    ///
+    /// Only the [dependencies] table is modified, the rest of the manifest
+    /// (e.g. [features], [profile.*], [patch.*]) is written to the staged crate verbatim.
+    fn staged_manifest(
+        manifest: &str,
+        functions: &[ParsedFunction],
+        mut locked: HashMap<String, Vec<semver::Version>>,
+    ) -> eyre::Result<String> {
+        let mut manifest: toml_edit::DocumentMut = manifest.parse()?;
+
+        let dependencies = manifest
+            .entry("dependencies")
+            .or_insert(toml_edit::Table::new().into())
+            .as_table_mut()
+            .wrap_err("Invalid [dependencies] section in Cargo.toml")?;
+
+        for parsed_function in functions {
+            for is_local in [false, true] {
+                Self::deps(parsed_function, is_local, dependencies, &mut locked)?;
+            }
+        }
+
+        Ok(manifest.to_string())
+    }
+
+    /// Write dependencies required to run a lambda into the [dependencies] table of Cargo.toml
+    ///
+    /// Dependencies declared by the user are kept as is, so that the versions pinned
+    /// in Cargo.lock are used for the build.
     fn deps(
-        &self,
         parsed_function: &ParsedFunction,
         is_local: bool,
-        doc: &mut toml_edit::DocumentMut,
+        deps: &mut toml_edit::Table,
+        locked: &mut HashMap<String, Vec<semver::Version>>,
     ) -> eyre::Result<()> {
+        let has_schema = matches!(
+            &parsed_function.params,
+            Params::Endpoint(params) if params.schema.is_some()
+        );
+
+        // The 401 response of JWT verification is a JSON
+        let has_jwt = matches!(
+            &parsed_function.params,
+            Params::Endpoint(params) if params.jwt.is_some()
+        );
+
         if matches!(parsed_function.role, Role::Cron | Role::Worker)
-            || (matches!(parsed_function.role, Role::Endpoint) && is_local)
+            || (matches!(parsed_function.role, Role::Endpoint)
+                && (is_local || has_schema || has_jwt))
         {
-            if let Some(serde_json) = doc["dependencies"]["serde_json"]
-                .or_insert(toml_edit::Table::new().into())
-                .as_table_mut()
-            {
-                serde_json.insert("version", toml_edit::value("1.0.149"));
-            }
+            Self::dep(deps, locked, "serde_json", "1.0.149");
+            let is_declared = deps.contains_key("reqwest");
 
-            if let Some(reqwest) = doc["dependencies"]["reqwest"]
-                .or_insert(toml_edit::Item::Table(toml_edit::Table::new()))
-                .as_table_mut()
-            {
-                reqwest.insert("version", toml_edit::value("0.13.1"));
-                reqwest.insert("default-features", toml_edit::value(false));
-                reqwest.insert(
-                    "features",
-                    toml_edit::Array::from_iter(["default-tls"]).into(),
-                );
+            if let Some(reqwest) = Self::dep(deps, locked, "reqwest", "0.13.1") {
+                // The default features of a declared reqwest are up to the user,
+                // TLS is the only one the generated code needs
+                if !is_declared {
+                    reqwest.insert("default-features", toml_edit::value(false));
+                }
+
+                Self::add_features(reqwest, &["default-tls"]);
             }
         }
 
         match parsed_function.role {
             Role::Cron | Role::Worker => {
-                doc["dependencies"]["lambda_runtime"]
-                    .or_insert(toml_edit::Table::new().into())
-                    .as_table_mut()
-                    .map(|t| t.insert("version", toml_edit::value("^1.0")));
+                Self::dep(deps, locked, "lambda_runtime", "^1.0");
             }
             Role::Endpoint => {
-                doc["dependencies"]["lambda_http"]
-                    .or_insert(toml_edit::Table::new().into())
-                    .as_table_mut()
-                    .map(|t| t.insert("version", toml_edit::value("^1.0")));
-                doc["dependencies"]["http"]
-                    .or_insert(toml_edit::Table::new().into())
-                    .as_table_mut()
-                    .map(|t| t.insert("version", toml_edit::value("^1.0")));
-                doc["dependencies"]["tower"]
-                    .or_insert(toml_edit::Table::new().into())
-                    .as_table_mut()
-                    .map(|t| t.insert("version", toml_edit::value("^0")));
+                Self::dep(deps, locked, "lambda_http", "^1.0");
+                Self::dep(deps, locked, "http", "^1.0");
+                Self::dep(deps, locked, "tower", "^0");
             }
         };
 
         let kinetics_version = env!("CARGO_PKG_VERSION");
-        if doc["dependencies"]["kinetics"].as_str().is_some() {
+        if deps["kinetics"].as_str().is_some() {
             // Discard string version and write an object
-            doc["dependencies"]["kinetics"] =
-                toml_edit::Table::from_iter([("version", kinetics_version)]).into();
+            deps["kinetics"] = toml_edit::Table::from_iter([("version", kinetics_version)]).into();
         } else {
             // For an object overwrite only the version field
-            doc["dependencies"]["kinetics"]
+            deps["kinetics"]
                 .or_insert(toml_edit::Table::new().into())
                 .as_table_mut()
                 .map(|t| t.insert("version", kinetics_version.into()));
         }
 
-        doc["dependencies"]["aws_lambda_events"]
-            .or_insert(toml_edit::Table::new().into())
-            .as_table_mut()
-            .map(|t| t.insert("version", toml_edit::value("0.18.0")));
+        Self::dep(deps, locked, "aws_lambda_events", "0.18.0");
+        Self::dep(deps, locked, "aws-config", "1.8.12");
+        Self::dep(deps, locked, "aws-sdk-ssm", "1.59.0");
+        Self::dep(deps, locked, "aws-sdk-sqs", "1.91.0");
 
-        doc["dependencies"]["aws-config"]
-            .or_insert(toml_edit::Table::new().into())
-            .as_table_mut()
-            .map(|t| t.insert("version", toml_edit::value("1.8.12")));
+        // The generated main functions need the multi-threaded runtime and macros,
+        // so the feature is added to the user's tokio as well.
+        if let Some(tokio) = Self::dep(deps, locked, "tokio", "1.49.0") {
+            Self::add_features(tokio, &["full"]);
+        }
 
-        doc["dependencies"]["aws-sdk-ssm"]
-            .or_insert(toml_edit::Table::new().into())
-            .as_table_mut()
-            .map(|t| t.insert("version", toml_edit::value("1.59.0")));
+        if has_schema {
+            Self::dep(deps, locked, "jsonschema", "0.30.0");
+        }
 
-        doc["dependencies"]["aws-sdk-sqs"]
-            .or_insert(toml_edit::Table::new().into())
-            .as_table_mut()
-            .map(|t| t.insert("version", toml_edit::value("1.91.0")));
+        deps.remove("kinetics-macro");
 
-        if let Some(tokio_dep) = doc["dependencies"]["tokio"]
-            .or_insert(toml_edit::Table::new().into())
-            .as_table_mut()
-        {
-            tokio_dep.insert("version", toml_edit::value("1.49.0"));
-            tokio_dep.insert("features", toml_edit::Array::from_iter(["full"]).into());
+        Ok(())
+    }
+
+    /// Add a dependency required by the generated code, or reuse the one the crate declares
+    ///
+    /// A declared dependency keeps its version, so that the one pinned in Cargo.lock is built,
+    /// and is only converted to a table for the required features to be merged into it.
+    /// Warns if no version pinned in Cargo.lock satisfies the required one: for an added
+    /// dependency cargo then resolves a different version than the one tested locally,
+    /// and a declared one may not fit the generated code.
+    fn dep<'a>(
+        deps: &'a mut toml_edit::Table,
+        locked: &mut HashMap<String, Vec<semver::Version>>,
+        name: &str,
+        version: &str,
+    ) -> Option<&'a mut dyn toml_edit::TableLike> {
+        let is_declared = deps.contains_key(name);
+
+        // Checked on the first call only, while the manifest has only the crate's own dependencies,
+        // the function is called for every generated binary
+        let conflict = locked
+            .remove(name)
+            .and_then(|versions| Self::lock_conflict(&versions, name, version));
+
+        if let Some(conflict) = conflict {
+            if is_declared {
+                log::warn!("{conflict}, update {name} in Cargo.toml if the build fails");
+            } else {
+                log::warn!("{conflict}, add {name} to Cargo.toml to pin the version");
+            }
         }
 
-        if let Some(deps_table) = doc["dependencies"].as_table_mut() {
-            deps_table.remove("kinetics-macro");
+        if !is_declared {
+            deps.insert(
+                name,
+                toml_edit::Table::from_iter([("version", version)]).into(),
+            );
+        } else if let Some(declared) = deps[name].as_str().map(String::from) {
+            deps[name] = toml_edit::Table::from_iter([("version", declared)]).into();
         }
 
-        Ok(())
+        deps[name].as_table_like_mut()
+    }
+
+    /// Describe the conflict of the required version with the ones pinned in Cargo.lock, if any
+    fn lock_conflict(versions: &[semver::Version], name: &str, version: &str) -> Option<String> {
+        let requirement = semver::VersionReq::parse(version).ok()?;
+
+        if versions.iter().any(|locked| requirement.matches(locked)) {
+            return None;
+        }
+
+        Some(format!(
+            "Dependency {name} {version} required by kinetics conflicts with {} in Cargo.lock",
+            versions
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    }
+
+    /// Enable the features of a dependency, keeping the ones already enabled
+    fn add_features(dep: &mut dyn toml_edit::TableLike, features: &[&str]) {
+        let Some(enabled) = dep
+            .entry("features")
+            .or_insert(toml_edit::Array::new().into())
+            .as_array_mut()
+        else {
+            return;
+        };
+
+        for feature in features {
+            if !enabled.iter().any(|f| f.as_str() == Some(feature)) {
+                enabled.push(*feature);
+            }
+        }
+    }
+
+    /// Versions of packages pinned in Cargo.lock of the crate
+    ///
+    /// Returns an empty map if there is no lock file.
+    fn locked_versions(src: &Path) -> HashMap<String, Vec<semver::Version>> {
+        let mut locked: HashMap<String, Vec<semver::Version>> = HashMap::new();
+
+        let Some(packages) = fs::read_to_string(src.join("Cargo.lock"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Value>().ok())
+            .and_then(|lock| lock.get("package").and_then(|p| p.as_array()).cloned())
+        else {
+            return locked;
+        };
+
+        for package in packages {
+            let name = package.get("name").and_then(|n| n.as_str());
+
+            let version = package
+                .get("version")
+                .and_then(|v| v.as_str())
+                .and_then(|v| semver::Version::parse(v).ok());
+
+            if let (Some(name), Some(version)) = (name, version) {
+                locked.entry(name.to_string()).or_default().push(version);
+            }
+        }
+
+        locked
+    }
+
+    /// Modules from the crate root to the file, e.g. `["api", "users"]` for `src/api/users/mod.rs`
+    ///
+    /// Empty for `src/lib.rs`, the root module.
+    fn module_path(relative_path: &str) -> Vec<String> {
+        let relative_path = Path::new(relative_path.strip_prefix("src/").unwrap_or(relative_path));
+
+        if relative_path == Path::new("lib.rs") {
+            return vec![];
+        }
+
+        let mut module_path = relative_path
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(os_str) => os_str.to_str().map(String::from),
+                _ => None,
+            })
+            .collect::<Vec<String>>();
+
+        // Remove extension from last component, or 'mod.rs' altogether
+        if let Some(last) = module_path.pop() {
+            if last != "mod.rs" {
+                module_path.push(last.trim_end_matches(".rs").to_string());
+            }
+        }
+
+        module_path
     }
 
     /// Generate the import statement for the function
     /// which is being deployed as a lambda
     fn import_statement(
         &self,
-        relative_path: &str,
-        rust_name: &str,
+        parsed_function: &ParsedFunction,
         project_name: &str,
     ) -> eyre::Result<String> {
-        let relative_path =
-            PathBuf::from_str(relative_path.strip_prefix("src/").unwrap_or(relative_path))?;
+        let rust_name = &parsed_function.rust_function_name;
 
-        let mut module_path_parts = relative_path
-            .components()
-            .filter_map(|component| {
-                if let std::path::Component::Normal(os_str) = component {
-                    os_str.to_str()
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<&str>>();
-
-        // Handle lib.rs (root module)
-        let is_root_module = relative_path == Path::new("lib.rs");
-
-        let module_path = if is_root_module {
-            "".to_string()
-        } else {
-            // Remove extension from last component
-            if let Some(last) = module_path_parts.last_mut() {
-                if *last == "mod.rs" {
-                    // Remove 'mod.rs'
-                    module_path_parts.pop();
-                } else {
-                    *last = last.trim_end_matches(".rs");
-                }
-            }
-            module_path_parts.join("::")
-        };
+        let module_path = Self::module_path(&parsed_function.relative_path)
+            .into_iter()
+            .chain(parsed_function.inline_modules.iter().cloned())
+            .collect::<Vec<_>>()
+            .join("::");
 
         // If module path is empty then the function is located in the lib.rs file
         let import_statement = if module_path.is_empty() {
@@ -444,13 +930,33 @@ impl Project {
         checksum: &mut FileHash,
     ) -> eyre::Result<()> {
         let dst_path_full = dst_dir.join(file_path_relative);
+
+        // Writing through a link left in the build dir would modify the file it points to
+        if dst_path_full.is_symlink() {
+            fs::remove_file(&dst_path_full)
+                .wrap_err_with(|| format!("Failed to delete a symlink {dst_path_full:?}"))?;
+        }
+
         // For all non .rs files just copy it.
+        // The files are tracked in checksums as well, otherwise their folders
+        // (e.g. JSON schemas of endpoints) would be deleted as obsolete.
         if src_path_full.extension().is_some_and(|ext| ext != "rs") {
-            return fs::copy(src_path_full, &dst_path_full)
-                .wrap_err_with(|| {
+            let content = fs::read(src_path_full)
+                .wrap_err_with(|| format!("Failed to read file {src_path_full:?}"))?;
+
+            if checksum.update(
+                file_path_relative.to_path_buf(),
+                &FileHash::hash_from_bytes(&content).wrap_err_with(|| {
+                    format!("Failed to calculate hash from bytes of {src_path_full:?}")
+                })?,
+            ) || !dst_path_full.exists()
+            {
+                fs::write(&dst_path_full, &content).wrap_err_with(|| {
                     format!("Failed to copy file {src_path_full:?} -> {dst_path_full:?}")
-                })
-                .map(|_| ());
+                })?;
+            }
+
+            return Ok(());
         }
 
         // Attempt kinetics macro replacements in .rs files.
@@ -469,3 +975,430 @@ impl Project {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixture::{assert_success, Fixture};
+
+    fn worker() -> ParsedFunction {
+        ParsedFunction {
+            rust_function_name: "work".to_string(),
+            relative_path: "src/lib.rs".to_string(),
+            inline_modules: vec![],
+            role: Role::Worker,
+            params: Params::Worker(syn::parse_str("").unwrap()),
+        }
+    }
+
+    /// Rewrite the dependencies of the manifest as the build does, with the lock file next to it
+    fn rewrite(manifest: &str, lock: &str) -> toml_edit::DocumentMut {
+        let src = std::env::temp_dir().join(format!("kinetics-deps-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("Cargo.lock"), lock).unwrap();
+        let mut locked = Project::locked_versions(&src);
+        fs::remove_dir_all(&src).unwrap();
+
+        let mut manifest: toml_edit::DocumentMut = manifest.parse().unwrap();
+        let deps = manifest["dependencies"].as_table_mut().unwrap();
+
+        for is_local in [false, true] {
+            Project::deps(&worker(), is_local, deps, &mut locked).unwrap();
+        }
+
+        manifest
+    }
+
+    fn features(manifest: &toml_edit::DocumentMut, name: &str) -> Vec<String> {
+        manifest["dependencies"][name]["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f.as_str().unwrap().to_string())
+            .collect()
+    }
+
+    const LOCK: &str = r#"
+        [[package]]
+        name = "reqwest"
+        version = "0.12.5"
+
+        [[package]]
+        name = "tokio"
+        version = "1.40.0"
+
+        # Pulled in by reqwest, older than the version required by kinetics
+        [[package]]
+        name = "serde_json"
+        version = "1.0.100"
+    "#;
+
+    #[test]
+    fn keeps_declared_versions_and_merges_features() {
+        let manifest = rewrite(
+            r#"
+                [dependencies]
+                kinetics = "0.1"
+                kinetics-macro = "0.1"
+                reqwest = "0.12"
+                tokio = { version = "1.40", features = ["macros"] }
+            "#,
+            LOCK,
+        );
+
+        let deps = &manifest["dependencies"];
+        assert_eq!(deps["reqwest"]["version"].as_str(), Some("0.12"));
+        assert!(deps["reqwest"].get("default-features").is_none());
+        assert_eq!(features(&manifest, "reqwest"), ["default-tls"]);
+        assert_eq!(deps["tokio"]["version"].as_str(), Some("1.40"));
+        assert_eq!(features(&manifest, "tokio"), ["macros", "full"]);
+        assert!(deps.get("kinetics-macro").is_none());
+    }
+
+    #[test]
+    fn adds_missing_dependencies() {
+        let manifest = rewrite(
+            r#"
+                [dependencies]
+                kinetics = "0.1"
+            "#,
+            LOCK,
+        );
+
+        let deps = &manifest["dependencies"];
+        assert_eq!(deps["reqwest"]["default-features"].as_bool(), Some(false));
+        assert_eq!(features(&manifest, "reqwest"), ["default-tls"]);
+        assert_eq!(features(&manifest, "tokio"), ["full"]);
+        assert_eq!(deps["lambda_runtime"]["version"].as_str(), Some("^1.0"));
+
+        // The transitive dependency pinned in the lock is overridden, with a warning
+        assert_eq!(deps["serde_json"]["version"].as_str(), Some("1.0.149"));
+    }
+
+    #[test]
+    fn keeps_other_manifest_sections() {
+        let sections = r#"
+[features]
+default = ["api"]
+api = []
+admin = ["api", "dep:uuid"]
+
+[profile.release]
+opt-level = "z" # Smaller bundles
+lto = true
+
+[patch.crates-io]
+tokio = { git = "https://github.com/tokio-rs/tokio", branch = "master" }
+"#;
+
+        let manifest = format!(
+            r#"[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+kinetics = "0.1"
+{sections}"#
+        );
+
+        let staged = Project::staged_manifest(&manifest, &[worker()], HashMap::new()).unwrap();
+        let original: toml_edit::DocumentMut = manifest.parse().unwrap();
+        let staged: toml_edit::DocumentMut = staged.parse().unwrap();
+
+        for section in ["package", "features", "profile", "patch"] {
+            assert_eq!(
+                staged[section].to_string(),
+                original[section].to_string(),
+                "[{section}] is changed"
+            );
+        }
+
+        // Formatting and comments are kept as well
+        assert!(staged.to_string().ends_with(sections));
+        assert!(staged["dependencies"].get("lambda_runtime").is_some());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn never_follows_symlinks() {
+        let root = std::env::temp_dir().join(format!("kinetics-links-{}", uuid::Uuid::new_v4()));
+        let (src, dst, outside) = (root.join("src"), root.join("dst"), root.join("outside"));
+        fs::create_dir_all(src.join("src")).unwrap();
+        fs::create_dir_all(outside.join("dir")).unwrap();
+        fs::write(src.join("src/lib.rs"), "pub mod api;").unwrap();
+        fs::write(outside.join("secret.rs"), "").unwrap();
+        fs::write(outside.join("dir/keep.rs"), "").unwrap();
+
+        let link = |target: &str, link: &Path| {
+            std::os::unix::fs::symlink(outside.join(target), link).unwrap();
+        };
+
+        link("secret.rs", &src.join("src/api.rs"));
+        link("dir", &src.join("src/dir"));
+
+        let project = project();
+        let mut checksum = FileHash::new(dst.clone(), false);
+        project.clone(&src, &dst, &mut checksum).unwrap();
+
+        // Links are skipped, not copied as files or dirs they point to
+        assert!(dst.join("src/lib.rs").is_file());
+        assert!(fs::symlink_metadata(dst.join("src/api.rs")).is_err());
+        assert!(fs::symlink_metadata(dst.join("src/dir")).is_err());
+
+        // Links left in the build dir are deleted, without touching their targets
+        link("secret.rs", &dst.join("src/stale.rs"));
+        link("dir", &dst.join("src/stale"));
+        project.clear_dir(&dst, &checksum).unwrap();
+
+        assert!(dst.join("src/lib.rs").is_file());
+        assert!(fs::symlink_metadata(dst.join("src/stale.rs")).is_err());
+        assert!(fs::symlink_metadata(dst.join("src/stale")).is_err());
+        assert!(outside.join("secret.rs").is_file());
+        assert!(outside.join("dir/keep.rs").is_file());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn detects_conflicts_with_lock() {
+        let pinned = |version: &str| [semver::Version::parse(version).unwrap()];
+        assert!(Project::lock_conflict(&pinned("1.0.100"), "serde_json", "1.0.149").is_some());
+        assert!(Project::lock_conflict(&pinned("1.0.150"), "serde_json", "1.0.149").is_none());
+    }
+
+    fn parsed_worker(relative_path: &str, inline_modules: &[&str], params: &str) -> ParsedFunction {
+        ParsedFunction {
+            rust_function_name: "list".to_string(),
+            relative_path: relative_path.to_string(),
+            inline_modules: inline_modules.iter().map(|m| m.to_string()).collect(),
+            role: Role::Worker,
+            params: Params::Worker(syn::parse_str(params).unwrap()),
+        }
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        // Both resolve to ApiUsersList
+        let functions = [
+            parsed_worker("src/api/users.rs", &[], ""),
+            parsed_worker("src/api.rs", &["users"], ""),
+            parsed_worker("src/orders.rs", &[], ""),
+        ];
+
+        let error = format!("{:?}", Project::validate_names(&functions).unwrap_err());
+        assert!(error.contains(
+            "ApiUsersList is the name of both src/api/users.rs::list and src/api.rs::list"
+        ));
+        assert!(!error.contains("OrdersList"));
+    }
+
+    #[test]
+    fn rejects_names_colliding_with_name_attribute() {
+        let functions = [
+            parsed_worker("src/orders.rs", &[], ""),
+            parsed_worker("src/users.rs", &[], r#"name = "OrdersList""#),
+        ];
+
+        let error = format!("{:?}", Project::validate_names(&functions).unwrap_err());
+        assert!(error
+            .contains("OrdersList is the name of both src/orders.rs::list and src/users.rs::list"));
+
+        let functions = [
+            parsed_worker("src/orders.rs", &[], ""),
+            parsed_worker("src/users.rs", &[], r#"name = "UsersWorker""#),
+        ];
+
+        assert!(Project::validate_names(&functions).is_ok());
+    }
+
+    fn project() -> Project {
+        serde_json::from_value(serde_json::json!({
+            "name": "test",
+            "url": "https://test.kinetics.app",
+            "kvdb": [],
+        }))
+        .unwrap()
+    }
+
+    fn function(name: &str, role: Role, params: Params) -> Function {
+        let project = project();
+
+        Function {
+            name: name.to_string(),
+            is_deploying: true,
+            role,
+            params,
+            project,
+        }
+    }
+
+    fn endpoint(name: &str, queues: &str) -> Function {
+        let params = syn::parse_str(&format!(r#"url_path = "/{name}", queues = {queues}"#));
+        function(name, Role::Endpoint, Params::Endpoint(params.unwrap()))
+    }
+
+    fn queue_worker(name: &str, params: &str) -> Function {
+        let params = Params::Worker(syn::parse_str(params).unwrap());
+        function(name, Role::Worker, params)
+    }
+
+    #[test]
+    fn accepts_queues_of_workers() {
+        let functions = [
+            queue_worker("OrderWorker", ""),
+            queue_worker("EmailWorker", "fifo = true"),
+            endpoint("Checkout", r#"["OrderWorker", "EmailWorker"]"#),
+            endpoint("Health", "[]"),
+        ];
+
+        assert!(Project::validate_queues(&functions).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_queues() {
+        let functions = [
+            queue_worker("OrderWorker", ""),
+            endpoint("Checkout", r#"["OrderWorkr", "Checkout"]"#),
+        ];
+
+        let error = format!("{:?}", Project::validate_queues(&functions).unwrap_err());
+
+        // Typos get a suggestion, other endpoints have no queue
+        assert!(error.contains("unknown queue OrderWorkr, did you mean OrderWorker?"));
+        assert!(error.contains("unknown queue Checkout"));
+    }
+
+    #[test]
+    fn imports_functions_of_inline_modules() {
+        let project = project();
+        let import = |path: &str, inline_modules: &[&str]| {
+            let function = parsed_worker(path, inline_modules, "");
+            project.import_statement(&function, "app").unwrap()
+        };
+
+        assert_eq!(import("src/lib.rs", &[]), "use app::list;");
+        assert_eq!(import("src/api.rs", &[]), "use app::api::list;");
+        assert_eq!(import("src/api.rs", &["v1"]), "use app::api::v1::list;");
+        assert_eq!(
+            import("src/api/mod.rs", &["v1", "admin"]),
+            "use app::api::v1::admin::list;"
+        );
+    }
+
+    fn export(content: &str, path: &[&str]) -> Option<String> {
+        let path = path.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        Project::export_module(content, &path).unwrap()
+    }
+
+    #[test]
+    fn exports_private_modules() {
+        let content = r#"
+            // mod users; in a comment
+            const DOC: &str = "mod users;";
+            #[cfg(feature = "admin")] mod users;
+            #[cfg(not(feature = "admin"))]
+            pub(crate) mod users;
+            mod other;
+        "#;
+
+        assert_eq!(
+            export(content, &["users"]).unwrap(),
+            r#"
+            // mod users; in a comment
+            const DOC: &str = "mod users;";
+            #[cfg(feature = "admin")] pub mod users;
+            #[cfg(not(feature = "admin"))]
+            pub mod users;
+            mod other;
+        "#
+        );
+    }
+
+    #[test]
+    fn exports_nested_inline_modules() {
+        let content = "mod api {\n    pub(super) mod users {\n        fn list() {}\n    }\n}\n";
+
+        assert_eq!(
+            export(content, &["api", "users"]).unwrap(),
+            "pub mod api {\n    pub mod users {\n        fn list() {}\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_public_and_missing_modules() {
+        assert!(export("pub mod users;\nmod other;\n", &["users"]).is_none());
+        assert!(export("mod other;\n", &["users"]).is_none());
+
+        // Raw identifiers match the file name of the module
+        assert_eq!(
+            export("mod r#type;\n", &["type"]).unwrap(),
+            "pub mod r#type;\n"
+        );
+    }
+
+    /// Functions in a two-level module, declared private in each parent
+    fn nested_modules_fixture() -> Fixture {
+        let endpoint = |name: &str| {
+            format!(
+                r#"use kinetics::macros::endpoint;
+
+#[endpoint(url_path = "/{name}")]
+pub async fn {name}(
+    _event: http::Request<kinetics::tools::http::Body>,
+    _secrets: &std::collections::HashMap<String, String>,
+    _config: &kinetics::tools::config::Config,
+) -> Result<http::Response<String>, tower::BoxError> {{
+    Ok(http::Response::new("{name}".into()))
+}}
+"#
+            )
+        };
+
+        Fixture::new(&[
+            ("src/lib.rs", "mod api;\n"),
+            ("src/api.rs", "mod users;\n"),
+            (
+                "src/api/users/mod.rs",
+                &format!("pub(crate) mod admin;\n{}", endpoint("list")),
+            ),
+            ("src/api/users/admin.rs", &endpoint("ban")),
+        ])
+    }
+
+    #[test]
+    fn exports_nested_module_files() {
+        let fixture = nested_modules_fixture();
+        let mut functions = fixture.parse();
+        functions.sort();
+        assert_eq!(functions, ["ApiUsersAdminBan", "ApiUsersModList"]);
+
+        // Bins import the functions by their full paths, every module on the way is public
+        assert!(fixture.read("src/lib.rs").contains("pub mod api;"));
+        assert_eq!(fixture.read("src/api.rs"), "pub mod users;\n");
+        assert!(fixture
+            .read("src/api/users/mod.rs")
+            .starts_with("pub mod admin;"));
+
+        assert!(fixture
+            .read("src/bin/ApiUsersAdminBan.rs")
+            .contains("use fixture::api::users::admin::ban;"));
+    }
+
+    #[test]
+    #[ignore = "builds the generated crate, fetching its dependencies"]
+    fn builds_functions_of_nested_module_files() {
+        let fixture = nested_modules_fixture();
+        fixture.parse();
+
+        assert_success(&fixture.cargo(
+            &[
+                "build",
+                "--bin",
+                "ApiUsersModList",
+                "--bin",
+                "ApiUsersAdminBan",
+            ],
+            &[],
+        ));
+    }
+}
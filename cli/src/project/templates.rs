@@ -4,3 +4,37 @@ pub mod worker;
 pub use worker::worker;
 pub mod cron;
 pub use cron::cron;
+
+/// Code skipping a secret the function doesn't declare, the `name` variable holds its name
+///
+/// A function declaring no secrets gets all of them.
+fn skip_undeclared_secret(secrets: &[String], name: &str) -> String {
+    if secrets.is_empty() {
+        return String::new();
+    }
+
+    let declared = secrets
+        .iter()
+        .map(|secret| format!("{secret:?}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "if ![{declared}].contains(&{name}.as_str()) {{
+            continue;
+        }}"
+    )
+}
+
+/// Code handling a failed lookup of the tags of a parameter, the `error` variable holds the error
+///
+/// For a function declaring secrets the backend lists only their parameters in
+/// `KINETICS_SECRETS_NAMES`, and the SSM permissions are scoped to them. Should the list still
+/// contain another parameter, it's not accessible, so it's skipped instead of failing cold start.
+fn skip_inaccessible_secret(secrets: &[String]) -> String {
+    if secrets.is_empty() {
+        return "return Err(error.into());".to_string();
+    }
+
+    "continue;".to_string()
+}
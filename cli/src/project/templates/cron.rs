@@ -1,9 +1,20 @@
-pub fn cron(import_statement: &str, rust_function_name: &str, is_local: bool) -> String {
+use kinetics_parser::Cron;
+
+pub fn cron(
+    import_statement: &str,
+    rust_function_name: &str,
+    params: &Cron,
+    is_local: bool,
+) -> String {
+    let skip_local_secret = super::skip_undeclared_secret(&params.secrets, "key");
+    let skip_deployed_secret = super::skip_undeclared_secret(&params.secrets, "name");
+    let skip_inaccessible_secret = super::skip_inaccessible_secret(&params.secrets);
     // For local run we create a dedicated crate, without cargo lambda wrappers
     if is_local {
         format!(
             "{import_statement}
             use kinetics::tools::config::Config as KineticsConfig;
+            use aws_lambda_events::eventbridge::EventBridgeEvent;
             #[tokio::main]\n\
             async fn main() -> Result<(), tower::BoxError> {{\n\
                 let user_function = {rust_function_name};
@@ -14,11 +25,37 @@ pub fn cron(import_statement: &str, rust_function_name: &str, is_local: bool) ->
                 for (k, v) in std::env::vars() {{
                     if k.starts_with(\"KINETICS_SECRET_\") {{
                         let key = k.replace(\"KINETICS_SECRET_\", \"\");
+                        {skip_local_secret}
                         secrets.insert(key, v);
                     }}
                 }}
 
-                user_function(&secrets, &kinetics_config).await?;
+                // Synthetic event of the schedule, as sent by EventBridge, with the payload as the detail
+                let detail = match std::env::var(\"KINETICS_INVOKE_PAYLOAD\") {{
+                    Ok(val) => serde_json::from_str::<serde_json::Value>(&val)
+                        .map_err(|e| format!(\"Payload must be a valid JSON: {{e}}\"))?,
+                    Err(_) => serde_json::json!({{}}),
+                }};
+
+                let event: EventBridgeEvent<serde_json::Value> = serde_json::from_value(serde_json::json!({{
+                    \"version\": \"0\",
+                    \"id\": \"test\",
+                    \"detail-type\": \"Scheduled Event\",
+                    \"source\": \"aws.events\",
+                    \"account\": std::env::var(\"KINETICS_CLOUD_ACCOUNT_ID\").ok(),
+                    \"region\": std::env::var(\"AWS_REGION\").ok(),
+                    \"resources\": [],
+                    \"detail\": detail,
+                }}))?;
+
+                let context = lambda_runtime::Context::default();
+                let (_, context) = lambda_runtime::LambdaEvent::new(event, context).into_parts();
+                let kinetics_config = kinetics_config.with_context(&context);
+
+                // Printed to stdout, which is shown in full once the invocation finishes
+                let result = user_function(&secrets, &kinetics_config).await;
+                println!(\"Result: {{result:?}}\");
+
                 Ok(())
             }}\n\n"
         )
@@ -28,12 +65,10 @@ pub fn cron(import_statement: &str, rust_function_name: &str, is_local: bool) ->
             use kinetics::tools::config::Config as KineticsConfig;
             use lambda_runtime::{{LambdaEvent, Error, run, service_fn}};\n\
             use aws_lambda_events::eventbridge::EventBridgeEvent;\n\
-            #[tokio::main]\n\
-            async fn main() -> Result<(), Error> {{\n\
-                let user_function = {rust_function_name};
-                let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
-                println!(\"Provisioning secrets\");
-                let secrets_client = aws_sdk_ssm::Client::new(&config);
+            /// Fetch the secrets of the function from SSM
+            async fn fetch_secrets(
+                secrets_client: &aws_sdk_ssm::Client,
+            ) -> Result<std::collections::HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {{
                 let secrets_names_env = \"KINETICS_SECRETS_NAMES\";
                 let mut secrets = std::collections::HashMap::new();
 
@@ -42,6 +77,28 @@ pub fn cron(import_statement: &str, rust_function_name: &str, is_local: bool) ->
                     .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty())
                 {{
+                    let tags = match secrets_client
+                        .list_tags_for_resource()
+                        .resource_type(aws_sdk_ssm::types::ResourceTypeForTagging::Parameter)
+                        .resource_id(secret_name.clone())
+                        .send()
+                        .await
+                    {{
+                        Ok(output) => output.tag_list.unwrap_or_default(),
+                        Err(error) => {{
+                            eprintln!(\"Error fetching tags for secret {{}}: {{:?}}\", secret_name, error);
+                            {skip_inaccessible_secret}
+                        }}
+                    }};
+
+                    let name = match tags.iter().find(|t| t.key() == \"original_name\") {{
+                        Some(tag) => tag.value().to_string(),
+                        None => secret_name.clone(),
+                    }};
+
+                    // Only the declared secrets are fetched and decrypted
+                    {skip_deployed_secret}
+
                     let desc = secrets_client
                         .get_parameter()
                         .name(secret_name.clone())
@@ -50,29 +107,32 @@ pub fn cron(import_statement: &str, rust_function_name: &str, is_local: bool) ->
                         .await?;
 
                     let result = desc.parameter.unwrap();
+                    let secret_value = result.value().unwrap();
+                    secrets.insert(name, secret_value.to_string());
+                }}
 
-                    let tags = secrets_client
-                        .list_tags_for_resource()
-                        .resource_type(aws_sdk_ssm::types::ResourceTypeForTagging::Parameter)
-                        .resource_id(secret_name.clone())
-                        .send()
-                        .await?
-                        .tag_list
-                        .unwrap_or_default();
+                Ok(secrets)
+            }}
 
-                    let name = match tags.iter().find(|t| t.key() == \"original_name\") {{
-                        Some(tag) => tag.value(),
-                        None => &secret_name.clone(),
-                    }};
+            #[tokio::main]\n\
+            async fn main() -> Result<(), Error> {{\n\
+                let user_function = {rust_function_name};
+                let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+                println!(\"Provisioning secrets\");
+                let secrets_client = aws_sdk_ssm::Client::new(&config);
 
-                    let secret_value = result.value().unwrap();
-                    secrets.insert(name.into(), secret_value.to_string());
-                }}
+                // Fetched on cold start, then reused by invocations until expired, to pick up rotated ones
+                let secrets_cache = kinetics::tools::secrets::Cache::default();
+                secrets_cache.get(|| fetch_secrets(&secrets_client)).await?;
 
                 let kinetics_config = KineticsConfig::new(&config, None).await?;
                 println!(\"Serving requests\");
 
-                run(service_fn(|_event: LambdaEvent<EventBridgeEvent<serde_json::Value>>| async {{
+                run(service_fn(|event: LambdaEvent<EventBridgeEvent<serde_json::Value>>| async {{
+                    let secrets = secrets_cache.get(|| fetch_secrets(&secrets_client)).await?;
+                    let (_, context) = event.into_parts();
+                    let kinetics_config = kinetics_config.with_context(&context);
+
                     match user_function(&secrets, &kinetics_config).await {{
                         Ok(()) => Ok(()),
                         Err(err) => {{
@@ -1,18 +1,248 @@
 use crate::tools::config::EndpointConfig;
+use kinetics_parser::Endpoint;
 
 pub fn endpoint(
     import_statement: &str,
     rust_function_name: &str,
     config: EndpointConfig,
+    params: &Endpoint,
     is_local: bool,
 ) -> String {
+    let schema = params.schema.as_deref();
+    let skip_local_secret = super::skip_undeclared_secret(&params.secrets, "key");
+    let skip_deployed_secret = super::skip_undeclared_secret(&params.secrets, "name");
+    let skip_inaccessible_secret = super::skip_inaccessible_secret(&params.secrets);
+
+    // Default response headers, sorted to keep the generated code stable
+    let mut response_headers = params
+        .response_headers
+        .iter()
+        .map(|(name, value)| format!("({name:?}, {value:?})"))
+        .collect::<Vec<_>>();
+
+    response_headers.sort();
+
+    // Headers set by the function take precedence over the default ones
+    let inject_headers = if response_headers.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "let mut response = response;
+
+            for (name, value) in [{}] {{
+                response
+                    .headers_mut()
+                    .entry(name)
+                    .or_insert(http::HeaderValue::from_static(value));
+            }}",
+            response_headers.join(", ")
+        )
+    };
+
+    // All methods are allowed unless the endpoint lists them
+    let allowed_methods = params.methods.join(", ");
+
+    // Reject requests with a method not listed by the endpoint, before anything else
+    let method_check = |on_error: &str| {
+        if params.methods.is_empty() {
+            return String::new();
+        }
+
+        format!(
+            "if ![{}].contains(&event.method().as_str()) {{
+                {on_error}
+            }}",
+            params
+                .methods
+                .iter()
+                .map(|method| format!("{method:?}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+
+    // The JWKS of the issuer is fetched once at cold start
+    let jwt_verifier = match &params.jwt {
+        Some(jwt) => format!(
+            "let jwt_verifier = kinetics::tools::jwt::Verifier::new({:?}, {:?}, {}).await?;",
+            jwt.issuer,
+            jwt.audience.as_deref(),
+            jwt.leeway
+        ),
+        None => String::new(),
+    };
+
+    // Reject requests without a valid token, the claims are passed to the function via config
+    let jwt_check = |on_error: &str| match &params.jwt {
+        Some(_) => format!(
+            "let kinetics_config = match jwt_verifier.verify(event.headers()).await {{
+                Ok(claims) => kinetics_config.with_claims(claims),
+                Err(e) => {{
+                    {on_error}
+                }}
+            }};"
+        ),
+        None => String::new(),
+    };
+
+    // Take the request ID from the header or generate it, and expose it to the function via config
+    let request_id = match &params.request_id {
+        Some(header) => format!(
+            "let request_id = kinetics::tools::http::request_id(event.headers(), {header:?});
+            let kinetics_config = kinetics_config.with_request_id(&request_id);"
+        ),
+        None => String::new(),
+    };
+
+    // Responses of the wrapper itself, e.g. 401, carry the request ID the same as the function's ones
+    let request_id_header = match &params.request_id {
+        Some(header) => format!(".header({header:?}, request_id.as_str())"),
+        None => String::new(),
+    };
+
+    // Logs of the function are recorded within a span with the request ID
+    let call_user_function = match &params.request_id {
+        Some(_) => "lambda_http::tracing::Instrument::instrument(
+                user_function(event, &secrets, &kinetics_config),
+                lambda_http::tracing::info_span!(\"request\", request_id = %request_id),
+            )"
+        .to_string(),
+        None => "user_function(event, &secrets, &kinetics_config)".to_string(),
+    };
+
+    // Echo the request ID, so that the caller is able to correlate the response
+    let echo_request_id = match &params.request_id {
+        Some(header) => format!(
+            "let mut response = response;
+
+            if let Ok(value) = http::HeaderValue::from_str(&request_id) {{
+                response.headers_mut().insert({header:?}, value);
+            }}"
+        ),
+        None => String::new(),
+    };
+
+    // The schema is embedded into the binary and compiled once at cold start
+    let schema_validator = match schema {
+        Some(path) => format!(
+            "let schema_validator = jsonschema::validator_for(
+                &serde_json::from_str(include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/{path}\")))?,
+            )
+            .map_err(|e| format!(\"Invalid JSON schema {path}: {{e}}\"))?;"
+        ),
+        None => String::new(),
+    };
+
+    // Reject requests with a body not matching the schema, `on_error` handles the list of errors.
+    // Requests without a body, e.g. GET ones, have nothing to validate.
+    let schema_check = |body: &str, method: &str, on_error: &str| match schema {
+        Some(_) => format!(
+            "let body: &[u8] = {body};
+
+            if !body.is_empty() && !matches!({method}, \"GET\" | \"HEAD\") {{
+                let schema_errors = match serde_json::from_slice::<serde_json::Value>(body) {{
+                    Ok(value) => schema_validator
+                        .iter_errors(&value)
+                        .map(|e| e.to_string())
+                        .collect::<Vec<_>>(),
+                    Err(e) => vec![format!(\"Invalid JSON: {{e}}\")],
+                }};
+
+                if !schema_errors.is_empty() {{
+                    {on_error}
+                }}
+            }}"
+        ),
+        None => String::new(),
+    };
+
     if is_local {
+        // Print the chunks of a streaming response as they arrive
+        let print_response = if params.streaming {
+            "let (parts, mut body) = response.into_parts();
+            println!(\"{parts:?}\");
+            let mut bytes = Vec::new();
+
+            while let Some(chunk) = body.chunk().await {
+                let chunk = chunk?;
+                print!(\"{}\", String::from_utf8_lossy(&chunk));
+                std::io::Write::flush(&mut std::io::stdout())?;
+                bytes.extend_from_slice(&chunk);
+            }
+
+            println!();
+            kinetics::tools::http::LocalResponse::write(&http::Response::from_parts(parts, bytes))?;"
+        } else {
+            "kinetics::tools::http::LocalResponse::write(&response)?;
+            println!(\"{response:?}\");"
+        };
+
+        // Streaming responses are printed as is, the rest is converted by the kinetics trait
+        let convert_response = if params.streaming {
+            ""
+        } else {
+            "let response = match response.into_response() {
+                Ok(response) => response,
+                Err(err) => {
+                    println!(\"Request failed: {err:?}\");
+                    return Ok(());
+                }
+            };"
+        };
+
+        // Responses of the wrapper itself are recorded for `kinetics serve` same as they are sent in the cloud
+        let write_response = |status: u16, headers: &str, errors: &str| {
+            format!(
+                "kinetics::tools::http::LocalResponse::write(
+                    &http::Response::builder()
+                        .status({status})
+                        .header(\"content-type\", \"application/json\")
+                        {headers}
+                        .body(serde_json::json!({{\"errors\": {errors}}}).to_string())?,
+                )?;"
+            )
+        };
+
+        let schema_check = schema_check(
+            "event.body().as_ref()",
+            "event.method().as_str()",
+            &format!(
+                "{}
+            println!(\"Status 400, request body does not match the schema: {{schema_errors:?}}\");
+            return Ok(());",
+                write_response(400, &request_id_header, "schema_errors")
+            ),
+        );
+
+        let jwt_check = jwt_check(&format!(
+            "{}
+            println!(\"Status 401, unauthorized: {{e}}\");
+            return Ok(());",
+            write_response(
+                401,
+                &format!(".header(\"www-authenticate\", \"Bearer\"){request_id_header}"),
+                "[format!(\"Unauthorized: {e}\")]"
+            )
+        ));
+
+        let method_check = method_check(&format!(
+            "{}
+            println!(\"Status 405, method {{}} is not allowed, allowed: {allowed_methods}\", event.method());
+            return Ok(());",
+            write_response(
+                405,
+                &format!(".header(\"allow\", {allowed_methods:?}){request_id_header}"),
+                "[format!(\"Method {} is not allowed\", event.method())]"
+            )
+        ));
+
         format!(
             "{import_statement}
             use http::request::Builder;
             use reqwest::header::{{HeaderName, HeaderValue}};
             use std::str::FromStr;
             use kinetics::tools::config::{{Config as KineticsConfig, EndpointConfig}};
+            use kinetics::tools::http::IntoResponse;
             #[tokio::main]
             async fn main() -> Result<(), tower::BoxError> {{\n\
                 let user_function = {rust_function_name};
@@ -28,18 +258,25 @@ pub fn endpoint(
                     url_path
                 }};
                 let kinetics_config = KineticsConfig::new(&config, Some(endpoint_config)).await?;
+                {schema_validator}
+                {jwt_verifier}
                 let mut secrets = std::collections::HashMap::new();
 
                 for (k, v) in std::env::vars() {{
                     if k.starts_with(\"KINETICS_SECRET_\") {{
                         let key = k.replace(\"KINETICS_SECRET_\", \"\");
+                        {skip_local_secret}
                         secrets.insert(key, v);
                     }}
                 }}
 
-                let payload = match std::env::var(\"KINETICS_INVOKE_PAYLOAD\") {{
-                    Ok(val) => val,
-                    Err(_) => \"{{}}\".into(),
+                // Binary payloads are base64-encoded, as they are passed by function URLs
+                let payload = match std::env::var(\"KINETICS_INVOKE_PAYLOAD_BASE64\") {{
+                    Ok(encoded) => kinetics::tools::http::Body::from_base64(&encoded)?,
+                    Err(_) => match std::env::var(\"KINETICS_INVOKE_PAYLOAD\") {{
+                        Ok(val) => kinetics::tools::http::Body::from(val),
+                        Err(_) => kinetics::tools::http::Body::from(\"{{}}\"),
+                    }},
                 }};
 
                 let headers_json = match std::env::var(\"KINETICS_INVOKE_HEADERS\") {{
@@ -60,16 +297,38 @@ pub fn endpoint(
                         );
                 }}
 
+                let method = std::env::var(\"KINETICS_INVOKE_METHOD\").unwrap_or(\"GET\".into());
+
                 let event = event_builder
+                    .method(method.as_str())
                     .uri(url_path)
-                    .body(kinetics::tools::http::Body::from(payload).try_into()?)?;
-                match user_function(event, &secrets, &kinetics_config).await {{
+                    .body(payload)?;
+                {request_id}
+                {method_check}
+                {jwt_check}
+                {schema_check}
+                let (head, body) = event.into_parts();
+                let event = http::Request::from_parts(head, body.try_into()?);
+                match {call_user_function}.await {{
                     Ok(response) => {{
-                        println!(\"{{response:?}}\");
+                        {convert_response}
+                        {inject_headers}
+                        {echo_request_id}
+                        {print_response}
                     }},
 
-                    Err(err) => {{
-                        println!(\"Request failed: {{:?}}\", err);
+                    // Custom errors might be mapped to a response
+                    Err(err) => match err.into_response() {{
+                        Ok(response) => {{
+                            {inject_headers}
+                            {echo_request_id}
+                            kinetics::tools::http::LocalResponse::write(&response)?;
+                            println!(\"{{response:?}}\");
+                        }},
+
+                        Err(err) => {{
+                            println!(\"Request failed: {{:?}}\", err);
+                        }}
                     }}
                 }}
 
@@ -77,16 +336,90 @@ pub fn endpoint(
             }}\n\n"
         )
     } else {
+        // Body of responses returned by the wrapper itself, e.g. on failed validation
+        let error_body = if params.streaming {
+            "kinetics::tools::http::StreamBody::from(body)"
+        } else {
+            "lambda_http::Body::from(body)"
+        };
+
+        // Streaming responses are sent as is, buffered ones are converted by kinetics IntoResponse
+        // and then to lambda_http body
+        let (run, into_response, error_into_response, return_response) = if params.streaming {
+            (
+                "lambda_http::run_with_streaming_response",
+                "response",
+                "response.map(kinetics::tools::http::StreamBody::from)",
+                "Ok(response)",
+            )
+        } else {
+            (
+                "run",
+                "match kinetics::tools::http::IntoResponse::into_response(response) {
+                    Ok(response) => response.into_response().await,
+                    Err(err) => {
+                        eprintln!(\"Error occurred while converting response: {err:?}\");
+                        return Err(err);
+                    }
+                }",
+                "response.into_response().await",
+                "Ok(kinetics::tools::http::limit_response_size(response))",
+            )
+        };
+
+        // The streaming runner is called by its path and needs no lambda_http conversion
+        let lambda_imports = if params.streaming {
+            "service_fn, Request"
+        } else {
+            "run, service_fn, IntoResponse, Request"
+        };
+
+        let schema_check = schema_check(
+            "event.body().as_ref()",
+            "event.method().as_str()",
+            &format!(
+                "let body = serde_json::json!({{\"errors\": schema_errors}}).to_string();
+
+            return Ok(http::Response::builder()
+                .status(400)
+                .header(\"content-type\", \"application/json\")
+                {request_id_header}
+                .body({error_body})?);"
+            ),
+        );
+
+        let method_check = method_check(&format!(
+            "let body = serde_json::json!({{\"errors\": [format!(\"Method {{}} is not allowed\", event.method())]}}).to_string();
+
+            return Ok(http::Response::builder()
+                .status(405)
+                .header(\"content-type\", \"application/json\")
+                .header(\"allow\", {allowed_methods:?})
+                {request_id_header}
+                .body({error_body})?);"
+        ));
+
+        let jwt_check = jwt_check(
+            &format!(
+                "let body = serde_json::json!({{\"errors\": [format!(\"Unauthorized: {{e}}\")]}}).to_string();
+
+            return Ok(http::Response::builder()
+                .status(401)
+                .header(\"content-type\", \"application/json\")
+                .header(\"www-authenticate\", \"Bearer\")
+                {request_id_header}
+                .body({error_body})?);"
+            ),
+        );
+
         format!(
             "{import_statement}
             use kinetics::tools::config::{{Config as KineticsConfig, EndpointConfig}};
-            use lambda_http::{{run, service_fn, Request}};\n\
-            #[tokio::main]\n\
-            async fn main() -> Result<(), lambda_http::Error> {{\n\
-                let user_function = {rust_function_name};
-                let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
-                println!(\"Fetching secrets\");
-                let secrets_client = aws_sdk_ssm::Client::new(&config);
+            use lambda_http::{{{lambda_imports}}};\n\
+            /// Fetch the secrets of the function from SSM
+            async fn fetch_secrets(
+                secrets_client: &aws_sdk_ssm::Client,
+            ) -> Result<std::collections::HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {{
                 let secrets_names_env = \"KINETICS_SECRETS_NAMES\";
                 let mut secrets = std::collections::HashMap::new();
 
@@ -95,6 +428,28 @@ pub fn endpoint(
                     .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty())
                 {{
+                    let tags = match secrets_client
+                        .list_tags_for_resource()
+                        .resource_type(aws_sdk_ssm::types::ResourceTypeForTagging::Parameter)
+                        .resource_id(secret_name.clone())
+                        .send()
+                        .await
+                    {{
+                        Ok(output) => output.tag_list.unwrap_or_default(),
+                        Err(error) => {{
+                            eprintln!(\"Error fetching tags for secret {{}}: {{:?}}\", secret_name, error);
+                            {skip_inaccessible_secret}
+                        }}
+                    }};
+
+                    let name = match tags.iter().find(|t| t.key() == \"original_name\") {{
+                        Some(tag) => tag.value().to_string(),
+                        None => secret_name.clone(),
+                    }};
+
+                    // Only the declared secrets are fetched and decrypted
+                    {skip_deployed_secret}
+
                     let desc = secrets_client
                         .get_parameter()
                         .name(secret_name.clone())
@@ -105,45 +460,64 @@ pub fn endpoint(
                         }})?;
 
                     let result = desc.parameter.unwrap();
+                    let secret_value = result.value().unwrap();
+                    secrets.insert(name, secret_value.to_string());
+                }}
 
-                    let tags = secrets_client
-                        .list_tags_for_resource()
-                        .resource_type(aws_sdk_ssm::types::ResourceTypeForTagging::Parameter)
-                        .resource_id(secret_name.clone())
-                        .send()
-                        .await
-                        .inspect_err(|e| {{
-                            eprintln!(\"Error fetching tags for secret {{}}: {{:?}}\", secret_name, e);
-                        }})?
-                        .tag_list
-                        .unwrap_or_default();
+                Ok(secrets)
+            }}
 
-                    let name = match tags.iter().find(|t| t.key() == \"original_name\") {{
-                        Some(tag) => tag.value(),
-                        None => &secret_name.clone(),
-                    }};
+            #[tokio::main]\n\
+            async fn main() -> Result<(), lambda_http::Error> {{\n\
+                let user_function = {rust_function_name};
+                let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+                println!(\"Fetching secrets\");
+                let secrets_client = aws_sdk_ssm::Client::new(&config);
 
-                    let secret_value = result.value().unwrap();
-                    secrets.insert(name.into(), secret_value.to_string());
-                }}
+                // Fetched on cold start, then reused by invocations until expired, to pick up rotated ones
+                let secrets_cache = kinetics::tools::secrets::Cache::default();
+                secrets_cache.get(|| fetch_secrets(&secrets_client)).await?;
 
                 let endpoint_config = {config};
                 let kinetics_config = KineticsConfig::new(&config, Some(endpoint_config)).await.inspect_err(|e| {{
                     eprintln!(\"Error initializing kinetics config: {{:?}}\", e);
                 }})?;
 
+                {schema_validator}
+                {jwt_verifier}
                 println!(\"Serving requests\");
 
-                run(service_fn(|event: Request| async {{
+                {run}(service_fn(|event: Request| async {{
+                    let secrets = secrets_cache.get(|| fetch_secrets(&secrets_client)).await?;
+                    
+                    // Expose invocation metadata, like remaining time, to the function
+                    let kinetics_config = match lambda_http::RequestExt::lambda_context_ref(&event) {{
+                        Some(context) => kinetics_config.with_context(context),
+                        None => kinetics_config.clone(),
+                    }};
+
+                    {request_id}
+                    {method_check}
+                    {jwt_check}
+                    {schema_check}
                     let (head, body) = event.into_parts();
                     let event = http::Request::from_parts(head, kinetics::tools::http::Body::from(body).try_into()?);
-                    match user_function(event, &secrets, &kinetics_config).await {{
-                        Ok(response) => Ok(response),
-                        Err(err) => {{
-                            eprintln!(\"Error occurred while handling request: {{:?}}\", err);
-                            Err(err)
+                    let response = match {call_user_function}.await {{
+                        Ok(response) => {into_response},
+
+                        // Custom errors might be mapped to a response, the rest is handled by the runtime
+                        Err(err) => match kinetics::tools::http::IntoResponse::into_response(err) {{
+                            Ok(response) => {error_into_response},
+                            Err(err) => {{
+                                eprintln!(\"Error occurred while handling request: {{:?}}\", err);
+                                return Err(err);
+                            }}
                         }}
-                    }}
+                    }};
+
+                    {inject_headers}
+                    {echo_request_id}
+                    {return_response}
                 }})).await
             }}\n\n"
         )
@@ -1,8 +1,18 @@
-pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool) -> String {
+use kinetics_parser::Worker;
+
+pub fn worker(
+    import_statement: &str,
+    rust_function_name: &str,
+    params: &Worker,
+    is_local: bool,
+) -> String {
+    let skip_local_secret = super::skip_undeclared_secret(&params.secrets, "key");
+    let skip_deployed_secret = super::skip_undeclared_secret(&params.secrets, "name");
+    let skip_inaccessible_secret = super::skip_inaccessible_secret(&params.secrets);
+
     if is_local {
         format!(
             "{import_statement}
-            use aws_lambda_events::sqs::{{SqsEvent, SqsMessage}};
             use kinetics::tools::{{queue::Record as QueueRecord, config::Config as KineticsConfig}};
             #[tokio::main]
             async fn main() -> Result<(), tower::BoxError> {{
@@ -14,6 +24,7 @@ pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool)
                 for (k, v) in std::env::vars() {{
                     if k.starts_with(\"KINETICS_SECRET_\") {{
                         let key = k.replace(\"KINETICS_SECRET_\", \"\");
+                        {skip_local_secret}
                         secrets.insert(key, v);
                     }}
                 }}
@@ -24,8 +35,8 @@ pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool)
                     Err(_) => \"{{}}\".into(),
                 }};
 
-                let sqs_event = SqsEvent {{
-                    records: vec![SqsMessage {{
+                let sqs_event = aws_lambda_events::sqs::SqsEvent {{
+                    records: vec![aws_lambda_events::sqs::SqsMessage {{
                         message_id: Some(\"test\".into()),
                         body: Some(payload),
                         ..Default::default()
@@ -35,9 +46,12 @@ pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool)
                 // Convert SqsEvent to LambdaEvent<SqsEvent>
                 let context = lambda_runtime::Context::default();
                 let event = lambda_runtime::LambdaEvent::new(sqs_event, context);
+                let records = QueueRecord::from_sqsevent(event)?;
 
-                if let Err(err) = user_function(QueueRecord::from_sqsevent(event)?, &secrets, &kinetics_config).await {{
-                    eprintln!(\"Request failed: {{:?}}\", err);
+                // The batch response lists the records to retry, the same as returned to SQS
+                match user_function(records, &secrets, &kinetics_config).await {{
+                    Ok(retries) => println!(\"Result: {{}}\", serde_json::to_string(&retries.collect())?),
+                    Err(err) => eprintln!(\"Request failed: {{:?}}\", err),
                 }}
 
                 Ok(())
@@ -48,12 +62,10 @@ pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool)
             "{import_statement}
             use lambda_runtime::{{Error, run, service_fn}};\n\
             use kinetics::tools::{{queue::Record as QueueRecord, config::Config as KineticsConfig}};
-            #[tokio::main]\n\
-            async fn main() -> Result<(), Error> {{\n\
-                let user_function = {rust_function_name};
-                let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
-                println!(\"Provisioning secrets\");
-                let secrets_client = aws_sdk_ssm::Client::new(&config);
+            /// Fetch the secrets of the function from SSM
+            async fn fetch_secrets(
+                secrets_client: &aws_sdk_ssm::Client,
+            ) -> Result<std::collections::HashMap<String, String>, Box<dyn std::error::Error + Send + Sync>> {{
                 let secrets_names_env = \"KINETICS_SECRETS_NAMES\";
                 let mut secrets = std::collections::HashMap::new();
 
@@ -62,6 +74,28 @@ pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool)
                     .map(|s| s.trim().to_string())
                     .filter(|s| !s.is_empty())
                 {{
+                    let tags = match secrets_client
+                        .list_tags_for_resource()
+                        .resource_type(aws_sdk_ssm::types::ResourceTypeForTagging::Parameter)
+                        .resource_id(secret_name.clone())
+                        .send()
+                        .await
+                    {{
+                        Ok(output) => output.tag_list.unwrap_or_default(),
+                        Err(error) => {{
+                            eprintln!(\"Error fetching tags for secret {{}}: {{:?}}\", secret_name, error);
+                            {skip_inaccessible_secret}
+                        }}
+                    }};
+
+                    let name = match tags.iter().find(|t| t.key() == \"original_name\") {{
+                        Some(tag) => tag.value().to_string(),
+                        None => secret_name.clone(),
+                    }};
+
+                    // Only the declared secrets are fetched and decrypted
+                    {skip_deployed_secret}
+
                     let desc = secrets_client
                         .get_parameter()
                         .name(secret_name.clone())
@@ -70,29 +104,31 @@ pub fn worker(import_statement: &str, rust_function_name: &str, is_local: bool)
                         .await?;
 
                     let result = desc.parameter.unwrap();
+                    let secret_value = result.value().unwrap();
+                    secrets.insert(name, secret_value.to_string());
+                }}
 
-                    let tags = secrets_client
-                        .list_tags_for_resource()
-                        .resource_type(aws_sdk_ssm::types::ResourceTypeForTagging::Parameter)
-                        .resource_id(secret_name.clone())
-                        .send()
-                        .await?
-                        .tag_list
-                        .unwrap_or_default();
+                Ok(secrets)
+            }}
 
-                    let name = match tags.iter().find(|t| t.key() == \"original_name\") {{
-                        Some(tag) => tag.value(),
-                        None => &secret_name.clone(),
-                    }};
+            #[tokio::main]\n\
+            async fn main() -> Result<(), Error> {{\n\
+                let user_function = {rust_function_name};
+                let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+                println!(\"Provisioning secrets\");
+                let secrets_client = aws_sdk_ssm::Client::new(&config);
 
-                    let secret_value = result.value().unwrap();
-                    secrets.insert(name.into(), secret_value.to_string());
-                }}
+                // Fetched on cold start, then reused by invocations until expired, to pick up rotated ones
+                let secrets_cache = kinetics::tools::secrets::Cache::default();
+                secrets_cache.get(|| fetch_secrets(&secrets_client)).await?;
 
                 let kinetics_config = KineticsConfig::new(&config, None).await?;
                 println!(\"Serving requests\");
 
-                run(service_fn(|event| async {{
+                run(service_fn(|event: lambda_runtime::LambdaEvent<aws_lambda_events::sqs::SqsEvent>| async {{
+                    let secrets = secrets_cache.get(|| fetch_secrets(&secrets_client)).await?;
+                    let kinetics_config = kinetics_config.with_context(&event.context);
+
                     match user_function(QueueRecord::from_sqsevent(event)?, &secrets, &kinetics_config).await {{
                         Ok(response) => Ok(response.collect()),
                         Err(err) => {{
@@ -0,0 +1,27 @@
+use std::time::{Duration, Instant};
+
+/// Durations of build phases, reported by `kinetics build --profile-build`
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    phases: Vec<(String, Duration)>,
+}
+
+impl Timings {
+    /// Run the phase and record how long it took, regardless of its result
+    pub fn measure<T>(&mut self, phase: &str, run: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = run();
+        self.record(phase, started);
+        result
+    }
+
+    /// Record a phase which started at the given time and has just finished
+    pub fn record(&mut self, phase: &str, started: Instant) {
+        self.phases.push((phase.to_owned(), started.elapsed()));
+    }
+
+    /// Recorded phases in the order of execution
+    pub fn phases(&self) -> &[(String, Duration)] {
+        &self.phases
+    }
+}
@@ -0,0 +1,1045 @@
+use super::Project;
+use crate::error::Error;
+use eyre::{eyre, WrapErr};
+use kinetics_parser::{Params, Parser};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+use syn::meta::ParseNestedMeta;
+use syn::{
+    Attribute, Expr, Fields, GenericArgument, Generics, Item, Lit, LitStr, Meta, PathArguments,
+    Type,
+};
+use walkdir::WalkDir;
+
+/// A struct or an enum found in the source code
+enum Definition {
+    Struct(syn::ItemStruct),
+    Enum(syn::ItemEnum),
+}
+
+impl Definition {
+    fn ident(&self) -> &syn::Ident {
+        match self {
+            Definition::Struct(item) => &item.ident,
+            Definition::Enum(item) => &item.ident,
+        }
+    }
+
+    fn attrs(&self) -> &[Attribute] {
+        match self {
+            Definition::Struct(item) => &item.attrs,
+            Definition::Enum(item) => &item.attrs,
+        }
+    }
+
+    fn generics(&self) -> &Generics {
+        match self {
+            Definition::Struct(item) => &item.generics,
+            Definition::Enum(item) => &item.generics,
+        }
+    }
+}
+
+/// The subset of serde attributes affecting the JSON shape of a type
+#[derive(Default)]
+struct Serde {
+    rename: Option<String>,
+    rename_all: Option<String>,
+
+    /// Rule for the fields of all variants of an enum
+    rename_all_fields: Option<String>,
+
+    tag: Option<String>,
+    content: Option<String>,
+    untagged: bool,
+    skip: bool,
+    flatten: bool,
+
+    /// A struct with a single field is serialized as the field
+    transparent: bool,
+
+    /// The field might be missing in JSON, e.g. `default` or `skip_serializing_if`,
+    /// on a container `default` makes all the fields optional
+    is_optional: bool,
+}
+
+impl Serde {
+    fn from_attrs(attrs: &[Attribute]) -> Self {
+        let mut serde = Serde::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            // Unsupported attributes are skipped, the ones parsed before an error are kept
+            let _ = attr.parse_nested_meta(|meta| {
+                let name = meta.path.get_ident().map(|ident| ident.to_string());
+
+                match name.as_deref() {
+                    Some("rename") if meta.input.peek(syn::Token![=]) => {
+                        serde.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                    }
+                    Some("rename_all") if meta.input.peek(syn::Token![=]) => {
+                        serde.rename_all = Some(meta.value()?.parse::<LitStr>()?.value());
+                    }
+                    Some("rename_all_fields") if meta.input.peek(syn::Token![=]) => {
+                        serde.rename_all_fields = Some(meta.value()?.parse::<LitStr>()?.value());
+                    }
+                    Some("tag") => serde.tag = Some(meta.value()?.parse::<LitStr>()?.value()),
+                    Some("content") => {
+                        serde.content = Some(meta.value()?.parse::<LitStr>()?.value())
+                    }
+                    Some("untagged") => serde.untagged = true,
+                    Some("skip" | "skip_serializing") => serde.skip = true,
+                    Some("flatten") => serde.flatten = true,
+                    Some("transparent") => serde.transparent = true,
+                    Some("default" | "skip_serializing_if") => {
+                        serde.is_optional = true;
+                        skip_meta(&meta)?;
+                    }
+                    _ => skip_meta(&meta)?,
+                }
+
+                Ok(())
+            });
+        }
+
+        serde
+    }
+}
+
+/// Consume the value of a nested meta, e.g. `= "..."` or `(...)`
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|meta| skip_meta(&meta))?;
+    }
+
+    Ok(())
+}
+
+/// Apply serde `rename_all` rule to a field (snake_case) or a variant (PascalCase) name
+///
+/// Follows serde_derive, so that e.g. acronyms and leading underscores are converted the same way.
+fn rename(name: &str, rule: &str, is_variant: bool) -> String {
+    if is_variant {
+        let snake = || {
+            let mut snake = String::new();
+
+            for (i, c) in name.char_indices() {
+                if i > 0 && c.is_uppercase() {
+                    snake.push('_');
+                }
+
+                snake.push(c.to_ascii_lowercase());
+            }
+
+            snake
+        };
+
+        return match rule {
+            "lowercase" => name.to_ascii_lowercase(),
+            "UPPERCASE" => name.to_ascii_uppercase(),
+            "camelCase" => lower_first(name),
+            "snake_case" => snake(),
+            "SCREAMING_SNAKE_CASE" => snake().to_ascii_uppercase(),
+            "kebab-case" => snake().replace('_', "-"),
+            "SCREAMING-KEBAB-CASE" => snake().to_ascii_uppercase().replace('_', "-"),
+            _ => name.to_owned(),
+        };
+    }
+
+    let pascal = || {
+        let mut pascal = String::new();
+        let mut capitalize = true;
+
+        for c in name.chars() {
+            if c == '_' {
+                capitalize = true;
+            } else if capitalize {
+                pascal.push(c.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                pascal.push(c);
+            }
+        }
+
+        pascal
+    };
+
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => lower_first(&pascal()),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_owned(),
+    }
+}
+
+fn lower_first(name: &str) -> String {
+    match name.chars().next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + &name[first.len_utf8()..],
+        None => String::new(),
+    }
+}
+
+/// Property name, quoted unless it is a valid identifier
+fn property(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+
+    if is_identifier {
+        name.to_owned()
+    } else {
+        format!("{name:?}")
+    }
+}
+
+/// Doc comment of an item as TSDoc
+fn doc(attrs: &[Attribute], indent: &str) -> String {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(value) => Some(value.value().trim().to_owned()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    match lines.as_slice() {
+        [] => String::new(),
+        [line] => format!("{indent}/** {line} */\n"),
+        lines => format!(
+            "{indent}/**\n{}{indent} */\n",
+            lines
+                .iter()
+                .map(|line| format!("{indent} * {line}\n").replace(" * \n", " *\n"))
+                .collect::<String>()
+        ),
+    }
+}
+
+/// Generates TypeScript definitions for the types and the types they refer to
+struct Generator {
+    /// Full path, e.g. `crate::api::User` -> module path and the definition
+    definitions: HashMap<String, (String, Definition)>,
+
+    queue: VecDeque<String>,
+    queued: HashSet<String>,
+
+    /// TypeScript name -> full path of the type it is generated from
+    names: HashMap<String, String>,
+}
+
+impl Generator {
+    /// Collect the structs and enums of the crate
+    fn new(path: &Path) -> eyre::Result<Self> {
+        let mut definitions = HashMap::new();
+        let src = path.join("src");
+
+        for entry in WalkDir::new(&src)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "rs"))
+        {
+            let content = fs::read_to_string(entry.path())
+                .wrap_err(format!("Failed to read {:?}", entry.path()))?;
+
+            let file = syn::parse_file(&content)
+                .wrap_err(format!("Failed to parse {:?}", entry.path()))?;
+
+            let mut module = vec!["crate".to_owned()];
+            let relative = entry.path().strip_prefix(&src)?.with_extension("");
+
+            for component in relative.iter().filter_map(|c| c.to_str()) {
+                module.push(component.to_owned());
+            }
+
+            // lib.rs, main.rs and mod.rs are the modules of their dirs
+            if module.len() == 2 && matches!(module[1].as_str(), "lib" | "main")
+                || module.last().is_some_and(|last| last == "mod")
+            {
+                module.pop();
+            }
+
+            Self::collect(&file.items, &module.join("::"), &mut definitions);
+        }
+
+        Ok(Generator {
+            definitions,
+            queue: VecDeque::new(),
+            queued: HashSet::new(),
+            names: HashMap::new(),
+        })
+    }
+
+    fn collect(
+        items: &[Item],
+        module: &str,
+        definitions: &mut HashMap<String, (String, Definition)>,
+    ) {
+        for item in items {
+            let definition = match item {
+                Item::Struct(item) => Definition::Struct(item.clone()),
+                Item::Enum(item) => Definition::Enum(item.clone()),
+                Item::Mod(item) => {
+                    if let Some((_, items)) = &item.content {
+                        Self::collect(items, &format!("{module}::{}", item.ident), definitions);
+                    }
+
+                    continue;
+                }
+                _ => continue,
+            };
+
+            definitions.insert(
+                format!("{module}::{}", definition.ident()),
+                (module.to_owned(), definition),
+            );
+        }
+    }
+
+    /// Find a type referenced from the module, by its path or a unique name in the crate
+    fn resolve(&self, path: &syn::Path, module: &str) -> Option<String> {
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+
+        let name = segments.last()?;
+
+        let candidates = [
+            segments.join("::"),
+            format!("{module}::{}", segments.join("::")),
+        ];
+
+        if let Some(found) = candidates
+            .iter()
+            .find(|candidate| self.definitions.contains_key(*candidate))
+        {
+            return Some(found.clone());
+        }
+
+        let mut matching = self
+            .definitions
+            .iter()
+            .filter(|(_, (_, definition))| definition.ident() == name)
+            .map(|(path, _)| path.clone());
+
+        match (matching.next(), matching.next()) {
+            (Some(found), None) => Some(found),
+            _ => None,
+        }
+    }
+
+    /// Queue the type for generation, unless it has been queued already
+    fn enqueue(&mut self, path: &str) -> eyre::Result<()> {
+        if !self.queued.insert(path.to_owned()) {
+            return Ok(());
+        }
+
+        let (_, definition) = self
+            .definitions
+            .get(path)
+            .ok_or_else(|| eyre!("Type {path} is not found in the crate"))?;
+
+        let name = definition.ident().to_string();
+
+        if let Some(existing) = self.names.insert(name.clone(), path.to_owned()) {
+            return Err(eyre!(
+                "Types {existing} and {path} have the same name {name}, rename one of them"
+            ));
+        }
+
+        self.queue.push_back(path.to_owned());
+        Ok(())
+    }
+
+    /// Generate the definitions of the queued types, and the types they refer to
+    fn generate(&mut self) -> eyre::Result<String> {
+        let mut output = vec![];
+
+        while let Some(path) = self.queue.pop_front() {
+            let (module, definition) = &self.definitions[&path];
+            let (module, definition) = (module.clone(), Self::clone_definition(definition));
+            output.push(self.definition(&definition, &module)?);
+        }
+
+        Ok(output.join("\n"))
+    }
+
+    fn clone_definition(definition: &Definition) -> Definition {
+        match definition {
+            Definition::Struct(item) => Definition::Struct(item.clone()),
+            Definition::Enum(item) => Definition::Enum(item.clone()),
+        }
+    }
+
+    fn definition(&mut self, definition: &Definition, module: &str) -> eyre::Result<String> {
+        let serde = Serde::from_attrs(definition.attrs());
+
+        let generics = definition
+            .generics()
+            .type_params()
+            .map(|param| param.ident.to_string())
+            .collect::<Vec<_>>();
+
+        let name = if generics.is_empty() {
+            definition.ident().to_string()
+        } else {
+            format!("{}<{}>", definition.ident(), generics.join(", "))
+        };
+
+        let doc = doc(definition.attrs(), "");
+
+        let body = match definition {
+            Definition::Struct(item) if serde.transparent => {
+                let field = item
+                    .fields
+                    .iter()
+                    .find(|field| !Serde::from_attrs(&field.attrs).skip)
+                    .ok_or_else(|| eyre!("Transparent struct {} has no fields", item.ident))?;
+
+                self.ts_type(&field.ty, module, &generics)?
+            }
+            Definition::Struct(item) => match &item.fields {
+                Fields::Named(_) => {
+                    let (mut fields, flattened) =
+                        self.fields(&item.fields, &serde, module, &generics)?;
+
+                    // Internally tagged struct has the tag with its name, like an enum variant
+                    if let Some(tag) = &serde.tag {
+                        let value = serde.rename.clone().unwrap_or(item.ident.to_string());
+                        fields.insert(0, format!("  {}: {value:?};", property(tag)));
+                    }
+
+                    if flattened.is_empty() {
+                        return Ok(format!(
+                            "{doc}export interface {name} {{\n{}}}\n",
+                            fields
+                                .iter()
+                                .map(|field| format!("{field}\n"))
+                                .collect::<String>()
+                        ));
+                    }
+
+                    let mut parts = flattened;
+                    parts.push(Self::object(&fields));
+                    parts.join(" & ")
+                }
+                Fields::Unnamed(fields) => {
+                    self.tuple(fields.unnamed.iter().map(|f| &f.ty), module, &generics)?
+                }
+                Fields::Unit => "null".to_owned(),
+            },
+            Definition::Enum(item) => {
+                let mut variants = vec![];
+
+                for variant in &item.variants {
+                    let variant_serde = Serde::from_attrs(&variant.attrs);
+
+                    if variant_serde.skip {
+                        continue;
+                    }
+
+                    let tag = variant_serde.rename.clone().unwrap_or_else(|| {
+                        serde
+                            .rename_all
+                            .as_deref()
+                            .map(|rule| rename(&variant.ident.to_string(), rule, true))
+                            .unwrap_or_else(|| variant.ident.to_string())
+                    });
+
+                    let content = match &variant.fields {
+                        Fields::Named(_) => {
+                            // Fields of variants are renamed by the variant's own rule,
+                            // or the one for all variants of the enum
+                            let fields_serde = Serde {
+                                rename_all: variant_serde
+                                    .rename_all
+                                    .clone()
+                                    .or(serde.rename_all_fields.clone()),
+                                is_optional: variant_serde.is_optional,
+                                ..Default::default()
+                            };
+
+                            let (fields, flattened) =
+                                self.fields(&variant.fields, &fields_serde, module, &generics)?;
+
+                            let mut parts = flattened;
+                            parts.push(Self::object(&fields));
+                            Some(parts.join(" & "))
+                        }
+                        Fields::Unnamed(fields) => Some(self.tuple(
+                            fields.unnamed.iter().map(|f| &f.ty),
+                            module,
+                            &generics,
+                        )?),
+                        Fields::Unit => None,
+                    };
+
+                    let tag_literal = format!("{tag:?}");
+
+                    variants.push(
+                        match (&serde.tag, &serde.content, serde.untagged, content) {
+                            (_, _, true, content) => content.unwrap_or("null".to_owned()),
+                            (Some(tag_name), Some(content_name), _, Some(content)) => format!(
+                                "{{ {}: {tag_literal}; {}: {content} }}",
+                                property(tag_name),
+                                property(content_name)
+                            ),
+                            (Some(tag_name), _, _, None) => {
+                                format!("{{ {}: {tag_literal} }}", property(tag_name))
+                            }
+                            (Some(tag_name), None, _, Some(content)) => {
+                                format!("{{ {}: {tag_literal} }} & {content}", property(tag_name))
+                            }
+                            (None, _, _, None) => tag_literal,
+                            (None, _, _, Some(content)) => {
+                                format!("{{ {}: {content} }}", property(&tag))
+                            }
+                        },
+                    );
+                }
+
+                match variants.as_slice() {
+                    [] => "never".to_owned(),
+                    // Each variant on its own line
+                    variants => variants
+                        .iter()
+                        .map(|variant| format!("\n  | {variant}"))
+                        .collect(),
+                }
+            }
+        };
+
+        let separator = if body.starts_with('\n') { "" } else { " " };
+        Ok(format!("{doc}export type {name} ={separator}{body};\n"))
+    }
+
+    /// Property lines of named fields and the types of flattened ones
+    fn fields(
+        &mut self,
+        fields: &Fields,
+        serde: &Serde,
+        module: &str,
+        generics: &[String],
+    ) -> eyre::Result<(Vec<String>, Vec<String>)> {
+        let mut properties = vec![];
+        let mut flattened = vec![];
+
+        for field in fields {
+            let field_serde = Serde::from_attrs(&field.attrs);
+
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+
+            if field_serde.skip {
+                continue;
+            }
+
+            if field_serde.flatten {
+                flattened.push(self.ts_type(&field.ty, module, generics)?);
+                continue;
+            }
+
+            let ident = ident.to_string();
+            let ident = ident.strip_prefix("r#").unwrap_or(&ident);
+
+            let name = field_serde.rename.clone().unwrap_or_else(|| {
+                serde
+                    .rename_all
+                    .as_deref()
+                    .map(|rule| rename(ident, rule, false))
+                    .unwrap_or_else(|| ident.to_owned())
+            });
+
+            let is_option = matches!(
+                &field.ty,
+                Type::Path(ty) if ty.path.segments.last().is_some_and(|s| s.ident == "Option")
+            );
+
+            let optional = if is_option || field_serde.is_optional || serde.is_optional {
+                "?"
+            } else {
+                ""
+            };
+
+            properties.push(format!(
+                "{}  {}{optional}: {};",
+                doc(&field.attrs, "  "),
+                property(&name),
+                self.ts_type(&field.ty, module, generics)?
+            ));
+        }
+
+        Ok((properties, flattened))
+    }
+
+    /// Inline object type from property lines
+    fn object(properties: &[String]) -> String {
+        if properties.is_empty() {
+            return "{}".to_owned();
+        }
+
+        // Doc comments are omitted in inline objects
+        let properties = properties
+            .iter()
+            .filter_map(|property| property.lines().last())
+            .map(|property| property.trim().trim_end_matches(';'))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        format!("{{ {properties} }}")
+    }
+
+    /// Newtypes are serialized as the inner value, tuples as arrays
+    fn tuple<'a>(
+        &mut self,
+        types: impl Iterator<Item = &'a Type>,
+        module: &str,
+        generics: &[String],
+    ) -> eyre::Result<String> {
+        let types = types
+            .map(|ty| self.ts_type(ty, module, generics))
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Ok(match types.as_slice() {
+            [single] => single.clone(),
+            types => format!("[{}]", types.join(", ")),
+        })
+    }
+
+    fn ts_type(&mut self, ty: &Type, module: &str, generics: &[String]) -> eyre::Result<String> {
+        let array = |item: String| {
+            if item.contains(' ') {
+                format!("({item})[]")
+            } else {
+                format!("{item}[]")
+            }
+        };
+
+        Ok(match ty {
+            Type::Reference(ty) => self.ts_type(&ty.elem, module, generics)?,
+            Type::Paren(ty) => self.ts_type(&ty.elem, module, generics)?,
+            Type::Group(ty) => self.ts_type(&ty.elem, module, generics)?,
+            Type::Slice(ty) => array(self.ts_type(&ty.elem, module, generics)?),
+            Type::Array(ty) => array(self.ts_type(&ty.elem, module, generics)?),
+            Type::Tuple(ty) if ty.elems.is_empty() => "null".to_owned(),
+            Type::Tuple(ty) => format!(
+                "[{}]",
+                ty.elems
+                    .iter()
+                    .map(|ty| self.ts_type(ty, module, generics))
+                    .collect::<eyre::Result<Vec<_>>>()?
+                    .join(", ")
+            ),
+            Type::Path(ty) => {
+                let Some(segment) = ty.path.segments.last() else {
+                    return Ok("unknown".to_owned());
+                };
+
+                let args = match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => args
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                        .collect::<Vec<_>>(),
+                    _ => vec![],
+                };
+
+                let resolved = self.resolve(&ty.path, module);
+
+                let mut arg = |index: usize| -> eyre::Result<String> {
+                    match args.get(index) {
+                        Some(ty) => self.ts_type(ty, module, generics),
+                        None => Ok("unknown".to_owned()),
+                    }
+                };
+
+                let ident = segment.ident.to_string();
+
+                match ident.as_str() {
+                    _ if ty.path.segments.len() == 1 && generics.contains(&ident) => ident,
+                    "String" | "str" | "char" | "PathBuf" | "Path" | "Uuid" | "DateTime"
+                    | "NaiveDate" | "NaiveDateTime" | "NaiveTime" => "string".to_owned(),
+                    "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32"
+                    | "u64" | "u128" | "usize" | "f32" | "f64" => "number".to_owned(),
+                    "bool" => "boolean".to_owned(),
+                    "Option" => format!("{} | null", arg(0)?),
+                    "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => array(arg(0)?),
+                    "HashMap" | "BTreeMap" => format!("Record<string, {}>", arg(1)?),
+                    "Box" | "Rc" | "Arc" | "Cow" => arg(args.len().saturating_sub(1))?,
+                    "Value" => "unknown".to_owned(),
+                    _ => match resolved {
+                        Some(path) => {
+                            let args = (0..args.len())
+                                .map(&mut arg)
+                                .collect::<eyre::Result<Vec<_>>>()?;
+                            self.enqueue(&path)?;
+
+                            if args.is_empty() {
+                                ident
+                            } else {
+                                format!("{ident}<{}>", args.join(", "))
+                            }
+                        }
+                        None => "unknown".to_owned(),
+                    },
+                }
+            }
+            _ => "unknown".to_owned(),
+        })
+    }
+}
+
+impl Project {
+    /// TypeScript definitions of the types listed in `types` of the endpoints
+    ///
+    /// Only structs and enums defined in the crate are generated, the types they refer to
+    /// are included as well. Serde attributes changing the JSON shape are respected,
+    /// types which can't be resolved are typed as `unknown`.
+    pub fn typescript(&self) -> eyre::Result<String> {
+        let functions = Parser::new(Some(&self.path), &self.features)?.functions;
+
+        let types = functions
+            .iter()
+            .filter_map(|function| match &function.params {
+                Params::Endpoint(params) => Some(&params.types),
+                _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if types.is_empty() {
+            return Err(Error::new(
+                "No types to export",
+                Some("List request and response types of endpoints, e.g. #[endpoint(types = [\"crate::api::User\"])]"),
+            )
+            .into());
+        }
+
+        let mut generator = Generator::new(&self.path)?;
+
+        for path in types {
+            let path = syn::parse_str::<syn::Path>(path)?;
+
+            let resolved = generator
+                .resolve(&path, "crate")
+                .ok_or_else(|| eyre!("Type {} is not found in the crate", quote_path(&path)))?;
+
+            generator.enqueue(&resolved)?;
+        }
+
+        Ok(format!(
+            "// Generated by `kinetics export typescript`, do not edit\n\n{}",
+            generator.generate()?
+        ))
+    }
+}
+
+fn quote_path(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::OptionExt;
+
+    /// TypeScript definitions of the types from the source of a single file crate
+    fn generate(source: &str, types: &[&str]) -> eyre::Result<String> {
+        let file = syn::parse_file(source)?;
+        let mut definitions = HashMap::new();
+        Generator::collect(&file.items, "crate", &mut definitions);
+
+        let mut generator = Generator {
+            definitions,
+            queue: VecDeque::new(),
+            queued: HashSet::new(),
+            names: HashMap::new(),
+        };
+
+        for ty in types {
+            let path = generator
+                .resolve(&syn::parse_str(ty)?, "crate")
+                .ok_or_eyre("Type not found")?;
+
+            generator.enqueue(&path)?;
+        }
+
+        generator.generate()
+    }
+
+    #[test]
+    fn field_attributes() {
+        let source = r#"
+            #[serde(rename_all = "camelCase")]
+            struct User {
+                user_id: u64,
+                #[serde(rename = "mail")]
+                email: String,
+                #[serde(skip)]
+                password: String,
+                #[serde(skip_serializing)]
+                session: String,
+                #[serde(default)]
+                tags: Vec<String>,
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                nick_name: Option<String>,
+                r#type: String,
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["User"]).unwrap(),
+            "export interface User {\n  userId: number;\n  mail: string;\n  tags?: string[];\n  \
+             nickName?: string | null;\n  type: string;\n}\n"
+        );
+    }
+
+    #[test]
+    fn rename_all_fields_rules() {
+        let cases = [
+            ("lowercase", "user_id"),
+            ("UPPERCASE", "USER_ID"),
+            ("PascalCase", "UserId"),
+            ("camelCase", "userId"),
+            ("snake_case", "user_id"),
+            ("SCREAMING_SNAKE_CASE", "USER_ID"),
+            ("kebab-case", "user-id"),
+            ("SCREAMING-KEBAB-CASE", "USER-ID"),
+        ];
+
+        for (rule, expected) in cases {
+            assert_eq!(rename("user_id", rule, false), expected, "{rule}");
+        }
+
+        // Leading underscore capitalizes the next letter, the same as serde does
+        assert_eq!(rename("_private", "PascalCase", false), "Private");
+    }
+
+    #[test]
+    fn rename_all_variants_rules() {
+        let cases = [
+            ("lowercase", "userid"),
+            ("UPPERCASE", "USERID"),
+            ("PascalCase", "UserId"),
+            ("camelCase", "userId"),
+            ("snake_case", "user_id"),
+            ("SCREAMING_SNAKE_CASE", "USER_ID"),
+            ("kebab-case", "user-id"),
+            ("SCREAMING-KEBAB-CASE", "USER-ID"),
+        ];
+
+        for (rule, expected) in cases {
+            assert_eq!(rename("UserId", rule, true), expected, "{rule}");
+        }
+
+        // Every capital letter starts a word
+        assert_eq!(rename("HTTPRequest", "snake_case", true), "h_t_t_p_request");
+    }
+
+    #[test]
+    fn externally_tagged_enum() {
+        let source = r#"
+            enum Event {
+                Started,
+                Moved { x: i32 },
+                Renamed(String),
+                Pair(u8, u8),
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Event"]).unwrap(),
+            "export type Event =\n  | \"Started\"\n  | { Moved: { x: number } }\n  \
+             | { Renamed: string }\n  | { Pair: [number, number] };\n"
+        );
+    }
+
+    #[test]
+    fn internally_tagged_enum() {
+        let source = r#"
+            #[serde(tag = "kind", rename_all = "snake_case")]
+            enum Shape {
+                Circle { radius: f64 },
+                Square(Side),
+                Empty,
+            }
+
+            struct Side {
+                len: f64,
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Shape"]).unwrap(),
+            "export type Shape =\n  | { kind: \"circle\" } & { radius: number }\n  \
+             | { kind: \"square\" } & Side\n  | { kind: \"empty\" };\n\n\
+             export interface Side {\n  len: number;\n}\n"
+        );
+    }
+
+    #[test]
+    fn adjacently_tagged_enum() {
+        let source = r#"
+            #[serde(tag = "t", content = "c")]
+            enum Message {
+                Ping,
+                Text(String),
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Message"]).unwrap(),
+            "export type Message =\n  | { t: \"Ping\" }\n  | { t: \"Text\"; c: string };\n"
+        );
+    }
+
+    #[test]
+    fn untagged_enum() {
+        let source = r#"
+            #[serde(untagged)]
+            enum Id {
+                Number(u64),
+                Name(String),
+                Missing,
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Id"]).unwrap(),
+            "export type Id =\n  | number\n  | string\n  | null;\n"
+        );
+    }
+
+    #[test]
+    fn variant_attributes() {
+        let source = r#"
+            #[serde(rename_all_fields = "camelCase")]
+            enum Request {
+                Create { user_name: String },
+                #[serde(rename = "remove", rename_all = "kebab-case")]
+                Delete { user_id: u64 },
+                #[serde(skip)]
+                Internal,
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Request"]).unwrap(),
+            "export type Request =\n  | { Create: { userName: string } }\n  \
+             | { remove: { \"user-id\": number } };\n"
+        );
+    }
+
+    #[test]
+    fn flatten() {
+        let source = r#"
+            struct Page {
+                #[serde(flatten)]
+                meta: Meta,
+                items: Vec<u8>,
+            }
+
+            struct Meta {
+                total: u32,
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Page"]).unwrap(),
+            "export type Page = Meta & { items: number[] };\n\n\
+             export interface Meta {\n  total: number;\n}\n"
+        );
+    }
+
+    #[test]
+    fn container_attributes() {
+        let source = r#"
+            #[serde(transparent)]
+            struct Tags {
+                inner: Vec<String>,
+            }
+
+            #[serde(default)]
+            struct Settings {
+                limit: u32,
+            }
+
+            #[serde(tag = "type")]
+            struct Ping {
+                id: u32,
+            }
+        "#;
+
+        assert_eq!(
+            generate(source, &["Tags", "Settings", "Ping"]).unwrap(),
+            "export type Tags = string[];\n\n\
+             export interface Settings {\n  limit?: number;\n}\n\n\
+             export interface Ping {\n  type: \"Ping\";\n  id: number;\n}\n"
+        );
+    }
+
+    #[test]
+    fn referenced_types_and_docs() {
+        let source = r#"
+            /// A page of results
+            struct Page<T> {
+                /// Items of the page
+                items: Vec<T>,
+                counts: HashMap<String, u32>,
+                next: Option<Box<Cursor>>,
+            }
+
+            struct Cursor(String, u32);
+
+            struct Marker;
+        "#;
+
+        assert_eq!(
+            generate(source, &["Page", "Marker"]).unwrap(),
+            "/** A page of results */\nexport interface Page<T> {\n  /** Items of the page */\n  \
+             items: T[];\n  counts: Record<string, number>;\n  next?: Cursor | null;\n}\n\n\
+             export type Marker = null;\n\n\
+             export type Cursor = [string, number];\n"
+        );
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let source = r#"
+            mod a {
+                struct User {}
+            }
+
+            mod b {
+                struct User {}
+            }
+        "#;
+
+        let error = generate(source, &["a::User", "b::User"]).unwrap_err();
+        assert!(error.to_string().contains("have the same name User"));
+    }
+}
@@ -0,0 +1,81 @@
+use super::filehash::FileHash;
+use super::Project;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// How often the source files are checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Successive saves within this interval trigger a single rebuild
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Watches the source code of a project, for rebuilding functions on changes
+///
+/// Files are compared by their checksums, so saving a file without changing it
+/// (or touching it) does not trigger a rebuild.
+pub struct Watcher {
+    src: PathBuf,
+
+    /// Path -> hash of each .rs file, as of the last change
+    checksums: HashMap<PathBuf, String>,
+}
+
+impl Watcher {
+    pub fn new(project: &Project) -> eyre::Result<Self> {
+        let src = project.path.join("src");
+
+        Ok(Watcher {
+            checksums: Self::checksums(&src)?,
+            src,
+        })
+    }
+
+    /// Wait until a .rs file is changed, added or removed
+    ///
+    /// Returns once the changes settle down, e.g. after an editor saved all files.
+    pub async fn changed(&mut self) -> eyre::Result<()> {
+        let mut checksums = Self::checksums(&self.src)?;
+
+        while checksums == self.checksums {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            checksums = Self::checksums(&self.src)?;
+        }
+
+        loop {
+            tokio::time::sleep(DEBOUNCE_INTERVAL).await;
+            let settled = Self::checksums(&self.src)?;
+
+            if settled == checksums {
+                break;
+            }
+
+            checksums = settled;
+        }
+
+        self.checksums = checksums;
+        Ok(())
+    }
+
+    fn checksums(src: &Path) -> eyre::Result<HashMap<PathBuf, String>> {
+        let mut checksums = HashMap::new();
+
+        for entry in WalkDir::new(src).into_iter().filter_map(Result::ok) {
+            let path = entry.path();
+
+            if !entry.file_type().is_file() || path.extension().is_none_or(|ext| ext != "rs") {
+                continue;
+            }
+
+            // The file might be removed in between, it is then picked up on the next check
+            let Ok(contents) = std::fs::read(path) else {
+                continue;
+            };
+
+            checksums.insert(path.to_path_buf(), FileHash::hash_from_bytes(contents)?);
+        }
+
+        Ok(checksums)
+    }
+}
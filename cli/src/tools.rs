@@ -1,21 +1,56 @@
 pub mod config;
 pub mod http;
+pub mod jwt;
+pub mod kvdb;
+pub mod metrics;
 pub mod queue;
+pub mod secrets;
+
+/// Max length of a name returned by [resource_name]
+///
+/// Fits the limits of the resources named this way, e.g. 80 chars of an SQS queue name
+/// along with `.fifo` suffix.
+pub const MAX_RESOURCE_NAME_LEN: usize = READABLE_NAME_LEN + HASH_LEN;
+
+/// Max length of the readable part of [resource_name]
+const READABLE_NAME_LEN: usize = 32;
+
+/// Length of the hash part of [resource_name], 128 bits of SHA-256 in hex
+const HASH_LEN: usize = 32;
 
 /// Unique resource name
 ///
 /// Construct a readable name by escaping non-ascii chars, and appending a hash of
 /// a full unescaped name (for uniqueness reason).
 ///
-/// The string is truncated to 64 symbols, which is the maximum length
-/// for a resource name in most platforms.
+/// The string is at most [MAX_RESOURCE_NAME_LEN] symbols, which is the maximum length
+/// for a resource name in most platforms. Only the readable part is truncated, so names
+/// differing after it still get different hashes. The names of deployed resources depend
+/// on the scheme, so it must not change.
+///
+/// ```
+/// use kinetics::tools::{resource_name, MAX_RESOURCE_NAME_LEN};
+///
+/// let long = "a".repeat(1000);
+/// let first = resource_name(&long, &long, &format!("{long}-first"));
+/// let second = resource_name(&long, &long, &format!("{long}-second"));
+///
+/// assert_ne!(first, second);
+/// assert_eq!(first.len(), MAX_RESOURCE_NAME_LEN);
+///
+/// // Non-ascii chars are dropped from the readable part only
+/// let name = resource_name("user", "project", "Очередь-заказов");
+/// assert_eq!(name.len(), 32);
+/// assert_ne!(name, resource_name("user", "project", "Очередь-платежей"));
+/// assert!(name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+/// ```
 pub fn resource_name(user_name: &str, project_name: &str, resource_name: &str) -> String {
     format!(
         "{}{}",
         // Keep readable name to distinguish resources in the dahsboards
         resource_name
             .chars()
-            .take(32)
+            .take(READABLE_NAME_LEN)
             .filter(|c| c.is_ascii_alphanumeric())
             .collect::<String>()
             .to_lowercase(),
@@ -23,7 +58,7 @@ pub fn resource_name(user_name: &str, project_name: &str, resource_name: &str) -
         sha256::digest(format!("{}-{}-{}", user_name, project_name, resource_name))
             .to_string()
             .chars()
-            .take(32)
+            .take(HASH_LEN)
             .collect::<String>(),
     )
 }
@@ -1,17 +1,25 @@
 use crate::sqldb::SqlDb;
+use crate::tools::queue::{Client as QueueClient, Record as QueueRecord, Retries as QueueRetries};
 use aws_config::SdkConfig;
-use lambda_runtime::Error;
+use lambda_runtime::{Context, Error};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
 
 /// Configuration of an endpoint lambda
 #[derive(Clone, Debug)]
 pub struct EndpointConfig {
     pub url_pattern: String,
+
+    /// Other URL patterns the endpoint responds at
+    pub url_aliases: Vec<String>,
 }
 
 impl EndpointConfig {
-    pub fn new(url_pattern: &str) -> Self {
+    pub fn new(url_pattern: &str, url_aliases: &[String]) -> Self {
         Self {
             url_pattern: url_pattern.to_owned(),
+            url_aliases: url_aliases.to_vec(),
         }
     }
 }
@@ -20,8 +28,13 @@ impl std::fmt::Display for EndpointConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "EndpointConfig {{ url_pattern: \"{}\".to_string() }}",
-            self.url_pattern
+            "EndpointConfig {{ url_pattern: {:?}.to_string(), url_aliases: vec![{}] }}",
+            self.url_pattern,
+            self.url_aliases
+                .iter()
+                .map(|alias| format!("{alias:?}.to_string()"))
+                .collect::<Vec<_>>()
+                .join(", ")
         )
     }
 }
@@ -33,6 +46,15 @@ impl std::fmt::Display for EndpointConfig {
 pub struct Config {
     pub db: SqlDb,
     endpoint: Option<EndpointConfig>,
+
+    /// Claims of the verified JWT of the request, for endpoints with `jwt` enabled
+    claims: Option<Value>,
+
+    /// ID of the request, for endpoints with `request_id` enabled
+    request_id: Option<String>,
+
+    /// Lambda context of the current invocation, not set when run locally
+    context: Option<Context>,
 }
 
 impl Config {
@@ -47,6 +69,9 @@ impl Config {
                     .await?
                     .spawn_password_refresh(),
                 endpoint,
+                claims: None,
+                request_id: None,
+                context: None,
             });
         }
 
@@ -57,10 +82,95 @@ impl Config {
         Ok(Self {
             db: SqlDb::new_local(&connection_string, config).await?,
             endpoint,
+            claims: None,
+            request_id: None,
+            context: None,
         })
     }
 
     pub fn url_pattern(&self) -> Option<&String> {
         self.endpoint.as_ref().map(|e| &e.url_pattern)
     }
+
+    /// Other URL patterns of the endpoint, set with a list in `url_path`
+    pub fn url_aliases(&self) -> &[String] {
+        self.endpoint
+            .as_ref()
+            .map(|e| e.url_aliases.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Claims of the caller's JWT, e.g. user id in `sub`
+    ///
+    /// Only set for endpoints with `jwt` verification enabled.
+    pub fn claims(&self) -> Option<&Value> {
+        self.claims.as_ref()
+    }
+
+    /// A copy of the config for a single request, with the caller's claims
+    pub fn with_claims(&self, claims: Value) -> Self {
+        Self {
+            claims: Some(claims),
+            ..self.clone()
+        }
+    }
+
+    /// ID of the current request, taken from the request header or generated
+    ///
+    /// Only set for endpoints with `request_id` enabled. Pass it on to downstream services
+    /// in order to correlate the logs.
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    /// A copy of the config for a single request, with its ID
+    pub fn with_request_id(&self, request_id: &str) -> Self {
+        Self {
+            request_id: Some(request_id.to_owned()),
+            ..self.clone()
+        }
+    }
+
+    /// Lambda context of the current invocation, e.g. AWS request ID or function ARN
+    ///
+    /// Not set when the function is run locally with `kinetics invoke --local`.
+    pub fn context(&self) -> Option<&Context> {
+        self.context.as_ref()
+    }
+
+    /// Time left until the invocation is timed out by Lambda
+    ///
+    /// Zero once the deadline has passed, `None` when run locally.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.context.as_ref().map(|context| {
+            context
+                .deadline()
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        })
+    }
+
+    /// A copy of the config for a single invocation, with its Lambda context
+    pub fn with_context(&self, context: &Context) -> Self {
+        Self {
+            context: Some(context.to_owned()),
+            ..self.clone()
+        }
+    }
+
+    /// Client of the worker's queue
+    ///
+    /// Available to all types of functions, so that any of them is able to send to a worker.
+    /// Same as [QueueClient::from_worker].
+    pub async fn queue<'a, Fut>(
+        &self,
+        worker: impl Fn(Vec<QueueRecord>, &'a HashMap<String, String>, &'a Config) -> Fut,
+    ) -> eyre::Result<QueueClient>
+    where
+        Fut: std::future::Future<
+            Output = Result<QueueRetries, Box<dyn std::error::Error + Send + Sync>>,
+        >,
+    {
+        QueueClient::from_worker(worker).await
+    }
 }
@@ -1,5 +1,140 @@
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bytes::Bytes;
+use http::{Response, StatusCode};
 use http_body::{Body as HttpBody, Frame, SizeHint};
-use std::{borrow::Cow, mem::take, pin::Pin, task::Poll};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{borrow::Cow, mem::take, pin::Pin, str::FromStr, task::Poll};
+use tokio::sync::mpsc;
+use tower::BoxError;
+
+/// Convert a value returned by an endpoint into an HTTP response
+///
+/// Endpoints return any type implementing it, e.g. `Response<Body>`, `String`,
+/// or `(StatusCode, Json<T>)`, instead of building the response by hand.
+///
+/// Implement it for custom error types in order to map domain errors to specific
+/// status codes and bodies, e.g. `NotFound` to 404. The response is then returned
+/// to the caller instead of failing the invocation.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be returned from an endpoint",
+    note = "return `Response<B>`, `String`, `&'static str`, `Vec<u8>`, `serde_json::Value`, `Json<T>`, \
+        a `(StatusCode, T)` tuple of those, or implement `kinetics::tools::http::IntoResponse`"
+)]
+pub trait IntoResponse {
+    /// Returns the response, or an error to be propagated to the runtime
+    fn into_response(self) -> Result<Response<Body>, BoxError>;
+}
+
+/// General-purpose errors are propagated to the runtime as is
+impl IntoResponse for BoxError {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        Err(self)
+    }
+}
+
+impl<B: Into<Body>> IntoResponse for Response<B> {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        Ok(self.map(Into::into))
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        with_content_type(self, "text/plain; charset=utf-8")
+    }
+}
+
+impl IntoResponse for &'static str {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        with_content_type(self, "text/plain; charset=utf-8")
+    }
+}
+
+impl IntoResponse for Vec<u8> {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        with_content_type(self, "application/octet-stream")
+    }
+}
+
+impl IntoResponse for serde_json::Value {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        Json(self).into_response()
+    }
+}
+
+/// The status code replaces the default 200 of the response
+impl<T: IntoResponse> IntoResponse for (StatusCode, T) {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        let (status, value) = self;
+        let mut response = value.into_response()?;
+        *response.status_mut() = status;
+        Ok(response)
+    }
+}
+
+/// A response with any serializable value as JSON body
+///
+/// E.g. `Ok((StatusCode::CREATED, Json(user)))`.
+#[derive(Debug, Clone)]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        with_content_type(serde_json::to_string(&self.0)?, "application/json")
+    }
+}
+
+/// A response with JSON body and the status code
+///
+/// ```
+/// use http::StatusCode;
+/// use kinetics::tools::http::{json_response, Body};
+///
+/// let response = json_response(serde_json::json!({"id": 1}), StatusCode::CREATED);
+///
+/// assert_eq!(response.status(), StatusCode::CREATED);
+/// assert_eq!(response.headers()["content-type"], "application/json");
+/// assert_eq!(response.body(), &Body::Text(r#"{"id":1}"#.into()));
+/// ```
+pub fn json_response(value: serde_json::Value, status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::Text(value.to_string()));
+    *response.status_mut() = status;
+
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+
+    response
+}
+
+/// A response with `{"errors": [message]}` JSON body, the format of kinetics' own errors
+///
+/// ```
+/// use http::StatusCode;
+/// use kinetics::tools::http::{error_response, Body};
+///
+/// let response = error_response("Order not found", StatusCode::NOT_FOUND);
+///
+/// assert_eq!(response.status(), StatusCode::NOT_FOUND);
+/// assert_eq!(response.headers()["content-type"], "application/json");
+/// assert_eq!(
+///     response.body(),
+///     &Body::Text(r#"{"errors":["Order not found"]}"#.into())
+/// );
+/// ```
+pub fn error_response(message: impl Into<String>, status: StatusCode) -> Response<Body> {
+    json_response(serde_json::json!({"errors": [message.into()]}), status)
+}
+
+fn with_content_type(
+    body: impl Into<Body>,
+    content_type: &'static str,
+) -> Result<Response<Body>, BoxError> {
+    Ok(Response::builder()
+        .header(http::header::CONTENT_TYPE, content_type)
+        .body(body.into())?)
+}
 
 /// Request body supported by kinetics runtime.
 /// In addition to empty bodies string and binary data are supported.
@@ -14,6 +149,201 @@ pub enum Body {
     Binary(Vec<u8>),
 }
 
+impl Body {
+    /// Decode a base64-encoded payload into a binary body
+    ///
+    /// The same way function URLs pass non-text payloads, e.g. images.
+    pub fn from_base64(encoded: &str) -> eyre::Result<Self> {
+        Ok(Body::Binary(BASE64_STANDARD.decode(encoded)?))
+    }
+}
+
+/// Error of reading typed data from a request, responded with 400 Bad Request
+///
+/// Return it as the error of an endpoint, or convert it with `into_response()`
+/// when the endpoint has a different error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadRequest(pub String);
+
+impl std::fmt::Display for BadRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for BadRequest {}
+
+/// Responds with `{"errors": [message]}`, the same as the JSON schema check does
+impl IntoResponse for BadRequest {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        Ok(error_response(self.0, StatusCode::BAD_REQUEST))
+    }
+}
+
+/// Deserialize the JSON body of a request
+///
+/// Works with text and binary bodies alike, so it doesn't matter how the caller
+/// encoded the payload. An empty or malformed body is a [BadRequest].
+///
+/// ```
+/// use kinetics::tools::http::{body_json, BadRequest, Body};
+///
+/// #[derive(serde::Deserialize)]
+/// struct Order {
+///     id: u32,
+/// }
+///
+/// let request = http::Request::new(Body::Text(r#"{"id": 1}"#.into()));
+/// assert_eq!(body_json::<Order, _>(&request).unwrap().id, 1);
+///
+/// let request = http::Request::new(Body::Binary(br#"{"id": 2}"#.to_vec()));
+/// assert_eq!(body_json::<Order, _>(&request).unwrap().id, 2);
+///
+/// let request = http::Request::new(Body::Empty);
+/// assert_eq!(
+///     body_json::<Order, _>(&request).err(),
+///     Some(BadRequest("Request body is empty".into()))
+/// );
+///
+/// let request = http::Request::new(Body::Text("{".into()));
+/// assert!(body_json::<Order, _>(&request).is_err());
+/// ```
+pub fn body_json<T: DeserializeOwned, B: AsRef<[u8]>>(
+    request: &http::Request<B>,
+) -> Result<T, BadRequest> {
+    let body = request.body().as_ref();
+
+    if body.is_empty() {
+        return Err(BadRequest("Request body is empty".into()));
+    }
+
+    serde_json::from_slice(body).map_err(|e| BadRequest(format!("Invalid JSON: {e}")))
+}
+
+/// Value of a parameter in the query string of a request, percent-decoded and parsed
+///
+/// The first value is returned if the parameter is repeated. `None` if the parameter
+/// is missing or can't be parsed into `T`.
+///
+/// ```
+/// use kinetics::tools::http::{query_param, Body};
+///
+/// let request = http::Request::builder()
+///     .uri("/orders?status=new&q=a%20b&status=paid&page=2")
+///     .body(Body::Empty)
+///     .unwrap();
+///
+/// assert_eq!(query_param::<String>(&request, "q").as_deref(), Some("a b"));
+/// assert_eq!(query_param::<String>(&request, "status").as_deref(), Some("new"));
+/// assert_eq!(query_param::<u32>(&request, "page"), Some(2));
+/// assert_eq!(query_param::<u32>(&request, "limit"), None);
+/// ```
+pub fn query_param<T: FromStr>(request: &http::Request<impl Sized>, name: &str) -> Option<T> {
+    url::form_urlencoded::parse(request.uri().query()?.as_bytes())
+        .find(|(key, _)| key == name)
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+/// ID of the request from the header, or a new UUID if the request has none
+///
+/// Used by endpoints with `request_id` enabled, to correlate logs across services.
+pub fn request_id(headers: &http::HeaderMap, name: &str) -> String {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Max size of a response returned by a function URL
+pub const MAX_RESPONSE_SIZE: usize = 6 * 1024 * 1024;
+
+/// Replace a response exceeding [MAX_RESPONSE_SIZE] with a 500 error
+///
+/// Otherwise the runtime fails to return the response, and the caller gets an opaque error.
+/// The size is estimated as the body, base64-encoded if binary, plus the headers.
+pub fn limit_response_size(response: Response<lambda_http::Body>) -> Response<lambda_http::Body> {
+    let body_size = match response.body() {
+        lambda_http::Body::Binary(bytes) => bytes.len().div_ceil(3) * 4,
+        body => body.len(),
+    };
+
+    let headers_size = response
+        .headers()
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len())
+        .sum::<usize>();
+
+    let size = body_size + headers_size;
+
+    if size <= MAX_RESPONSE_SIZE {
+        return response;
+    }
+
+    eprintln!(
+        "Response of {size} bytes exceeds the function URL limit of {MAX_RESPONSE_SIZE} bytes, \
+        returning 500 instead. Paginate the results, or store large payloads in S3 and return a link."
+    );
+
+    let body = serde_json::json!({
+        "errors": [format!("Response is too large: {size} bytes, max {MAX_RESPONSE_SIZE}")],
+    });
+
+    let mut response = Response::new(lambda_http::Body::Text(body.to_string()));
+    *response.status_mut() = http::StatusCode::INTERNAL_SERVER_ERROR;
+
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+
+    response
+}
+
+/// Env var with the path to record the response of an endpoint run by `kinetics serve`
+#[doc(hidden)]
+pub const LOCAL_RESPONSE_FILE_ENV: &str = "KINETICS_INVOKE_RESPONSE_FILE";
+
+/// Response of an endpoint run locally, passed back to the local server
+#[doc(hidden)]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+
+    /// Base64-encoded, as the body might be binary
+    pub body: String,
+}
+
+impl LocalResponse {
+    /// Record the response to the file, if the endpoint is run by the local server
+    pub fn write<B: AsRef<[u8]>>(response: &Response<B>) -> eyre::Result<()> {
+        let Ok(path) = std::env::var(LOCAL_RESPONSE_FILE_ENV) else {
+            return Ok(());
+        };
+
+        let local_response = LocalResponse {
+            status: response.status().as_u16(),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+            body: BASE64_STANDARD.encode(response.body()),
+        };
+
+        std::fs::write(path, serde_json::to_vec(&local_response)?)?;
+        Ok(())
+    }
+}
+
 impl From<lambda_http::Body> for Body {
     fn from(value: lambda_http::Body) -> Self {
         match value {
@@ -125,9 +455,20 @@ impl<'a> From<&'a [u8]> for Body {
     }
 }
 
+impl AsRef<[u8]> for Body {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Body::Empty => &[],
+            Body::Text(ref bytes) => bytes.as_ref(),
+            Body::Binary(ref bytes) => bytes.as_ref(),
+        }
+    }
+}
+
 impl HttpBody for Body {
     type Data = bytes::Bytes;
-    type Error = tower::BoxError;
+    type Error = BoxError;
 
     fn is_end_stream(&self) -> bool {
         matches!(self, Body::Empty)
@@ -153,3 +494,220 @@ impl HttpBody for Body {
         })
     }
 }
+
+/// Response body of endpoints with `streaming = true`
+///
+/// Chunks written to the sender are sent to the caller right away, e.g. tokens generated by
+/// an LLM. The response ends when the sender is dropped.
+///
+/// ```no_run
+/// use kinetics::tools::http::StreamBody;
+///
+/// # async fn example() -> http::Response<StreamBody> {
+/// let (sender, body) = StreamBody::channel(16);
+///
+/// tokio::spawn(async move {
+///     for token in ["Hello", ", ", "world"] {
+///         if sender.send(token.into()).await.is_err() {
+///             // The caller has gone away
+///             break;
+///         }
+///     }
+/// });
+///
+/// http::Response::new(body)
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StreamBody {
+    inner: StreamInner,
+}
+
+#[derive(Debug)]
+enum StreamInner {
+    /// Sent at once, e.g. an error response
+    Full(Body),
+
+    Channel(mpsc::Receiver<Bytes>),
+}
+
+impl StreamBody {
+    /// Create a body streaming the chunks sent to the returned sender
+    ///
+    /// The sender waits when `buffer` chunks are pending, so that a slow caller
+    /// does not make the function hold the whole response in memory.
+    pub fn channel(buffer: usize) -> (mpsc::Sender<Bytes>, Self) {
+        let (sender, receiver) = mpsc::channel(buffer);
+
+        let body = StreamBody {
+            inner: StreamInner::Channel(receiver),
+        };
+
+        (sender, body)
+    }
+
+    /// Wait for the next chunk of the body, `None` when the body has ended
+    pub async fn chunk(&mut self) -> Option<Result<Bytes, BoxError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_frame(cx))
+            .await
+            .map(|frame| frame.map(|frame| frame.into_data().unwrap_or_default()))
+    }
+}
+
+impl From<Body> for StreamBody {
+    fn from(body: Body) -> Self {
+        StreamBody {
+            inner: StreamInner::Full(body),
+        }
+    }
+}
+
+impl From<String> for StreamBody {
+    fn from(s: String) -> Self {
+        StreamBody::from(Body::from(s))
+    }
+}
+
+impl<'a> From<&'a str> for StreamBody {
+    fn from(s: &'a str) -> Self {
+        StreamBody::from(Body::from(s))
+    }
+}
+
+impl From<Vec<u8>> for StreamBody {
+    fn from(b: Vec<u8>) -> Self {
+        StreamBody::from(Body::from(b))
+    }
+}
+
+impl HttpBody for StreamBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            StreamInner::Full(body) => body.is_end_stream(),
+            StreamInner::Channel(receiver) => receiver.is_closed() && receiver.is_empty(),
+        }
+    }
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match &mut self.get_mut().inner {
+            StreamInner::Full(body) => Pin::new(body).poll_frame(cx),
+            StreamInner::Channel(receiver) => receiver
+                .poll_recv(cx)
+                .map(|chunk| chunk.map(|chunk| Ok(Frame::data(chunk)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Order {
+        id: u32,
+    }
+
+    #[test]
+    fn decodes_base64_body() {
+        // Not a valid UTF-8 sequence, kept as bytes
+        assert_eq!(
+            Body::from_base64("AJ+Slv8=").unwrap(),
+            Body::Binary(vec![0, 159, 146, 150, 255])
+        );
+
+        assert_eq!(Body::from_base64("").unwrap(), Body::Binary(vec![]));
+        assert!(Body::from_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn reads_json_of_text_and_binary_bodies() {
+        let request = http::Request::new(Body::Text(r#"{"id": 1}"#.into()));
+        assert_eq!(body_json::<Order, _>(&request), Ok(Order { id: 1 }));
+
+        let request = http::Request::new(Body::Binary(br#"{"id": 2}"#.to_vec()));
+        assert_eq!(body_json::<Order, _>(&request), Ok(Order { id: 2 }));
+    }
+
+    #[test]
+    fn rejects_empty_and_malformed_bodies() {
+        let request = http::Request::new(Body::Empty);
+        let error = body_json::<Order, _>(&request).unwrap_err();
+        assert_eq!(error, BadRequest("Request body is empty".into()));
+
+        for body in ["{", r#"{"id": "1"}"#, "[]"] {
+            let request = http::Request::new(Body::Text(body.into()));
+            let error = body_json::<Order, _>(&request).unwrap_err();
+            assert!(error.0.starts_with("Invalid JSON"), "{body}: {error}");
+        }
+
+        // Returned from an endpoint, the error responds with 400
+        let response = BadRequest("Request body is empty".into())
+            .into_response()
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    fn request(uri: &str) -> http::Request<Body> {
+        http::Request::builder().uri(uri).body(Body::Empty).unwrap()
+    }
+
+    #[test]
+    fn query_param_missing() {
+        for uri in ["/orders", "/orders?", "/orders?size=1", "/orders?pages=1"] {
+            assert_eq!(query_param(&request(uri), "page"), None::<String>, "{uri}");
+        }
+    }
+
+    #[test]
+    fn query_param_repeated() {
+        let request = request("/orders?status=new&status=paid");
+        assert_eq!(query_param(&request, "status"), Some("new".to_string()));
+    }
+
+    #[test]
+    fn query_param_url_encoded() {
+        let request = request("/orders?q=a%20b%26c&name=J%C3%B6rg&tag=a+b");
+        assert_eq!(query_param(&request, "q"), Some("a b&c".to_string()));
+        assert_eq!(query_param(&request, "name"), Some("Jörg".to_string()));
+        assert_eq!(query_param(&request, "tag"), Some("a b".to_string()));
+    }
+
+    #[test]
+    fn query_param_typed() {
+        let request = request("/orders?page=2&limit=ten&paid=true");
+        assert_eq!(query_param::<u32>(&request, "page"), Some(2));
+        assert_eq!(query_param::<bool>(&request, "paid"), Some(true));
+
+        // A value of another type is the same as a missing one
+        assert_eq!(query_param::<u32>(&request, "limit"), None);
+    }
+
+    #[test]
+    fn json_response_has_body_and_headers() {
+        let response = json_response(serde_json::json!({"id": 1}), StatusCode::CREATED);
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()["content-type"], "application/json");
+        assert_eq!(response.body(), &Body::Text(r#"{"id":1}"#.into()));
+    }
+
+    #[test]
+    fn error_response_has_body_and_headers() {
+        let response = error_response("Order not found", StatusCode::NOT_FOUND);
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["content-type"], "application/json");
+
+        assert_eq!(
+            response.body(),
+            &Body::Text(r#"{"errors":["Order not found"]}"#.into())
+        );
+    }
+}
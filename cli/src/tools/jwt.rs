@@ -0,0 +1,333 @@
+use eyre::{eyre, OptionExt, WrapErr};
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+/// Algorithms accepted in the token header, the rest are rejected before the key is picked
+const ALGORITHMS: [Algorithm; 2] = [Algorithm::RS256, Algorithm::ES256];
+
+/// Min interval between JWKS fetches, so that tokens with made up key ids can't flood the issuer
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+struct Jwks {
+    // Keys are parsed one by one, so that a key of an unsupported type doesn't break the rest
+    keys: Vec<Value>,
+}
+
+impl Jwks {
+    fn into_keys(self) -> Vec<Jwk> {
+        self.keys
+            .into_iter()
+            .filter_map(|key| serde_json::from_value(key).ok())
+            .collect()
+    }
+}
+
+/// Keys of the issuer, with the time they have been fetched at
+struct Keys {
+    keys: Vec<Jwk>,
+    fetched_at: Instant,
+}
+
+#[derive(Debug, Deserialize)]
+struct Discovery {
+    jwks_uri: String,
+}
+
+/// Verifies Bearer JWTs of endpoint requests
+///
+/// The JWKS of the issuer is fetched at cold start, and refetched when a token is signed
+/// by an unknown key, e.g. after the issuer rotated the keys. RS256 and ES256 are supported.
+pub struct Verifier {
+    issuer: String,
+    audience: Option<String>,
+
+    /// Tolerated clock skew in seconds
+    leeway: u64,
+
+    jwks_uri: String,
+    keys: RwLock<Keys>,
+}
+
+impl Verifier {
+    /// Discover the JWKS of the issuer and fetch the keys
+    pub async fn new(issuer: &str, audience: Option<&str>, leeway: u64) -> eyre::Result<Self> {
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        );
+
+        let discovery: Discovery = Self::fetch(&discovery_url)
+            .await
+            .wrap_err("Failed to discover the JWKS of the issuer")?;
+
+        let keys = Self::fetch::<Jwks>(&discovery.jwks_uri)
+            .await
+            .wrap_err("Failed to fetch the JWKS of the issuer")?
+            .into_keys();
+
+        Ok(Self::with_keys(
+            issuer,
+            audience,
+            leeway,
+            discovery.jwks_uri,
+            keys,
+        ))
+    }
+
+    fn with_keys(
+        issuer: &str,
+        audience: Option<&str>,
+        leeway: u64,
+        jwks_uri: String,
+        keys: Vec<Jwk>,
+    ) -> Self {
+        Verifier {
+            issuer: issuer.to_owned(),
+            audience: audience.map(str::to_owned),
+            leeway,
+            jwks_uri,
+            keys: RwLock::new(Keys {
+                keys,
+                fetched_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Verify the token from `Authorization: Bearer <token>` header and return its claims
+    pub async fn verify(&self, headers: &http::HeaderMap) -> eyre::Result<Value> {
+        let token = headers
+            .get(http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_eyre("No Bearer token in Authorization header")?
+            .trim();
+
+        let header = jsonwebtoken::decode_header(token).wrap_err("Malformed token header")?;
+
+        if !ALGORITHMS.contains(&header.alg) {
+            return Err(eyre!("Unsupported token algorithm {:?}", header.alg));
+        }
+
+        let key = match self.key(header.kid.as_deref()).await {
+            Some(key) => key,
+            None => {
+                // The issuer might have rotated the keys
+                self.refresh().await?;
+
+                self.key(header.kid.as_deref())
+                    .await
+                    .ok_or_eyre("Token is signed by an unknown key")?
+            }
+        };
+
+        let key = DecodingKey::from_jwk(&key).wrap_err("Invalid key in the JWKS")?;
+
+        // The algorithm of the header must also match the type of the key,
+        // so that a token can't pick a weaker one, e.g. HS256 with the public key as a secret
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = self.leeway;
+        validation.validate_nbf = true;
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_required_spec_claims(&["exp", "iss"]);
+
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = jsonwebtoken::decode::<Value>(token, &key, &validation)
+            .map_err(|error| eyre!("Invalid token: {error}"))?
+            .claims;
+
+        Ok(claims)
+    }
+
+    /// Find the key by id, a token without id is only accepted if the JWKS has a single key
+    async fn key(&self, kid: Option<&str>) -> Option<Jwk> {
+        let keys = &self.keys.read().await.keys;
+
+        match kid {
+            Some(kid) => keys
+                .iter()
+                .find(|key| key.common.key_id.as_deref() == Some(kid))
+                .cloned(),
+            None if keys.len() == 1 => keys.first().cloned(),
+            None => None,
+        }
+    }
+
+    /// Refetch the keys, unless they have been fetched recently
+    async fn refresh(&self) -> eyre::Result<()> {
+        let mut keys = self.keys.write().await;
+
+        if keys.fetched_at.elapsed() < REFRESH_INTERVAL {
+            return Ok(());
+        }
+
+        let jwks: Jwks = Self::fetch(&self.jwks_uri)
+            .await
+            .wrap_err("Failed to fetch the JWKS of the issuer")?;
+
+        *keys = Keys {
+            keys: jwks.into_keys(),
+            fetched_at: Instant::now(),
+        };
+
+        Ok(())
+    }
+
+    async fn fetch<T: for<'de> Deserialize<'de>>(url: &str) -> eyre::Result<T> {
+        let text = reqwest::get(url).await?.error_for_status()?.text().await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::prelude::{Engine as _, BASE64_URL_SAFE_NO_PAD};
+    use jsonwebtoken::{EncodingKey, Header};
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use serde_json::json;
+
+    const ISSUER: &str = "https://issuer.example.com";
+    const AUDIENCE: &str = "api";
+    const KID: &str = "key-1";
+
+    /// A P-256 key pair, with the public part as a JWK
+    fn key_pair() -> (EncodingKey, Jwk) {
+        let pkcs8 =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                .unwrap();
+
+        let pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            pkcs8.as_ref(),
+            &SystemRandom::new(),
+        )
+        .unwrap();
+
+        // Uncompressed point: 0x04 || x || y
+        let (x, y) = pair.public_key().as_ref()[1..].split_at(32);
+
+        let jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "kid": KID,
+            "x": BASE64_URL_SAFE_NO_PAD.encode(x),
+            "y": BASE64_URL_SAFE_NO_PAD.encode(y),
+        }))
+        .unwrap();
+
+        (EncodingKey::from_ec_der(pkcs8.as_ref()), jwk)
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": ISSUER,
+            "aud": AUDIENCE,
+            "sub": "user",
+            "exp": chrono::Utc::now().timestamp() + 300,
+        })
+    }
+
+    fn sign(key: &EncodingKey, kid: &str, claims: &Value) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(kid.to_owned());
+        jsonwebtoken::encode(&header, claims, key).unwrap()
+    }
+
+    fn verifier(jwk: Jwk) -> Verifier {
+        // Keys are fetched just now, so an unknown key id doesn't trigger a refetch
+        Verifier::with_keys(
+            ISSUER,
+            Some(AUDIENCE),
+            0,
+            "http://127.0.0.1:9/jwks".to_owned(),
+            vec![jwk],
+        )
+    }
+
+    async fn verify(verifier: &Verifier, token: &str) -> eyre::Result<Value> {
+        let mut headers = http::HeaderMap::new();
+
+        headers.insert(
+            http::header::AUTHORIZATION,
+            format!("Bearer {token}").parse().unwrap(),
+        );
+
+        verifier.verify(&headers).await
+    }
+
+    #[tokio::test]
+    async fn accepts_valid_token() {
+        let (key, jwk) = key_pair();
+        let token = sign(&key, KID, &claims());
+        let claims = verify(&verifier(jwk), &token).await.unwrap();
+        assert_eq!(claims["sub"], "user");
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_signature() {
+        let (_, jwk) = key_pair();
+        let (other_key, _) = key_pair();
+        let token = sign(&other_key, KID, &claims());
+        assert!(verify(&verifier(jwk), &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_alg() {
+        let (_, jwk) = key_pair();
+
+        // Signed with HS256, using the public key of the issuer as the secret
+        let secret = serde_json::to_vec(&jwk).unwrap();
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some(KID.to_owned());
+
+        let token =
+            jsonwebtoken::encode(&header, &claims(), &EncodingKey::from_secret(&secret)).unwrap();
+
+        assert!(verify(&verifier(jwk), &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_issuer() {
+        let (key, jwk) = key_pair();
+        let mut claims = claims();
+        claims["iss"] = json!("https://other.example.com");
+        let token = sign(&key, KID, &claims);
+        assert!(verify(&verifier(jwk), &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_wrong_audience() {
+        let (key, jwk) = key_pair();
+        let mut claims = claims();
+        claims["aud"] = json!(["other"]);
+        let token = sign(&key, KID, &claims);
+        assert!(verify(&verifier(jwk), &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_expired_token() {
+        let (key, jwk) = key_pair();
+        let mut claims = claims();
+        claims["exp"] = json!(chrono::Utc::now().timestamp() - 60);
+        let token = sign(&key, KID, &claims);
+        assert!(verify(&verifier(jwk), &token).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_kid() {
+        let (key, jwk) = key_pair();
+        let token = sign(&key, "key-2", &claims());
+        let error = verify(&verifier(jwk), &token).await.unwrap_err();
+        assert!(error.to_string().contains("unknown key"));
+    }
+}
@@ -0,0 +1,282 @@
+use aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder;
+use aws_sdk_dynamodb::primitives::Blob;
+use aws_sdk_dynamodb::types::AttributeValue;
+use base64::prelude::{Engine as _, BASE64_STANDARD, BASE64_URL_SAFE_NO_PAD};
+use eyre::{eyre, WrapErr};
+use ring::{aead, hkdf, rand::SecureRandom as _, rand::SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// An item of a KV DB table
+pub type Item = HashMap<String, AttributeValue>;
+
+/// Table and index keys have at most two attributes each
+const MAX_KEY_ATTRIBUTES: usize = 4;
+
+/// Context of the cursor key derived from the secret, so that it differs from other uses of it
+const CURSOR_KEY_INFO: &[u8] = b"kinetics kvdb cursor";
+
+/// A page of query results
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub items: Vec<Item>,
+
+    /// Opaque token of the next page, `None` for the last one
+    ///
+    /// Return it to the caller as is, and pass back to [query_page] to fetch the next page.
+    /// The token is encrypted, so a client can neither read the key of the last item nor change it.
+    pub cursor: Option<String>,
+}
+
+/// Key attribute value, only scalar types are allowed in keys
+#[derive(Debug, Serialize, Deserialize)]
+enum KeyValue {
+    S(String),
+    N(String),
+
+    /// Base64-encoded bytes
+    B(String),
+}
+
+/// Query all items, following the pages until the last one
+///
+/// ```no_run
+/// # async fn example(client: aws_sdk_dynamodb::Client) -> eyre::Result<()> {
+/// use aws_sdk_dynamodb::types::AttributeValue;
+///
+/// let items = kinetics::tools::kvdb::query_all(
+///     client
+///         .query()
+///         .table_name("orders")
+///         .key_condition_expression("customer = :customer")
+///         .expression_attribute_values(":customer", AttributeValue::S("42".into())),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn query_all(query: QueryFluentBuilder) -> eyre::Result<Vec<Item>> {
+    query
+        .into_paginator()
+        .items()
+        .send()
+        .try_collect()
+        .await
+        .wrap_err("Failed to query the table")
+}
+
+/// Query a single page of at most `limit` items, starting after the `cursor`
+///
+/// The cursor of the first page is `None`. The cursor of a following page is taken from
+/// the previous [Page]; an invalid one, e.g. tampered by a client, results in an error.
+/// Cursors are encrypted with a key derived from the `secret`, e.g. one of the project's secrets,
+/// so that clients can neither read nor make up a start key. Changing the secret invalidates
+/// the cursors issued before.
+pub async fn query_page(
+    query: QueryFluentBuilder,
+    limit: i32,
+    cursor: Option<&str>,
+    secret: &str,
+) -> eyre::Result<Page> {
+    let key = cursor_key(secret);
+    let start_key = cursor
+        .map(|cursor| decode_cursor(cursor, &key))
+        .transpose()?;
+
+    let output = query
+        .limit(limit)
+        .set_exclusive_start_key(start_key)
+        .send()
+        .await
+        .wrap_err("Failed to query the table")?;
+
+    let cursor = output
+        .last_evaluated_key()
+        .map(|last_key| encode_cursor(last_key, &key))
+        .transpose()?;
+
+    Ok(Page {
+        items: output.items.unwrap_or_default(),
+        cursor,
+    })
+}
+
+/// Derive the AES-256-GCM key of cursors from the secret
+fn cursor_key(secret: &str) -> aead::LessSafeKey {
+    let key: aead::UnboundKey = hkdf::Salt::new(hkdf::HKDF_SHA256, &[])
+        .extract(secret.as_bytes())
+        .expand(&[CURSOR_KEY_INFO], &aead::AES_256_GCM)
+        .expect("AES-256 key length is a valid HKDF output length")
+        .into();
+
+    aead::LessSafeKey::new(key)
+}
+
+/// Serialize the last evaluated key into an encrypted URL-safe token, base64 of nonce and ciphertext
+fn encode_cursor(key: &Item, secret: &aead::LessSafeKey) -> eyre::Result<String> {
+    let key = key
+        .iter()
+        .map(|(name, value)| {
+            let value = match value {
+                AttributeValue::S(value) => KeyValue::S(value.clone()),
+                AttributeValue::N(value) => KeyValue::N(value.clone()),
+                AttributeValue::B(value) => KeyValue::B(BASE64_STANDARD.encode(value.as_ref())),
+                _ => return Err(eyre!("Unsupported type of key attribute {name}")),
+            };
+
+            Ok((name.clone(), value))
+        })
+        .collect::<eyre::Result<BTreeMap<_, _>>>()?;
+
+    // A random nonce for each cursor, the same key encrypts all cursors of the secret
+    let mut nonce = [0; aead::NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| eyre!("Failed to generate a cursor nonce"))?;
+
+    let mut payload = serde_json::to_vec(&key)?;
+
+    secret
+        .seal_in_place_append_tag(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::empty(),
+            &mut payload,
+        )
+        .map_err(|_| eyre!("Failed to encrypt the cursor"))?;
+
+    Ok(BASE64_URL_SAFE_NO_PAD.encode([nonce.as_slice(), &payload].concat()))
+}
+
+/// Decrypt the token created by [encode_cursor] and parse it back into the exclusive start key
+fn decode_cursor(cursor: &str, secret: &aead::LessSafeKey) -> eyre::Result<Item> {
+    let mut token = BASE64_URL_SAFE_NO_PAD
+        .decode(cursor)
+        .map_err(|_| eyre!("Invalid cursor"))?;
+
+    if token.len() < aead::NONCE_LEN + secret.algorithm().tag_len() {
+        return Err(eyre!("Invalid cursor"));
+    }
+
+    let (nonce, payload) = token.split_at_mut(aead::NONCE_LEN);
+    let nonce =
+        aead::Nonce::try_assume_unique_for_key(nonce).map_err(|_| eyre!("Invalid cursor"))?;
+
+    // Fails on a changed token or one encrypted with another secret
+    let json = secret
+        .open_in_place(nonce, aead::Aad::empty(), payload)
+        .map_err(|_| eyre!("Invalid cursor"))?;
+
+    let key: BTreeMap<String, KeyValue> =
+        serde_json::from_slice(json).map_err(|_| eyre!("Invalid cursor"))?;
+
+    if key.is_empty() || key.len() > MAX_KEY_ATTRIBUTES {
+        return Err(eyre!("Invalid cursor"));
+    }
+
+    key.into_iter()
+        .map(|(name, value)| {
+            let value = match value {
+                KeyValue::S(value) => AttributeValue::S(value),
+                KeyValue::N(value) if value.parse::<f64>().is_ok() => AttributeValue::N(value),
+                KeyValue::B(value) => AttributeValue::B(Blob::new(
+                    BASE64_STANDARD
+                        .decode(value)
+                        .map_err(|_| eyre!("Invalid cursor"))?,
+                )),
+                KeyValue::N(_) => return Err(eyre!("Invalid cursor")),
+            };
+
+            Ok((name, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> Item {
+        HashMap::from([
+            ("customer".to_string(), AttributeValue::S("42".into())),
+            (
+                "created".to_string(),
+                AttributeValue::N("1700000000".into()),
+            ),
+            (
+                "hash".to_string(),
+                AttributeValue::B(Blob::new(vec![0, 1, 255])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn round_trip() {
+        let cursor = encode_cursor(&key(), &cursor_key("secret")).unwrap();
+        assert_eq!(
+            decode_cursor(&cursor, &cursor_key("secret")).unwrap(),
+            key()
+        );
+    }
+
+    #[test]
+    fn hides_key() {
+        let cursor = encode_cursor(&key(), &cursor_key("secret")).unwrap();
+        let token = BASE64_URL_SAFE_NO_PAD.decode(&cursor).unwrap();
+
+        for plaintext in ["customer", "1700000000"] {
+            assert!(
+                !token
+                    .windows(plaintext.len())
+                    .any(|window| window == plaintext.as_bytes()),
+                "{plaintext}"
+            );
+        }
+
+        // Each cursor gets its own nonce
+        assert_ne!(
+            cursor,
+            encode_cursor(&key(), &cursor_key("secret")).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_cursor() {
+        let cursor = encode_cursor(&key(), &cursor_key("secret")).unwrap();
+        let token = BASE64_URL_SAFE_NO_PAD.decode(&cursor).unwrap();
+
+        // Flipping a bit anywhere, nonce, ciphertext or tag, fails the authentication
+        for i in [0, aead::NONCE_LEN, token.len() - 1] {
+            let mut tampered = token.clone();
+            tampered[i] ^= 1;
+
+            let tampered = BASE64_URL_SAFE_NO_PAD.encode(tampered);
+            assert!(
+                decode_cursor(&tampered, &cursor_key("secret")).is_err(),
+                "{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_other_secret() {
+        let cursor = encode_cursor(&key(), &cursor_key("secret")).unwrap();
+        assert!(decode_cursor(&cursor, &cursor_key("other")).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_cursor() {
+        let short = BASE64_URL_SAFE_NO_PAD.encode([0; aead::NONCE_LEN]);
+
+        for cursor in ["", ".", "garbage", "not base64!!!", &short] {
+            assert!(
+                decode_cursor(cursor, &cursor_key("secret")).is_err(),
+                "{cursor}"
+            );
+        }
+
+        // Signed cursor of the previous format
+        let payload = BASE64_URL_SAFE_NO_PAD.encode(r#"{"customer":{"S":"42"}}"#);
+        let signed = format!("{payload}.{}", BASE64_URL_SAFE_NO_PAD.encode([0; 32]));
+        assert!(decode_cursor(&signed, &cursor_key("secret")).is_err());
+    }
+}
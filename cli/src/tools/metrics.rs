@@ -0,0 +1,232 @@
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Unit of a metric value, as supported by CloudWatch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Unit {
+    Seconds,
+    Microseconds,
+    Milliseconds,
+    Bytes,
+    Kilobytes,
+    Megabytes,
+    Gigabytes,
+    Terabytes,
+    Bits,
+    Kilobits,
+    Megabits,
+    Gigabits,
+    Terabits,
+    Percent,
+    Count,
+    #[serde(rename = "Bytes/Second")]
+    BytesPerSecond,
+    #[serde(rename = "Kilobytes/Second")]
+    KilobytesPerSecond,
+    #[serde(rename = "Megabytes/Second")]
+    MegabytesPerSecond,
+    #[serde(rename = "Gigabytes/Second")]
+    GigabytesPerSecond,
+    #[serde(rename = "Terabytes/Second")]
+    TerabytesPerSecond,
+    #[serde(rename = "Bits/Second")]
+    BitsPerSecond,
+    #[serde(rename = "Kilobits/Second")]
+    KilobitsPerSecond,
+    #[serde(rename = "Megabits/Second")]
+    MegabitsPerSecond,
+    #[serde(rename = "Gigabits/Second")]
+    GigabitsPerSecond,
+    #[serde(rename = "Terabits/Second")]
+    TerabitsPerSecond,
+    #[serde(rename = "Count/Second")]
+    CountPerSecond,
+    None,
+}
+
+#[derive(Debug, Clone)]
+struct Metric {
+    name: String,
+    value: f64,
+    unit: Unit,
+}
+
+/// Custom CloudWatch metrics
+///
+/// Printed to stdout in [Embedded Metric Format](https://docs.aws.amazon.com/AmazonCloudWatch/latest/monitoring/CloudWatch_Embedded_Metric_Format_Specification.html),
+/// CloudWatch extracts the metrics from the function's logs, so no additional permissions are needed.
+/// The metrics appear in CloudWatch under the namespace, with the dimensions.
+///
+/// All metrics added to the builder are emitted in a single log line:
+///
+/// ```no_run
+/// use kinetics::tools::metrics::{Metrics, Unit};
+///
+/// Metrics::new("Shop")
+///     .dimension("Service", "checkout")
+///     .metric("OrdersProcessed", 1.0, Unit::Count)
+///     .metric("OrderValue", 49.9, Unit::None)
+///     .emit();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    namespace: String,
+    dimensions: Vec<(String, String)>,
+    metrics: Vec<Metric>,
+}
+
+impl Metrics {
+    pub fn new(namespace: &str) -> Self {
+        Metrics {
+            namespace: namespace.to_owned(),
+            dimensions: Vec::new(),
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Add a dimension, applies to all metrics of the builder
+    ///
+    /// Panics if a metric or another dimension has the same name.
+    pub fn dimension(mut self, name: &str, value: &str) -> Self {
+        self.assert_unique(name);
+        self.dimensions.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Add a metric
+    ///
+    /// Panics if a dimension or another metric has the same name.
+    pub fn metric(mut self, name: &str, value: f64, unit: Unit) -> Self {
+        self.assert_unique(name);
+        self.metrics.push(Metric {
+            name: name.to_owned(),
+            value,
+            unit,
+        });
+
+        self
+    }
+
+    /// Both are root members of the EMF object, so one would overwrite the other
+    fn assert_unique(&self, name: &str) {
+        let is_taken = name == "_aws"
+            || self
+                .dimensions
+                .iter()
+                .any(|(dimension, _)| dimension == name)
+            || self.metrics.iter().any(|metric| metric.name == name);
+
+        assert!(!is_taken, "Metric or dimension {name} is already defined");
+    }
+
+    /// Serialize the metrics to EMF JSON
+    ///
+    /// The dimensions and the values are stored as root members of the object,
+    /// referenced by the metadata in the `_aws` member.
+    pub fn to_emf(&self) -> Value {
+        let mut root = Map::new();
+
+        root.insert(
+            "_aws".into(),
+            json!({
+                "Timestamp": Utc::now().timestamp_millis(),
+                "CloudWatchMetrics": [{
+                    "Namespace": self.namespace,
+                    "Dimensions": [self.dimensions.iter().map(|(name, _)| name).collect::<Vec<_>>()],
+                    "Metrics": self.metrics
+                        .iter()
+                        .map(|metric| json!({"Name": metric.name, "Unit": metric.unit}))
+                        .collect::<Vec<_>>(),
+                }],
+            }),
+        );
+
+        for (name, value) in &self.dimensions {
+            root.insert(name.clone(), json!(value));
+        }
+
+        for metric in &self.metrics {
+            root.insert(metric.name.clone(), json!(metric.value));
+        }
+
+        Value::Object(root)
+    }
+
+    /// Print the metrics to stdout, to be ingested by CloudWatch
+    pub fn emit(&self) {
+        if self.metrics.is_empty() {
+            return;
+        }
+
+        println!("{}", self.to_emf());
+    }
+}
+
+/// Emit a single metric
+///
+/// A shortcut for [Metrics] with one metric.
+pub fn metric(namespace: &str, name: &str, value: f64, unit: Unit, dimensions: &[(&str, &str)]) {
+    dimensions
+        .iter()
+        .fold(Metrics::new(namespace), |metrics, (name, value)| {
+            metrics.dimension(name, value)
+        })
+        .metric(name, value, unit)
+        .emit();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_to_emf() {
+        let emf = Metrics::new("Shop")
+            .dimension("Service", "checkout")
+            .metric("OrdersProcessed", 1.0, Unit::Count)
+            .metric("Throughput", 2.5, Unit::BytesPerSecond)
+            .to_emf();
+
+        assert!(emf["_aws"]["Timestamp"].is_i64());
+
+        assert_eq!(
+            emf["_aws"]["CloudWatchMetrics"],
+            json!([{
+                "Namespace": "Shop",
+                "Dimensions": [["Service"]],
+                "Metrics": [
+                    {"Name": "OrdersProcessed", "Unit": "Count"},
+                    {"Name": "Throughput", "Unit": "Bytes/Second"},
+                ],
+            }])
+        );
+
+        assert_eq!(emf["Service"], "checkout");
+        assert_eq!(emf["OrdersProcessed"], 1.0);
+        assert_eq!(emf["Throughput"], 2.5);
+        assert_eq!(emf.as_object().unwrap().len(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric or dimension Service is already defined")]
+    fn rejects_metric_named_like_dimension() {
+        Metrics::new("Shop")
+            .dimension("Service", "checkout")
+            .metric("Service", 1.0, Unit::Count);
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric or dimension Orders is already defined")]
+    fn rejects_dimension_named_like_metric() {
+        Metrics::new("Shop")
+            .metric("Orders", 1.0, Unit::Count)
+            .dimension("Orders", "all");
+    }
+
+    #[test]
+    #[should_panic(expected = "Metric or dimension _aws is already defined")]
+    fn rejects_metadata_name() {
+        Metrics::new("Shop").metric("_aws", 1.0, Unit::Count);
+    }
+}
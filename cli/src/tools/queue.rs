@@ -5,15 +5,23 @@ use eyre::OptionExt;
 use kinetics_parser::ParsedFunction;
 use lambda_runtime::LambdaEvent;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::{OnceCell, RwLock};
 
 #[derive(Clone)]
 pub struct Client {
     queue: SendMessageFluentBuilder,
+
+    /// FIFO queues require a message group for every message
+    fifo: bool,
 }
 
+/// Message group of messages sent without one, all of them are then processed in order
+const DEFAULT_MESSAGE_GROUP: &str = "default";
+
 static SQS_CLIENT_CACHE: OnceCell<Arc<RwLock<HashMap<String, Client>>>> = OnceCell::const_new();
 
 /// A queue client
@@ -21,17 +29,36 @@ static SQS_CLIENT_CACHE: OnceCell<Arc<RwLock<HashMap<String, Client>>>> = OnceCe
 /// Used to send items to the worker queue.
 impl Client {
     pub fn new(queue: SendMessageFluentBuilder) -> Self {
-        Client { queue }
+        Client { queue, fifo: false }
     }
 
     /// Send a message to the queue
     ///
+    /// Messages to a FIFO queue are all sent to the same group, and processed one after another.
     /// Return Ok(()) if operation succeeds
     pub async fn send(
         &self,
         message: impl ::std::convert::Into<::std::string::String>,
     ) -> eyre::Result<()> {
-        self.queue.clone().message_body(message).send().await?;
+        self.send_to_group(message, DEFAULT_MESSAGE_GROUP).await
+    }
+
+    /// Send a message to a message group of the queue
+    ///
+    /// Messages of a FIFO queue are ordered within their group, while different groups
+    /// are processed in parallel. The group is ignored by standard queues.
+    pub async fn send_to_group(
+        &self,
+        message: impl ::std::convert::Into<::std::string::String>,
+        group_id: &str,
+    ) -> eyre::Result<()> {
+        let mut request = self.queue.clone().message_body(message);
+
+        if self.fifo {
+            request = request.message_group_id(group_id);
+        }
+
+        request.send().await?;
         Ok(())
     }
 
@@ -58,12 +85,20 @@ impl Client {
             return Ok(client.clone());
         }
 
+        let (project_name, function_path) = cache_key
+            .split_once("::")
+            .ok_or_eyre("Failed to get the project name from a worker")?;
+
+        let worker_name = ParsedFunction::path_to_name(&function_path.replace("::", "/"));
+
+        // Local queue is always a standard one
+        let fifo = std::env::var("KINETICS_QUEUE_NAME").is_err()
+            && std::env::var("KINETICS_FIFO_QUEUES")
+                .is_ok_and(|queues| queues.split(',').any(|name| name == worker_name));
+
         let client = Client {
+            fifo,
             queue: {
-                let (project_name, function_path) = cache_key
-                    .split_once("::")
-                    .ok_or_eyre("Failed to get the project name from a worker")?;
-
                 let region = std::env::var("AWS_REGION").unwrap_or("us-east-1".to_string());
 
                 let queue_endpoint_url = std::env::var("KINETICS_QUEUE_ENDPOINT_URL")
@@ -83,12 +118,19 @@ impl Client {
                 // out of user and project names
                 let queue_name = std::env::var("KINETICS_QUEUE_NAME")
                     .or_else(|_| {
-                        Ok::<String, std::env::VarError>(resource_name(
+                        let name = resource_name(
                             &std::env::var("KINETICS_USERNAME")
                                 .expect("KINETICS_USERNAME is not set"),
                             project_name,
-                            &ParsedFunction::path_to_name(&function_path.replace("::", "/")),
-                        ))
+                            &worker_name,
+                        );
+
+                        // Names of FIFO queues must end with the suffix
+                        Ok::<String, std::env::VarError>(if fifo {
+                            format!("{name}.fifo")
+                        } else {
+                            name
+                        })
                     })
                     .expect("Queue name is not set");
 
@@ -139,6 +181,68 @@ impl Retries {
 
         sqs_batch_response
     }
+
+    /// Process the records one by one, and collect the failed ones to be retried
+    ///
+    /// Only the failed records are retried, the rest of the batch is deleted from the queue.
+    /// Records of a FIFO queue are ordered within a message group, so after a failure
+    /// the following records of the same group are retried without processing.
+    ///
+    /// ```no_run
+    /// # async fn example(records: Vec<kinetics::tools::queue::Record>) {
+    /// use kinetics::tools::queue::Retries;
+    ///
+    /// let retries = Retries::process(&records, |record| async move {
+    ///     let body = record.body.as_deref().ok_or("Empty body")?;
+    ///     println!("Processing {body}");
+    ///     Ok::<(), Box<dyn std::error::Error>>(())
+    /// })
+    /// .await;
+    /// # }
+    /// ```
+    pub async fn process<'a, F, Fut, E>(records: &'a [Record], mut handler: F) -> Self
+    where
+        F: FnMut(&'a Record) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: Debug,
+    {
+        let mut retries = Self::new();
+        let mut failed_groups = HashSet::new();
+
+        for record in records {
+            if let Some(group) = &record.message_group_id {
+                if failed_groups.contains(group) {
+                    retries.add_record(record);
+                    continue;
+                }
+            }
+
+            if let Err(err) = handler(record).await {
+                eprintln!(
+                    "Failed to process record {}: {err:?}",
+                    record.message_id.as_deref().unwrap_or("without id")
+                );
+
+                retries.add_record(record);
+
+                if let Some(group) = &record.message_group_id {
+                    failed_groups.insert(group.clone());
+                }
+            }
+        }
+
+        retries
+    }
+
+    /// Add the record to be retried
+    ///
+    /// An empty item identifier would fail the whole batch, so a record without id is only logged.
+    fn add_record(&mut self, record: &Record) {
+        match &record.message_id {
+            Some(id) => self.add(id),
+            None => eprintln!("Record without id can't be retried, skipping it"),
+        }
+    }
 }
 
 /// A record received from a queue
@@ -149,6 +253,10 @@ pub struct Record {
 
     #[serde(default)]
     pub body: Option<String>,
+
+    /// Group of the message, only set for FIFO queues
+    #[serde(default)]
+    pub message_group_id: Option<String>,
 }
 
 impl Record {
@@ -160,7 +268,105 @@ impl Record {
             .map(|r| Record {
                 message_id: r.message_id.clone(),
                 body: r.body.clone(),
+                message_group_id: r.attributes.get("MessageGroupId").cloned(),
             })
             .collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: Option<&str>, group: Option<&str>) -> Record {
+        Record {
+            message_id: id.map(str::to_owned),
+            body: id.map(str::to_owned),
+            message_group_id: group.map(str::to_owned),
+        }
+    }
+
+    /// Ids of the records to be retried, in the order they have been added
+    fn ids(retries: &Retries) -> Vec<String> {
+        retries
+            .collect()
+            .batch_item_failures
+            .into_iter()
+            .map(|item| item.item_identifier)
+            .collect()
+    }
+
+    /// Process the records, failing the ones listed in `failing`
+    async fn process(records: &[Record], failing: &[&str]) -> (Retries, Vec<String>) {
+        let mut processed = Vec::new();
+
+        let retries = Retries::process(records, |record| {
+            let body = record.body.clone().unwrap_or_default();
+            let is_failing = failing.contains(&body.as_str());
+            processed.push(body);
+            async move {
+                if is_failing {
+                    Err("failed")
+                } else {
+                    Ok(())
+                }
+            }
+        })
+        .await;
+
+        (retries, processed)
+    }
+
+    #[tokio::test]
+    async fn retries_only_failed_records() {
+        let records = [
+            record(Some("1"), None),
+            record(Some("2"), None),
+            record(Some("3"), None),
+            record(Some("4"), None),
+        ];
+
+        let (retries, processed) = process(&records, &["2", "4"]).await;
+        assert_eq!(processed, ["1", "2", "3", "4"]);
+        assert_eq!(ids(&retries), ["2", "4"]);
+    }
+
+    #[tokio::test]
+    async fn retries_nothing_on_success() {
+        let records = [record(Some("1"), None), record(Some("2"), None)];
+        let (retries, _) = process(&records, &[]).await;
+        assert!(ids(&retries).is_empty());
+    }
+
+    #[tokio::test]
+    async fn stops_fifo_group_after_failure() {
+        let records = [
+            record(Some("a1"), Some("a")),
+            record(Some("b1"), Some("b")),
+            record(Some("a2"), Some("a")),
+            record(Some("b2"), Some("b")),
+            record(Some("a3"), Some("a")),
+        ];
+
+        let (retries, processed) = process(&records, &["a1"]).await;
+
+        // The rest of group "a" is retried without processing, group "b" goes on
+        assert_eq!(processed, ["a1", "b1", "b2"]);
+        assert_eq!(ids(&retries), ["a1", "a2", "a3"]);
+    }
+
+    #[tokio::test]
+    async fn skips_failed_record_without_id() {
+        let records = [record(None, None), record(Some("2"), None)];
+
+        let retries = Retries::process(&records, |record| async move {
+            match record.message_id {
+                None => Err("failed"),
+                Some(_) => Ok(()),
+            }
+        })
+        .await;
+
+        assert!(ids(&retries).is_empty());
+    }
+}
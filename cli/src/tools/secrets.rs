@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// Time after which cached secrets are refetched, so that rotated ones are picked up
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// Secrets by their names, with the time they were fetched at
+type Fetched = (Instant, Arc<HashMap<String, String>>);
+
+/// Secrets fetched once and reused by the following invocations of a warm function
+///
+/// Concurrent reads wait for a single fetch. Once the TTL has passed, the next read
+/// refetches the secrets.
+pub struct Cache {
+    ttl: Duration,
+    secrets: Mutex<Option<Fetched>>,
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL)
+    }
+}
+
+impl Cache {
+    pub fn new(ttl: Duration) -> Self {
+        Cache {
+            ttl,
+            secrets: Mutex::new(None),
+        }
+    }
+
+    /// Cached secrets, fetched with `fetch` on the first read and once expired
+    ///
+    /// If refetching fails the expired secrets are returned, so that a temporary failure
+    /// doesn't fail the invocation. The next read tries to refetch them again.
+    pub async fn get<F, Fut, E>(&self, fetch: F) -> Result<Arc<HashMap<String, String>>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<HashMap<String, String>, E>>,
+        E: Debug,
+    {
+        let mut cached = self.secrets.lock().await;
+
+        if let Some((fetched_at, secrets)) = cached.as_ref() {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(secrets.clone());
+            }
+        }
+
+        match fetch().await {
+            Ok(secrets) => {
+                let secrets = Arc::new(secrets);
+                *cached = Some((Instant::now(), secrets.clone()));
+                Ok(secrets)
+            }
+            Err(err) => match cached.as_ref() {
+                Some((_, secrets)) => {
+                    eprintln!("Failed to refetch secrets, using the cached ones: {err:?}");
+                    Ok(secrets.clone())
+                }
+                None => Err(err),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Secrets with the number of the fetch as the value
+    async fn fetch(count: &AtomicUsize) -> Result<HashMap<String, String>, &'static str> {
+        let count = count.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::task::yield_now().await;
+        Ok(HashMap::from([("KEY".to_owned(), count.to_string())]))
+    }
+
+    #[tokio::test]
+    async fn fetches_once_for_concurrent_reads() {
+        let cache = Cache::default();
+        let count = AtomicUsize::new(0);
+
+        let reads = futures::future::join_all((0..10).map(|_| cache.get(|| fetch(&count)))).await;
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        for secrets in reads {
+            assert_eq!(secrets.unwrap()["KEY"], "1");
+        }
+    }
+
+    #[tokio::test]
+    async fn refetches_after_ttl() {
+        let cache = Cache::new(Duration::ZERO);
+        let count = AtomicUsize::new(0);
+
+        cache.get(|| fetch(&count)).await.unwrap();
+        let secrets = cache.get(|| fetch(&count)).await.unwrap();
+
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(secrets["KEY"], "2");
+    }
+
+    #[tokio::test]
+    async fn returns_stale_secrets_on_refetch_failure() {
+        let cache = Cache::new(Duration::ZERO);
+        let count = AtomicUsize::new(0);
+
+        cache.get(|| fetch(&count)).await.unwrap();
+
+        let secrets = cache
+            .get(|| async { Err::<HashMap<String, String>, _>("unavailable") })
+            .await
+            .unwrap();
+
+        assert_eq!(secrets["KEY"], "1");
+
+        // The next read tries again
+        let secrets = cache.get(|| fetch(&count)).await.unwrap();
+        assert_eq!(secrets["KEY"], "2");
+    }
+
+    #[tokio::test]
+    async fn fails_without_cached_secrets() {
+        let cache = Cache::default();
+
+        let result = cache
+            .get(|| async { Err::<HashMap<String, String>, _>("unavailable") })
+            .await;
+
+        assert_eq!(result.unwrap_err(), "unavailable");
+    }
+}
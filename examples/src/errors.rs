@@ -0,0 +1,39 @@
+use http::{Request, Response, StatusCode};
+use kinetics::tools::config::Config as KineticsConfig;
+use kinetics::tools::http::IntoResponse;
+use kinetics::{macros::endpoint, tools::http::Body};
+use serde_json::json;
+use std::collections::HashMap;
+use tower::BoxError;
+
+/// A domain error which is mapped to a specific HTTP response
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Result<Response<Body>, BoxError> {
+        let (status, message) = match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "Not found"),
+        };
+
+        Ok(Response::builder()
+            .status(status)
+            .header("content-type", "application/json")
+            .body(json!({"error": message}).to_string().into())?)
+    }
+}
+
+/// REST API endpoint which always responds with 404 and a JSON error
+///
+/// Test locally with the following command:
+/// kinetics invoke ErrorsErrors
+#[endpoint(url_path = "/errors")]
+pub async fn errors(
+    _event: Request<Body>,
+    _secrets: &HashMap<String, String>,
+    _config: &KineticsConfig,
+) -> Result<Response<String>, ApiError> {
+    Err(ApiError::NotFound)
+}
@@ -2,6 +2,7 @@ pub mod basic;
 pub mod database;
 pub mod datadog;
 pub mod environment;
+pub mod errors;
 pub mod init_once;
 pub mod queue;
 pub mod secrets;
@@ -1,47 +1,165 @@
 use kinetics_parser::{Cron, Endpoint, Worker};
 use proc_macro::TokenStream;
-use syn::parse_macro_input;
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, ItemFn, ReturnType, Type};
+
+/// Check that the macro is applied to an async function with the expected arguments
+///
+/// The error points at the function signature, so that rustc reports it
+/// at the user's code instead of a failure deep in the generated lambda.
+fn check_signature(function: &ItemFn, macro_name: &str, args: &[&str]) -> syn::Result<()> {
+    if function.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            function.sig.fn_token,
+            format!("`#[{macro_name}]` function must be async"),
+        ));
+    }
+
+    if function.sig.inputs.len() != args.len() {
+        return Err(syn::Error::new(
+            function.sig.paren_token.span.join(),
+            format!(
+                "`#[{macro_name}]` function must take {} arguments: {}",
+                args.len(),
+                args.join(", ")
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that the endpoint returns a `Result`
+///
+/// The types within are checked by the compiler against `IntoResponse`, which gives
+/// a clear message on its own.
+fn check_endpoint_return(function: &ItemFn) -> syn::Result<()> {
+    let error = |span| {
+        Err(syn::Error::new(
+            span,
+            "`#[endpoint]` function must return `Result<T, E>`, \
+            where both T and E implement `kinetics::tools::http::IntoResponse`",
+        ))
+    };
+
+    match &function.sig.output {
+        ReturnType::Default => error(function.sig.paren_token.span.join()),
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path)
+                if path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "Result") =>
+            {
+                Ok(())
+            }
+            ty => error(ty.span()),
+        },
+    }
+}
+
+/// Prepend a compile error to the function, if any
+///
+/// The function itself is kept, so that its usages do not produce additional errors.
+fn with_error(item: TokenStream, result: syn::Result<()>) -> TokenStream {
+    match result {
+        Ok(()) => item,
+        Err(error) => {
+            let mut output: TokenStream = error.to_compile_error().into();
+            output.extend(item);
+            output
+        }
+    }
+}
 
 /// Lambda endpoint
 ///
 /// Parameters:
 /// - `name`: override the function name
-/// - `url_path`: URL path of the endpoint
+/// - `description`: human-readable description, shown in `kinetics func list`
+/// - `url_path`: URL path of the endpoint, or a list of paths it responds at, `["/users", "/v1/users"]`
+/// - `methods`: allowed HTTP methods, `["GET", "POST"]`, all of them by default
 /// - `environment`: environment variables
+/// - `kvdb`: names of KV DB tables the function accesses, `["orders", ...]`
+/// - `secrets`: names of secrets the function reads, `["API_KEY", ...]`
+/// - `policies`: IAM statements added to the role, `[{ actions = ["ses:SendEmail"], resources = ["*"] }]`
+/// - `schema`: path to a JSON Schema file to validate request body against, relative to crate root
+/// - `queues`: names of worker functions the endpoint sends messages to
+/// - `headers`: headers added to every response, unless already set by the function
+/// - `jwt`: verify the Bearer token against the issuer's JWKS, `{ issuer = "...", audience = "...", leeway = 60 }`
+/// - `request_id`: read the request ID from `X-Request-Id` (`true`) or a custom header (`"X-Correlation-Id"`), generate it if missing, and echo on the response
+/// - `catch_all`: serve requests to paths not matched by any other endpoint, one per project
+/// - `streaming`: stream the response, the function returns `Response<kinetics::tools::http::StreamBody>`
+/// - `types`: request and response types to generate TypeScript definitions for, `["crate::api::User", ...]`
+/// - `memory`: memory of the function in MB (128..10240), 256 by default
+/// - `timeout`: run time in seconds (1..900), 1 by default
 #[proc_macro_attribute]
 pub fn endpoint(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse the macro attributes in order to validate the inputs,
     // then discard the result.
     let _args = parse_macro_input!(attr as Endpoint);
-    item
+    let input = item.clone();
+    let function = parse_macro_input!(input as ItemFn);
+
+    with_error(
+        item,
+        check_signature(&function, "endpoint", &["event", "secrets", "config"])
+            .and_then(|_| check_endpoint_return(&function)),
+    )
 }
 
 /// Cron lambda
 ///
 /// Parameters:
 /// - `name`: override the function name
+/// - `description`: human-readable description, shown in `kinetics func list`
 /// - `schedule`: [Schedule expression](https://docs.aws.amazon.com/AWSCloudFormation/latest/UserGuide/aws-resource-scheduler-schedule.html#cfn-scheduler-schedule-scheduleexpression)
+/// - `at`: RFC 3339 timestamp of a one-time run, instead of `schedule`
+/// - `max_retries`: times a failed run is retried (0..2)
 /// - `environment`: environment variables
+/// - `kvdb`: names of KV DB tables the function accesses, `["orders", ...]`
+/// - `secrets`: names of secrets the function reads, `["API_KEY", ...]`
+/// - `policies`: IAM statements added to the role, `[{ actions = ["ses:SendEmail"], resources = ["*"] }]`
+/// - `timeout`: run time in seconds (1..900), 3 by default
 #[proc_macro_attribute]
 pub fn cron(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse the macro attributes in order to validate the inputs,
     // then discard the result.
     let _args = parse_macro_input!(attr as Cron);
-    item
+    let input = item.clone();
+    let function = parse_macro_input!(input as ItemFn);
+
+    with_error(
+        item,
+        check_signature(&function, "cron", &["secrets", "config"]),
+    )
 }
 
 /// Worker lambda
 ///
 /// Parameters:
 /// - `name`: override the function name
+/// - `description`: human-readable description, shown in `kinetics func list`
 /// - `concurrency`: max number of concurrent workers
 /// - `fifo`: set to true to enable FIFO processing
 /// - `batch_size`: max number of records to process in a single batch (1..10)
+/// - `max_attempts`: times a message is received before it is considered failed (1..1000)
 /// - `environment`: environment variables
+/// - `kvdb`: names of KV DB tables the function accesses, `["orders", ...]`
+/// - `secrets`: names of secrets the function reads, `["API_KEY", ...]`
+/// - `policies`: IAM statements added to the role, `[{ actions = ["ses:SendEmail"], resources = ["*"] }]`
+/// - `timeout`: run time in seconds (1..900), 3 by default
 #[proc_macro_attribute]
 pub fn worker(attr: TokenStream, item: TokenStream) -> TokenStream {
     // Parse the macro attributes in order to validate the inputs,
     // then discard the result.s
     let _args = parse_macro_input!(attr as Worker);
-    item
+    let input = item.clone();
+    let function = parse_macro_input!(input as ItemFn);
+
+    with_error(
+        item,
+        check_signature(&function, "worker", &["records", "secrets", "config"]),
+    )
 }
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use syn::{
+    ext::IdentExt, punctuated::Punctuated, token, Attribute, Expr, ExprLit, Item, Lit, Meta,
+};
+
+/// Check whether an item is enabled with the given set of cargo features
+///
+/// Evaluates all `#[cfg(...)]` attributes of the item. Only `feature = "..."` predicates
+/// (and `test`, which is never set for a deployed build) can be resolved while parsing
+/// the source code, other predicates (e.g. `target_os`) are assumed to be satisfied.
+pub(crate) fn is_enabled(attrs: &[Attribute], features: &[String]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .filter_map(|attr| attr.parse_args::<Meta>().ok())
+        .all(|meta| eval(&meta, features) != Some(false))
+}
+
+/// Paths of file modules declared in the file and disabled with the given set of cargo features
+///
+/// E.g. `src/beta` for `#[cfg(feature = "beta")] mod beta;` in `src/lib.rs`, the module is either
+/// `src/beta.rs` or `src/beta/mod.rs`, and its submodules are in `src/beta/` as well.
+pub(crate) fn disabled_modules(
+    relative_path: &Path,
+    items: &[Item],
+    features: &[String],
+) -> Vec<PathBuf> {
+    let parent = relative_path.parent().unwrap_or(Path::new(""));
+    let stem = relative_path.file_stem().and_then(|stem| stem.to_str());
+
+    // Submodules of `mod.rs` and crate roots are next to them, the rest are in a dir of the name
+    let dir = match stem {
+        Some("mod") => parent.to_path_buf(),
+        Some("lib" | "main") if parent == Path::new("src") => parent.to_path_buf(),
+        Some(stem) => parent.join(stem),
+        None => return vec![],
+    };
+
+    let mut disabled = vec![];
+    collect_disabled(&dir, items, features, &mut disabled);
+    disabled
+}
+
+fn collect_disabled(dir: &Path, items: &[Item], features: &[String], disabled: &mut Vec<PathBuf>) {
+    for item in items {
+        let Item::Mod(module) = item else {
+            continue;
+        };
+
+        let path = dir.join(module.ident.unraw().to_string());
+
+        if !is_enabled(&module.attrs, features) {
+            disabled.push(path);
+        } else if let Some((_, items)) = &module.content {
+            // File modules declared in an inline one are in its dir
+            collect_disabled(&path, items, features, disabled);
+        }
+    }
+}
+
+/// Evaluate a single cfg predicate
+///
+/// Returns None if the predicate can not be resolved.
+fn eval(meta: &Meta, features: &[String]) -> Option<bool> {
+    match meta {
+        Meta::Path(path) if path.is_ident("test") => Some(false),
+        Meta::Path(_) => None,
+
+        Meta::NameValue(name_value) if name_value.path.is_ident("feature") => {
+            match &name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(feature),
+                    ..
+                }) => Some(features.contains(&feature.value())),
+                _ => None,
+            }
+        }
+
+        Meta::NameValue(_) => None,
+
+        Meta::List(list) => {
+            let nested = list
+                .parse_args_with(Punctuated::<Meta, token::Comma>::parse_terminated)
+                .ok()?;
+
+            let results = nested
+                .iter()
+                .map(|meta| eval(meta, features))
+                .collect::<Vec<_>>();
+
+            if list.path.is_ident("not") {
+                results.first().copied().flatten().map(|result| !result)
+            } else if list.path.is_ident("all") {
+                if results.contains(&Some(false)) {
+                    Some(false)
+                } else if results.iter().all(Option::is_some) {
+                    Some(true)
+                } else {
+                    None
+                }
+            } else if list.path.is_ident("any") {
+                if results.contains(&Some(true)) {
+                    Some(true)
+                } else if results.iter().all(|result| *result == Some(false)) {
+                    Some(false)
+                } else {
+                    None
+                }
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn is_item_enabled(item: &str, enabled: &[&str]) -> bool {
+        let item = syn::parse_str::<Item>(item).unwrap();
+
+        let Item::Fn(item) = item else {
+            panic!("Not a function");
+        };
+
+        is_enabled(&item.attrs, &features(enabled))
+    }
+
+    #[test]
+    fn evaluates_feature_predicates() {
+        let item = r#"#[cfg(feature = "beta")] fn f() {}"#;
+        assert!(is_item_enabled(item, &["beta"]));
+        assert!(!is_item_enabled(item, &[]));
+
+        let item = r#"#[cfg(not(feature = "beta"))] fn f() {}"#;
+        assert!(!is_item_enabled(item, &["beta"]));
+        assert!(is_item_enabled(item, &[]));
+
+        let item = r#"#[cfg(all(feature = "a", feature = "b"))] fn f() {}"#;
+        assert!(is_item_enabled(item, &["a", "b"]));
+        assert!(!is_item_enabled(item, &["a"]));
+
+        let item = r#"#[cfg(any(feature = "a", feature = "b"))] fn f() {}"#;
+        assert!(is_item_enabled(item, &["b"]));
+        assert!(!is_item_enabled(item, &[]));
+
+        assert!(!is_item_enabled("#[cfg(test)] fn f() {}", &[]));
+    }
+
+    #[test]
+    fn assumes_unknown_predicates_satisfied() {
+        assert!(is_item_enabled(
+            r#"#[cfg(target_os = "linux")] fn f() {}"#,
+            &[]
+        ));
+        assert!(is_item_enabled(r#"#[cfg(not(unix))] fn f() {}"#, &[]));
+
+        // Resolved part of the predicate still decides
+        let item = r#"#[cfg(all(unix, feature = "beta"))] fn f() {}"#;
+        assert!(!is_item_enabled(item, &[]));
+    }
+
+    #[test]
+    fn finds_disabled_file_modules() {
+        let file = syn::parse_file(
+            r#"
+                #[cfg(feature = "beta")]
+                mod beta;
+                mod api;
+
+                mod inline {
+                    #[cfg(not(feature = "beta"))]
+                    mod legacy;
+                }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            disabled_modules(Path::new("src/lib.rs"), &file.items, &[]),
+            [PathBuf::from("src/beta")]
+        );
+
+        assert_eq!(
+            disabled_modules(Path::new("src/api.rs"), &file.items, &features(&["beta"])),
+            [PathBuf::from("src/api/inline/legacy")]
+        );
+
+        assert_eq!(
+            disabled_modules(Path::new("src/api/mod.rs"), &file.items, &[]),
+            [PathBuf::from("src/api/beta")]
+        );
+    }
+}
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     token, LitStr,
 };
 
-pub(crate) type Environment = HashMap<String, String>;
+pub(crate) type Environment = BTreeMap<String, String>;
 
 /// Helper struct to parse environment variables in function attributes
 /// It is used to parse individual environment attribute from environment = {"FOO": "BAR", "BAZ": "QUX"}}
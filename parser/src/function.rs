@@ -32,6 +32,11 @@ pub struct ParsedFunction {
     /// Path to the file where function is defined
     pub relative_path: String,
 
+    /// Inline modules the function is nested in within the file, outermost first
+    ///
+    /// E.g. `["v1"]` for a function inside `mod v1 { ... }`.
+    pub inline_modules: Vec<String>,
+
     /// The kind of function (endpoint, cron, or worker), without parameters
     pub role: Role,
 
@@ -59,7 +64,12 @@ impl ParsedFunction {
     /// function name requirements.
     pub fn func_name(&self, is_local: bool) -> eyre::Result<String> {
         let rust_name = &self.rust_function_name;
-        let full_path = format!("{}/{rust_name}", self.relative_path);
+        let full_path = [self.relative_path.as_str()]
+            .into_iter()
+            .chain(self.inline_modules.iter().map(String::as_str))
+            .chain([rust_name.as_str()])
+            .collect::<Vec<_>>()
+            .join("/");
         let default_func_name = Self::path_to_name(&full_path);
         let name = self.params.name().unwrap_or(&default_func_name);
 
@@ -69,7 +79,6 @@ impl ParsedFunction {
                 name
             ))
         } else {
-            // TODO Check the name for uniqueness
             Ok(format!("{}{}", name, if is_local { "Local" } else { "" }))
         }
     }
@@ -1,3 +1,4 @@
+mod cfg;
 mod environment;
 mod function;
 pub mod params;
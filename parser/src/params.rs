@@ -1,18 +1,53 @@
 pub mod cron;
 pub mod endpoint;
+pub mod policy;
 pub mod worker;
 
 pub use cron::Cron;
 pub use endpoint::Endpoint;
+pub use policy::Policy;
 pub use worker::Worker;
 
 use crate::environment::Environment;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
+use syn::{bracketed, parse::ParseStream, punctuated::Punctuated, token, Ident, LitInt, LitStr};
+
+/// Max run time of a Lambda function in seconds
+const MAX_TIMEOUT: u32 = 900;
+
+/// Parse a list of resource names with a comma delimiter, like: ["orders", ...]
+pub(crate) fn parse_names(input: ParseStream) -> syn::Result<Vec<String>> {
+    let content;
+    bracketed!(content in input);
+
+    Ok(
+        Punctuated::<LitStr, token::Comma>::parse_terminated(&content)?
+            .iter()
+            .map(LitStr::value)
+            .collect(),
+    )
+}
+
+/// Parse the `timeout` attribute, the run time of a function in seconds
+pub(crate) fn parse_timeout(input: ParseStream, ident: &Ident) -> syn::Result<u32> {
+    let parsed = input.parse::<LitInt>()?.base10_parse::<u32>()?;
+
+    if !(1..=MAX_TIMEOUT).contains(&parsed) {
+        return Err(syn::Error::new(
+            ident.span(),
+            format!("Timeout must be between 1 and {MAX_TIMEOUT} seconds"),
+        ));
+    }
+
+    Ok(parsed)
+}
 
 /// The workload-specific parameters
 ///
 /// Parsed from the kinetics macro attribute.
+// Params are created once per function on build, boxing is not worth the indirection
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Params {
     Endpoint(Endpoint),
@@ -41,6 +76,32 @@ impl Params {
         }
     }
 
+    pub fn description(&self) -> Option<&String> {
+        match self {
+            Params::Endpoint(params) => params.description.as_ref(),
+            Params::Cron(params) => params.description.as_ref(),
+            Params::Worker(params) => params.description.as_ref(),
+        }
+    }
+
+    /// Names of KV DB tables the function accesses
+    pub fn kvdb(&self) -> &[String] {
+        match self {
+            Params::Endpoint(params) => &params.kvdb,
+            Params::Cron(params) => &params.kvdb,
+            Params::Worker(params) => &params.kvdb,
+        }
+    }
+
+    /// Custom IAM policy statements of the function
+    pub fn policies(&self) -> &[Policy] {
+        match self {
+            Params::Endpoint(params) => &params.policies,
+            Params::Cron(params) => &params.policies,
+            Params::Worker(params) => &params.policies,
+        }
+    }
+
     pub fn environment(&self) -> &Environment {
         match self {
             Params::Endpoint(params) => &params.environment,
@@ -1,22 +1,70 @@
 use crate::environment::{parse_environment, Environment};
+use crate::params::{parse_names, parse_timeout, Policy};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use syn::{
     parse::{Parse, ParseStream},
-    token, Ident, LitStr,
+    token, Ident, LitInt, LitStr,
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cron {
     pub name: Option<String>,
+
+    /// Human-readable description, shown in listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Schedule expression, e.g. `rate(1 hour)`
+    ///
+    /// A one-time run set with `at` attribute is encoded as `at(yyyy-mm-ddThh:mm:ss)` in UTC,
+    /// the expression the scheduler accepts for it.
     pub schedule: String,
+
     pub environment: Environment,
+
+    /// Times a failed run is retried, `MaximumRetryAttempts` of the async invocation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+
+    /// Run time of the function in seconds, after which the run fails
+    #[serde(default = "Cron::default_timeout")]
+    pub timeout: u32,
+
+    /// Names of KV DB tables the function accesses, only these are granted to it when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kvdb: Vec<String>,
+
+    /// Names of secrets the function reads, only these are granted to it when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+
+    /// IAM statements added to the function's role, e.g. to call AWS services directly
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<Policy>,
+}
+
+impl Cron {
+    /// Run time of the function in seconds, unless set with `timeout`
+    const DEFAULT_TIMEOUT: u32 = 3;
+
+    fn default_timeout() -> u32 {
+        Self::DEFAULT_TIMEOUT
+    }
 }
 
 impl Parse for Cron {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut name = None;
+        let mut description = None;
         let mut environment = None;
         let mut schedule = None;
+        let mut at = None;
+        let mut max_retries = None;
+        let mut timeout = None;
+        let mut kvdb = vec![];
+        let mut secrets = vec![];
+        let mut policies = vec![];
 
         while !input.is_empty() {
             let ident_span = input.span();
@@ -30,6 +78,15 @@ impl Parse for Cron {
                     }
                     name = Some(input.parse::<LitStr>()?.value());
                 }
+                "description" => {
+                    if description.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `description`",
+                        ));
+                    }
+                    description = Some(input.parse::<LitStr>()?.value());
+                }
                 "environment" => {
                     if environment.is_some() {
                         return Err(syn::Error::new(
@@ -48,6 +105,76 @@ impl Parse for Cron {
                     }
                     schedule = Some(input.parse::<LitStr>()?.value());
                 }
+                "at" => {
+                    if at.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `at`"));
+                    }
+
+                    let value = input.parse::<LitStr>()?;
+
+                    // Scheduler expects the time without offset, convert it to UTC
+                    let time = DateTime::parse_from_rfc3339(&value.value()).map_err(|e| {
+                        syn::Error::new(
+                            value.span(),
+                            format!(
+                                "Invalid `at` timestamp, expected RFC 3339 \
+                                (e.g. 2025-01-01T00:00:00Z): {e}"
+                            ),
+                        )
+                    })?;
+
+                    at = Some(format!(
+                        "at({})",
+                        time.with_timezone(&Utc).format("%Y-%m-%dT%H:%M:%S")
+                    ));
+                }
+                "max_retries" => {
+                    if max_retries.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `max_retries`",
+                        ));
+                    }
+
+                    let parsed = input.parse::<LitInt>()?.base10_parse::<u32>()?;
+
+                    // The limit of Lambda async invocations
+                    if parsed > 2 {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Max retries must be an integer between 0 and 2",
+                        ));
+                    }
+
+                    max_retries = Some(parsed);
+                }
+                "kvdb" => {
+                    if !kvdb.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `kvdb`"));
+                    }
+                    kvdb = parse_names(input)?;
+                }
+                "policies" => {
+                    if !policies.is_empty() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `policies`",
+                        ));
+                    }
+                    policies = Policy::parse_list(input)?;
+                }
+                "secrets" => {
+                    if !secrets.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `secrets`"));
+                    }
+                    secrets = parse_names(input)?;
+                }
+                "timeout" => {
+                    if timeout.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `timeout`"));
+                    }
+                    timeout = Some(parse_timeout(input, &ident)?);
+                }
                 // Ignore unknown attributes
                 _ => {}
             }
@@ -57,11 +184,48 @@ impl Parse for Cron {
             }
         }
 
+        let schedule = match (schedule, at) {
+            (Some(_), Some(_)) => {
+                return Err(input
+                    .error("Cron validation failed: `schedule` and `at` are mutually exclusive"))
+            }
+            (Some(schedule), None) | (None, Some(schedule)) => schedule,
+            (None, None) => return Err(input.error("Cron validation failed: no schedule provided")),
+        };
+
         Ok(Cron {
             name,
+            description,
             environment: environment.unwrap_or_default(),
-            schedule: schedule
-                .ok_or_else(|| input.error("Cron validation failed: no schedule provided"))?,
+            schedule,
+            max_retries,
+            timeout: timeout.unwrap_or(Self::DEFAULT_TIMEOUT),
+            kvdb,
+            secrets,
+            policies,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_one_time_run_as_schedule() {
+        let cron: Cron = syn::parse_str(r#"at = "2025-01-01T02:00:00+02:00""#).unwrap();
+        assert_eq!(cron.schedule, "at(2025-01-01T00:00:00)");
+
+        let json = serde_json::to_value(&cron).unwrap();
+        assert_eq!(json["schedule"], "at(2025-01-01T00:00:00)");
+        assert!(json.get("at").is_none());
+    }
+
+    #[test]
+    fn rejects_schedule_with_at() {
+        let result =
+            syn::parse_str::<Cron>(r#"schedule = "rate(1 hour)", at = "2025-01-01T00:00:00Z""#);
+
+        assert!(result.is_err());
+    }
+}
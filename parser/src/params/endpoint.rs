@@ -1,11 +1,13 @@
 use crate::environment::{parse_environment, Environment};
-use http::Method;
+use crate::params::{parse_names, parse_timeout, Policy};
+use http::{HeaderName, HeaderValue, Method};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use syn::{
-    bracketed,
+    braced, bracketed,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
-    token, Ident, LitBool, LitStr,
+    token, Ident, LitBool, LitInt, LitStr,
 };
 
 const ALLOWED_METHODS: [Method; 5] = [
@@ -19,19 +21,205 @@ const ALLOWED_METHODS: [Method; 5] = [
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Endpoint {
     pub name: Option<String>,
+
+    /// Human-readable description, shown in listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
     pub url_path: String,
+
+    /// Other URL paths the endpoint responds at, e.g. `/v1/users` for `/users`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub url_path_aliases: Vec<String>,
+
     pub environment: Environment,
     pub is_disabled: Option<bool>,
     pub methods: Vec<String>,
+
+    /// Path to a JSON Schema file (relative to the crate root) to validate request body against
+    pub schema: Option<String>,
+
+    /// Names of worker functions the endpoint sends messages to
+    #[serde(default)]
+    pub queues: Vec<String>,
+
+    /// Headers added to every response, unless the handler sets them itself
+    #[serde(default)]
+    pub response_headers: BTreeMap<String, String>,
+
+    /// Verify a Bearer JWT of every request, requests without a valid token are rejected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jwt: Option<Jwt>,
+
+    /// Lowercase name of the header with the request ID, read from the request and echoed
+    /// on the response. A new UUID is generated when the request has no such header.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+
+    /// Serve requests to paths not matched by any endpoint of the project
+    #[serde(default)]
+    pub catch_all: bool,
+
+    /// Stream the response to the caller as it is produced, instead of buffering it
+    #[serde(default)]
+    pub streaming: bool,
+
+    /// Paths of request and response types, e.g. `crate::api::CreateUser`,
+    /// to generate TypeScript definitions for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+
+    /// Memory of the function in MB, CPU is allocated proportionally
+    #[serde(default = "Endpoint::default_memory")]
+    pub memory: u32,
+
+    /// Run time of the function in seconds, after which the request fails
+    #[serde(default = "Endpoint::default_timeout")]
+    pub timeout: u32,
+
+    /// Names of KV DB tables the function accesses, only these are granted to it when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kvdb: Vec<String>,
+
+    /// Names of secrets the function reads, only these are granted to it when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+
+    /// IAM statements added to the function's role, e.g. to call AWS services directly
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<Policy>,
+}
+
+/// JWT verification settings
+///
+/// The signing keys are fetched from the issuer's JWKS, found by OpenID discovery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Jwt {
+    /// Expected `iss` claim, e.g. `https://example.auth0.com/`
+    pub issuer: String,
+
+    /// Expected `aud` claim, not checked if omitted
+    pub audience: Option<String>,
+
+    /// Tolerated clock skew in seconds, when checking `exp` and `nbf` claims
+    pub leeway: u64,
+}
+
+impl Jwt {
+    const DEFAULT_LEEWAY: u64 = 60;
+}
+
+impl Parse for Jwt {
+    /// Parse settings in braces, like: { issuer = "...", audience = "..." }
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut issuer = None;
+        let mut audience = None;
+        let mut leeway = None;
+
+        while !content.is_empty() {
+            let ident_span = content.span();
+            let ident: Ident = content.parse()?;
+            content.parse::<token::Eq>()?;
+
+            match ident.to_string().as_str() {
+                "issuer" => {
+                    if issuer.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `issuer`"));
+                    }
+                    issuer = Some(content.parse::<LitStr>()?.value());
+                }
+                "audience" => {
+                    if audience.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `audience`",
+                        ));
+                    }
+                    audience = Some(content.parse::<LitStr>()?.value());
+                }
+                "leeway" => {
+                    if leeway.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `leeway`"));
+                    }
+                    leeway = Some(content.parse::<LitInt>()?.base10_parse::<u64>()?);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident_span,
+                        format!("Unknown `jwt` attribute `{ident}`"),
+                    ))
+                }
+            }
+
+            if !content.is_empty() {
+                content.parse::<token::Comma>()?;
+            }
+        }
+
+        let issuer =
+            issuer.ok_or_else(|| content.error("Missing required `jwt` attribute `issuer`"))?;
+
+        if !issuer.starts_with("https://") {
+            return Err(content.error("`jwt` issuer must be an https:// URL"));
+        }
+
+        Ok(Jwt {
+            issuer,
+            audience,
+            leeway: leeway.unwrap_or(Self::DEFAULT_LEEWAY),
+        })
+    }
+}
+
+impl Endpoint {
+    /// Header name used with `request_id = true`
+    const DEFAULT_REQUEST_ID_HEADER: &str = "x-request-id";
+
+    /// Memory of the function in MB, unless set with `memory`
+    const DEFAULT_MEMORY: u32 = 256;
+
+    /// All URL paths of the endpoint, the main one first
+    pub fn url_paths(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.url_path.as_str())
+            .chain(self.url_path_aliases.iter().map(String::as_str))
+    }
+
+    fn default_memory() -> u32 {
+        Self::DEFAULT_MEMORY
+    }
+
+    /// Run time of the function in seconds, unless set with `timeout`
+    const DEFAULT_TIMEOUT: u32 = 1;
+
+    fn default_timeout() -> u32 {
+        Self::DEFAULT_TIMEOUT
+    }
 }
 
 impl Parse for Endpoint {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut name = None;
+        let mut description = None;
         let mut url_path = None;
         let mut environment = None;
         let mut is_disabled = None;
         let mut methods = vec![];
+        let mut schema = None;
+        let mut queues = vec![];
+        let mut response_headers = None;
+        let mut jwt = None;
+        let mut request_id = None;
+        let mut catch_all = None;
+        let mut streaming = None;
+        let mut types = vec![];
+        let mut memory = None;
+        let mut timeout = None;
+        let mut kvdb = vec![];
+        let mut secrets = vec![];
+        let mut policies = vec![];
 
         while !input.is_empty() {
             let ident_span = input.span();
@@ -45,6 +233,15 @@ impl Parse for Endpoint {
                     }
                     name = Some(input.parse::<LitStr>()?.value());
                 }
+                "description" => {
+                    if description.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `description`",
+                        ));
+                    }
+                    description = Some(input.parse::<LitStr>()?.value());
+                }
                 "url_path" => {
                     if url_path.is_some() {
                         return Err(syn::Error::new(
@@ -52,7 +249,31 @@ impl Parse for Endpoint {
                             "Duplicate attribute `url_path`",
                         ));
                     }
-                    url_path = Some(input.parse::<LitStr>()?.value());
+
+                    // Either a single path, or a list of them, like: ["/users", "/v1/users"]
+                    let paths = if input.peek(token::Bracket) {
+                        parse_names(input)?
+                    } else {
+                        vec![input.parse::<LitStr>()?.value()]
+                    };
+
+                    if paths.is_empty() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "At least one path is required in `url_path`",
+                        ));
+                    }
+
+                    for (i, path) in paths.iter().enumerate() {
+                        if paths[..i].contains(path) {
+                            return Err(syn::Error::new(
+                                ident_span,
+                                format!("Duplicate path in `url_path`: {path}"),
+                            ));
+                        }
+                    }
+
+                    url_path = Some(paths);
                 }
                 "environment" => {
                     if environment.is_some() {
@@ -72,6 +293,173 @@ impl Parse for Endpoint {
                     }
                     is_disabled = Some(input.parse::<LitBool>()?.value());
                 }
+                "schema" => {
+                    if schema.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `schema`"));
+                    }
+                    schema = Some(input.parse::<LitStr>()?.value());
+                }
+                "headers" => {
+                    if response_headers.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `headers`"));
+                    }
+
+                    let headers = parse_environment(input)?;
+
+                    for (name, value) in headers.iter() {
+                        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                            return Err(syn::Error::new(
+                                ident_span,
+                                format!("Invalid header name: {name}"),
+                            ));
+                        }
+
+                        if HeaderValue::from_str(value).is_err() {
+                            return Err(syn::Error::new(
+                                ident_span,
+                                format!("Invalid value of header {name}: {value}"),
+                            ));
+                        }
+                    }
+
+                    // Header names are case-insensitive, keep them lowercase as in HTTP/2
+                    response_headers = Some(
+                        headers
+                            .into_iter()
+                            .map(|(name, value)| (name.to_lowercase(), value))
+                            .collect(),
+                    );
+                }
+                "jwt" => {
+                    if jwt.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `jwt`"));
+                    }
+                    jwt = Some(input.parse::<Jwt>()?);
+                }
+                "request_id" => {
+                    if request_id.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `request_id`",
+                        ));
+                    }
+
+                    // Either a flag enabling the default header, or a custom header name
+                    request_id = Some(if input.peek(LitBool) {
+                        input
+                            .parse::<LitBool>()?
+                            .value()
+                            .then(|| Self::DEFAULT_REQUEST_ID_HEADER.to_owned())
+                    } else {
+                        let name = input.parse::<LitStr>()?.value();
+
+                        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+                            return Err(syn::Error::new(
+                                ident_span,
+                                format!("Invalid header name: {name}"),
+                            ));
+                        }
+
+                        Some(name.to_lowercase())
+                    });
+                }
+                "catch_all" => {
+                    if catch_all.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `catch_all`",
+                        ));
+                    }
+                    catch_all = Some(input.parse::<LitBool>()?.value());
+                }
+                "streaming" => {
+                    if streaming.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `streaming`",
+                        ));
+                    }
+                    streaming = Some(input.parse::<LitBool>()?.value());
+                }
+                "types" => {
+                    if !types.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `types`"));
+                    }
+
+                    // A single path, or a list of them, like: ["crate::api::Request", ...]
+                    let paths = if input.peek(token::Bracket) {
+                        let content;
+                        bracketed!(content in input);
+
+                        Punctuated::<LitStr, token::Comma>::parse_terminated(&content)?
+                            .into_iter()
+                            .collect::<Vec<_>>()
+                    } else {
+                        vec![input.parse::<LitStr>()?]
+                    };
+
+                    for path in paths {
+                        if path.parse::<syn::Path>().is_err() {
+                            return Err(syn::Error::new(
+                                path.span(),
+                                format!("Invalid type path: {}", path.value()),
+                            ));
+                        }
+
+                        types.push(path.value());
+                    }
+                }
+                "memory" => {
+                    if memory.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `memory`"));
+                    }
+
+                    let parsed = input.parse::<LitInt>()?.base10_parse::<u32>()?;
+
+                    if !(128..=10240).contains(&parsed) {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Memory must be between 128 and 10240 MB",
+                        ));
+                    }
+
+                    memory = Some(parsed);
+                }
+                "timeout" => {
+                    if timeout.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `timeout`"));
+                    }
+                    timeout = Some(parse_timeout(input, &ident)?);
+                }
+                "kvdb" => {
+                    if !kvdb.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `kvdb`"));
+                    }
+                    kvdb = parse_names(input)?;
+                }
+                "policies" => {
+                    if !policies.is_empty() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `policies`",
+                        ));
+                    }
+                    policies = Policy::parse_list(input)?;
+                }
+                "secrets" => {
+                    if !secrets.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `secrets`"));
+                    }
+                    secrets = parse_names(input)?;
+                }
+                "queues" => {
+                    if !queues.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `queues`"));
+                    }
+
+                    // Names of workers, like: ["MyWorker", ...]
+                    queues = parse_names(input)?;
+                }
                 "methods" => {
                     if !methods.is_empty() {
                         return Err(syn::Error::new(ident_span, "Duplicate attribute `methods`"));
@@ -124,13 +512,31 @@ impl Parse for Endpoint {
             }
         }
 
+        // The first path is the main one, e.g. the one called by `kinetics invoke`
+        let mut url_path =
+            url_path.ok_or_else(|| input.error("Missing required attribute `url_path`"))?;
+
         Ok(Endpoint {
             name,
-            url_path: url_path
-                .ok_or_else(|| input.error("Missing required attribute `url_path`"))?,
+            description,
+            url_path: url_path.remove(0),
+            url_path_aliases: url_path,
             environment: environment.unwrap_or_default(),
             methods,
             is_disabled,
+            schema,
+            queues,
+            response_headers: response_headers.unwrap_or_default(),
+            jwt,
+            request_id: request_id.flatten(),
+            catch_all: catch_all.unwrap_or_default(),
+            streaming: streaming.unwrap_or_default(),
+            types,
+            memory: memory.unwrap_or(Self::DEFAULT_MEMORY),
+            timeout: timeout.unwrap_or(Self::DEFAULT_TIMEOUT),
+            kvdb,
+            secrets,
+            policies,
         })
     }
 }
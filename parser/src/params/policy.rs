@@ -0,0 +1,87 @@
+use crate::params::parse_names;
+use serde::{Deserialize, Serialize};
+use syn::{
+    braced, bracketed,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token, Ident,
+};
+
+/// IAM policy statement added to the function's role
+///
+/// Grants access to AWS services not managed by kinetics, e.g. SES or Bedrock.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Policy {
+    /// Allowed actions, e.g. `ses:SendEmail`
+    pub actions: Vec<String>,
+
+    /// ARNs of resources the actions are allowed on, `*` for all of them
+    pub resources: Vec<String>,
+}
+
+impl Policy {
+    /// Parse a list of statements, like: [{ actions = [...], resources = [...] }, ...]
+    pub(crate) fn parse_list(input: ParseStream) -> syn::Result<Vec<Self>> {
+        let content;
+        bracketed!(content in input);
+
+        Ok(
+            Punctuated::<Policy, token::Comma>::parse_terminated(&content)?
+                .into_iter()
+                .collect(),
+        )
+    }
+}
+
+impl Parse for Policy {
+    /// Parse a statement in braces, like: { actions = ["ses:SendEmail"], resources = ["*"] }
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        braced!(content in input);
+
+        let mut actions = None;
+        let mut resources = None;
+
+        while !content.is_empty() {
+            let ident_span = content.span();
+            let ident: Ident = content.parse()?;
+            content.parse::<token::Eq>()?;
+
+            match ident.to_string().as_str() {
+                "actions" => {
+                    if actions.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `actions`"));
+                    }
+                    actions = Some(parse_names(&content)?);
+                }
+                "resources" => {
+                    if resources.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `resources`",
+                        ));
+                    }
+                    resources = Some(parse_names(&content)?);
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        ident_span,
+                        format!("Unknown `policies` attribute `{ident}`"),
+                    ))
+                }
+            }
+
+            if !content.is_empty() {
+                content.parse::<token::Comma>()?;
+            }
+        }
+
+        Ok(Policy {
+            actions: actions
+                .ok_or_else(|| content.error("Missing required `policies` attribute `actions`"))?,
+            resources: resources.ok_or_else(|| {
+                content.error("Missing required `policies` attribute `resources`")
+            })?,
+        })
+    }
+}
@@ -1,4 +1,5 @@
 use crate::environment::{parse_environment, Environment};
+use crate::params::{parse_names, parse_timeout, Policy};
 use serde::{Deserialize, Serialize};
 use syn::{
     parse::{Parse, ParseStream},
@@ -8,19 +9,59 @@ use syn::{
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Worker {
     pub name: Option<String>,
+
+    /// Human-readable description, shown in listings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
     pub concurrency: u32,
     pub fifo: bool,
     pub environment: Environment,
     pub batch_size: Option<u32>,
+
+    /// Times a message is received before it is considered failed, `maxReceiveCount` of the queue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_attempts: Option<u32>,
+
+    /// Run time of the function in seconds, after which the batch is considered failed
+    #[serde(default = "Worker::default_timeout")]
+    pub timeout: u32,
+
+    /// Names of KV DB tables the function accesses, only these are granted to it when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kvdb: Vec<String>,
+
+    /// Names of secrets the function reads, only these are granted to it when set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+
+    /// IAM statements added to the function's role, e.g. to call AWS services directly
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policies: Vec<Policy>,
+}
+
+impl Worker {
+    /// Run time of the function in seconds, unless set with `timeout`
+    const DEFAULT_TIMEOUT: u32 = 3;
+
+    fn default_timeout() -> u32 {
+        Self::DEFAULT_TIMEOUT
+    }
 }
 
 impl Parse for Worker {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         let mut name = None;
+        let mut description = None;
         let mut concurrency = None;
         let mut fifo = None;
         let mut environment = None;
         let mut batch_size = None;
+        let mut max_attempts = None;
+        let mut timeout = None;
+        let mut kvdb = vec![];
+        let mut secrets = vec![];
+        let mut policies = vec![];
 
         while !input.is_empty() {
             let ident_span = input.span();
@@ -34,6 +75,15 @@ impl Parse for Worker {
                     }
                     name = Some(input.parse::<LitStr>()?.value());
                 }
+                "description" => {
+                    if description.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `description`",
+                        ));
+                    }
+                    description = Some(input.parse::<LitStr>()?.value());
+                }
                 "environment" => {
                     if environment.is_some() {
                         return Err(syn::Error::new(
@@ -81,6 +131,52 @@ impl Parse for Worker {
 
                     batch_size = Some(parsed);
                 }
+                "max_attempts" => {
+                    if max_attempts.is_some() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `max_attempts`",
+                        ));
+                    }
+
+                    let parsed = input.parse::<LitInt>()?.base10_parse::<u32>()?;
+
+                    if !(1..=1000).contains(&parsed) {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Max attempts must be a positive integer between 1 and 1000",
+                        ));
+                    }
+
+                    max_attempts = Some(parsed);
+                }
+                "kvdb" => {
+                    if !kvdb.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `kvdb`"));
+                    }
+                    kvdb = parse_names(input)?;
+                }
+                "policies" => {
+                    if !policies.is_empty() {
+                        return Err(syn::Error::new(
+                            ident_span,
+                            "Duplicate attribute `policies`",
+                        ));
+                    }
+                    policies = Policy::parse_list(input)?;
+                }
+                "secrets" => {
+                    if !secrets.is_empty() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `secrets`"));
+                    }
+                    secrets = parse_names(input)?;
+                }
+                "timeout" => {
+                    if timeout.is_some() {
+                        return Err(syn::Error::new(ident_span, "Duplicate attribute `timeout`"));
+                    }
+                    timeout = Some(parse_timeout(input, &ident)?);
+                }
                 // Ignore unknown attributes
                 _ => {}
             }
@@ -92,10 +188,16 @@ impl Parse for Worker {
 
         Ok(Self {
             name,
+            description,
             concurrency: concurrency.unwrap_or(1),
             fifo: fifo.unwrap_or_default(),
             environment: environment.unwrap_or_default(),
             batch_size,
+            max_attempts,
+            timeout: timeout.unwrap_or(Self::DEFAULT_TIMEOUT),
+            kvdb,
+            secrets,
+            policies,
         })
     }
 }
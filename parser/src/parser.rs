@@ -1,9 +1,9 @@
 use crate::{
-    ParsedFunction, Role, params::{Cron, Endpoint, Params, Worker}
+    cfg, ParsedFunction, Role, params::{Cron, Endpoint, Params, Worker}
 };
 use color_eyre::eyre;
 use std::path::PathBuf;
-use syn::{parse::Parse, visit::Visit, Attribute, ItemFn};
+use syn::{parse::Parse, visit::Visit, Attribute, ItemFn, ItemMod};
 use walkdir::WalkDir;
 
 #[derive(Debug, Default)]
@@ -13,14 +13,26 @@ pub struct Parser {
 
     /// Relative path to currently processing file
     pub relative_path: String,
+
+    /// Enabled cargo features, functions gated behind disabled features are skipped
+    pub features: Vec<String>,
+
+    /// Inline modules enclosing the currently visited item, outermost first
+    inline_modules: Vec<String>,
+
+    /// Errors of macro attributes parsing in the currently processing file
+    errors: Vec<String>,
 }
 
 impl Parser {
     /// Init new Parser
     ///
-    /// And optionally parse the requested dir
-    pub fn new(path: Option<&PathBuf>) -> eyre::Result<Self> {
-        let mut parser: Parser = Default::default();
+    /// And optionally parse the requested dir, with the given set of cargo features enabled
+    pub fn new(path: Option<&PathBuf>, features: &[String]) -> eyre::Result<Self> {
+        let mut parser = Parser {
+            features: features.to_vec(),
+            ..Default::default()
+        };
 
         if let Some(path) = path {
             parser.walk_dir(path)?;
@@ -30,7 +42,11 @@ impl Parser {
     }
 
     pub fn walk_dir(&mut self, path: &PathBuf) -> eyre::Result<()> {
+        let mut files = vec![];
+
+        // Sorted, so that functions are found in the same order on every machine
         for entry in WalkDir::new(path)
+            .sort_by_file_name()
             .into_iter()
             .filter_map(|e| e.ok())
             .filter(|e| {
@@ -40,12 +56,40 @@ impl Parser {
         {
             let content = std::fs::read_to_string(entry.path())?;
             let syntax = syn::parse_file(&content)?;
+            files.push((entry.path().strip_prefix(path)?.to_path_buf(), syntax));
+        }
+
+        // Modules disabled for the current set of features, e.g. `#[cfg(feature = "beta")] mod beta;`
+        let disabled = files
+            .iter()
+            .flat_map(|(relative_path, syntax)| {
+                cfg::disabled_modules(relative_path, &syntax.items, &self.features)
+            })
+            .collect::<Vec<_>>();
+
+        for (relative_path, syntax) in files {
+            // Both `beta.rs` and everything in `beta/` belong to the module
+            if disabled
+                .iter()
+                .any(|module| relative_path.with_extension("").starts_with(module))
+            {
+                continue;
+            }
 
             // Set current file relative path for further imports resolution
             // WARN It prevents to implement parallel parsing of files and requires rework in the future
-            self.set_relative_path(entry.path().strip_prefix(path)?.to_str());
+            self.set_relative_path(relative_path.to_str());
 
             self.visit_file(&syntax);
+
+            if !self.errors.is_empty() {
+                let messages = self.errors.drain(..).collect::<Vec<_>>().join("\n");
+
+                return Err(eyre::eyre!(
+                    "Invalid kinetics macro in {}:\n{messages}",
+                    self.relative_path
+                ));
+            }
         }
 
         Ok(())
@@ -88,34 +132,161 @@ impl Parser {
 }
 
 impl Visit<'_> for Parser {
+    /// Visits inline modules, unless disabled with a cfg attribute
+    ///
+    /// Functions inside are imported through the modules, so the nesting is tracked.
+    fn visit_item_mod(&mut self, item: &ItemMod) {
+        if cfg::is_enabled(&item.attrs, &self.features) {
+            self.inline_modules.push(item.ident.to_string());
+            syn::visit::visit_item_mod(self, item);
+            self.inline_modules.pop();
+        }
+    }
+
     /// Visits function definitions
     fn visit_item_fn(&mut self, item: &ItemFn) {
+        // Skip functions disabled for the current set of features, e.g. #[cfg(feature = "beta")]
+        if !cfg::is_enabled(&item.attrs, &self.features) {
+            return;
+        }
+
         for attr in &item.attrs {
             // Skip non-endpoint or non-worker attributes
-            let (role, params) = match self.parse_attr_role(attr).as_str() {
-                "endpoint" => {
-                    let params = self.parse_endpoint(attr).unwrap();
-                    (Role::Endpoint, Params::Endpoint(params))
-                }
-                "worker" => {
-                    let params = self.parse_worker(attr).unwrap();
-                    (Role::Worker, Params::Worker(params))
-                }
-                "cron" => {
-                    let params = self.parse_cron(attr).unwrap();
-                    (Role::Cron, Params::Cron(params))
-                }
+            let parsed = match self.parse_attr_role(attr).as_str() {
+                "endpoint" => self
+                    .parse_endpoint(attr)
+                    .map(|params| (Role::Endpoint, Params::Endpoint(params))),
+                "worker" => self
+                    .parse_worker(attr)
+                    .map(|params| (Role::Worker, Params::Worker(params))),
+                "cron" => self
+                    .parse_cron(attr)
+                    .map(|params| (Role::Cron, Params::Cron(params))),
                 _ => continue,
             };
 
+            // Collect the error and keep visiting, so that all invalid attributes are reported at once
+            let (role, params) = match parsed {
+                Ok(parsed) => parsed,
+                Err(error) => {
+                    self.errors
+                        .push(format!("function `{}`: {error}", item.sig.ident));
+                    continue;
+                }
+            };
+
             self.functions.push(ParsedFunction {
                 role,
                 params,
                 rust_function_name: item.sig.ident.to_string(),
                 relative_path: self.relative_path.clone(),
+                inline_modules: self.inline_modules.clone(),
             });
         }
 
         // We don't need to parse the function body (in case nested functions), so just exit here
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Parse a crate made of the given files
+    fn parse_functions(files: &[(&str, &str)], features: &[&str]) -> Vec<ParsedFunction> {
+        // Tests run in parallel, each crate gets its own dir
+        static CRATES: AtomicUsize = AtomicUsize::new(0);
+
+        let path = std::env::temp_dir().join(format!(
+            "kinetics-parser-{}-{}",
+            std::process::id(),
+            CRATES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        for (file, content) in files {
+            let file = path.join(file);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
+        }
+
+        let features = features.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        let parser = Parser::new(Some(&path), &features);
+        fs::remove_dir_all(&path).unwrap();
+
+        parser.unwrap().functions
+    }
+
+    /// Parse a crate with an endpoint in each of the given files
+    fn parse(files: &[(&str, &str)], features: &[&str]) -> Vec<String> {
+        parse_functions(files, features)
+            .into_iter()
+            .map(|function| function.rust_function_name)
+            .collect()
+    }
+
+    #[test]
+    fn skips_files_of_disabled_modules() {
+        let files = [
+            (
+                "src/lib.rs",
+                "#[cfg(feature = \"beta\")]\nmod beta;\nmod api;\n",
+            ),
+            (
+                "src/api.rs",
+                "#[endpoint(url_path = \"/api\")]\nfn api() {}\n",
+            ),
+            (
+                "src/beta.rs",
+                "mod nested;\n#[endpoint(url_path = \"/beta\")]\nfn beta() {}\n",
+            ),
+            (
+                "src/beta/nested.rs",
+                "#[endpoint(url_path = \"/nested\")]\nfn nested() {}\n",
+            ),
+        ];
+
+        assert_eq!(parse(&files, &[]), ["api"]);
+        // Files are walked by name, `beta/` goes before `beta.rs`
+        assert_eq!(parse(&files, &["beta"]), ["api", "nested", "beta"]);
+    }
+
+    #[test]
+    fn tracks_inline_modules() {
+        let content = r#"
+            #[endpoint(url_path = "/hello")]
+            fn hello() {}
+
+            mod v1 {
+                #[endpoint(url_path = "/v1/hello")]
+                fn hello() {}
+
+                pub mod admin {
+                    #[endpoint(url_path = "/v1/admin/hello")]
+                    fn hello() {}
+                }
+            }
+
+            #[endpoint(url_path = "/bye")]
+            fn bye() {}
+        "#;
+
+        let files = [("src/lib.rs", "mod api;"), ("src/api.rs", content)];
+
+        let functions = parse_functions(&files, &[])
+            .into_iter()
+            .map(|f| (f.inline_modules.join("::"), f.func_name(false).unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            functions,
+            [
+                ("".to_string(), "ApiHello".to_string()),
+                ("v1".to_string(), "ApiV1Hello".to_string()),
+                ("v1::admin".to_string(), "ApiV1AdminHello".to_string()),
+                ("".to_string(), "ApiBye".to_string()),
+            ]
+        );
+    }
+}